@@ -0,0 +1,228 @@
+// Parsers for USB descriptors. Everything below only uses `core` (and
+// `alloc` where a descriptor owns a variable number of children) so it can be
+// reused in a `no_std` context.
+pub mod audio;
+pub mod bos;
+pub mod cdc;
+pub mod configuration;
+pub mod device;
+pub mod dfu;
+pub mod hid;
+pub mod hub;
+pub mod mass_storage;
+pub mod ms_os;
+pub mod string;
+pub mod vendor;
+pub mod video;
+pub mod webusb;
+
+use core::fmt;
+
+pub mod descriptor_type {
+    pub const DEVICE: u8 = 0x01;
+    pub const CONFIGURATION: u8 = 0x02;
+    pub const STRING: u8 = 0x03;
+    pub const INTERFACE: u8 = 0x04;
+    pub const ENDPOINT: u8 = 0x05;
+    pub const DEVICE_QUALIFIER: u8 = 0x06;
+    pub const OTHER_SPEED_CONFIGURATION: u8 = 0x07;
+    pub const INTERFACE_POWER: u8 = 0x08;
+    pub const OTG: u8 = 0x09;
+    pub const DEBUG: u8 = 0x0A;
+    pub const INTERFACE_ASSOCIATION: u8 = 0x0B;
+    pub const BOS: u8 = 0x0F;
+    pub const DEVICE_CAPABILITY: u8 = 0x10;
+    pub const HUB: u8 = 0x29;
+    pub const SUPERSPEED_HUB: u8 = 0x2A;
+    pub const SUPERSPEED_ENDPOINT_COMPANION: u8 = 0x30;
+    pub const SUPERSPEEDPLUS_ISOCHRONOUS_ENDPOINT_COMPANION: u8 = 0x31;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Error {
+    pub offset: usize, // byte offset of the offending descriptor or field
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    // fewer bytes available than the descriptor needs
+    Truncated { needed: usize, available: usize },
+    // bLength does not fit the descriptor
+    InvalidLength { length: u8 },
+    // bDescriptorType is not the one being parsed
+    UnexpectedType { expected: u8, found: u8 },
+    // a field holds a value the specification does not allow
+    InvalidValue { field: &'static str, value: u32 },
+    // a descriptor of this type may not appear at this position
+    Misplaced { descriptor_type: u8 },
+    // a count field disagrees with the number of descriptors actually present
+    CountMismatch { field: &'static str, declared: u32, found: usize },
+    // an item that opens or closes a scope without its counterpart
+    Unbalanced { item: &'static str },
+}
+
+impl Error {
+    pub fn new(offset: usize, kind: ErrorKind) -> Self {
+        Self { offset, kind }
+    }
+
+    // the same error, `base` bytes further into an enclosing buffer
+    pub fn at(self, base: usize) -> Self {
+        Self { offset: self.offset + base, ..self }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ErrorKind::Truncated { needed, available } => write!(
+                f, "truncated at offset {}: need {} bytes, {} available", self.offset, needed, available
+            ),
+            ErrorKind::InvalidLength { length } => write!(
+                f, "invalid bLength {} at offset {}", length, self.offset
+            ),
+            ErrorKind::UnexpectedType { expected, found } => write!(
+                f, "expected descriptor type {:#04x}, found {:#04x} at offset {}", expected, found, self.offset
+            ),
+            ErrorKind::InvalidValue { field, value } => write!(
+                f, "invalid {} {:#x} at offset {}", field, value, self.offset
+            ),
+            ErrorKind::Misplaced { descriptor_type } => write!(
+                f, "unexpected descriptor type {:#04x} at offset {}", descriptor_type, self.offset
+            ),
+            ErrorKind::CountMismatch { field, declared, found } => write!(
+                f, "{} is {} but {} found, at offset {}", field, declared, found, self.offset
+            ),
+            ErrorKind::Unbalanced { item } => write!(
+                f, "unbalanced {} at offset {}", item, self.offset
+            ),
+        }
+    }
+}
+
+// binary coded decimal version number, e.g. bcdUSB 0x0210 is 2.10
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bcd(pub u16);
+
+impl Bcd {
+    pub fn major(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    pub fn minor(self) -> u8 {
+        self.0 as u8
+    }
+}
+
+impl fmt::Display for Bcd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:x}.{:02x}", self.major(), self.minor())
+    }
+}
+
+// 128 bit UUID stored like a Windows GUID: the first three fields little endian
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Uuid(pub [u8; 16]);
+
+impl Uuid {
+    // `bytes` must be exactly 16 bytes long
+    pub fn from_slice(bytes: &[u8]) -> Self {
+        let mut uuid = [0; 16];
+        uuid.copy_from_slice(bytes);
+        Uuid(uuid)
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let b = &self.0;
+        write!(
+            f, "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+            u32_at(b, 0), u16_at(b, 4), u16_at(b, 6), b[8], b[9], b[10], b[11], b[12], b[13], b[14], b[15]
+        )
+    }
+}
+
+// check that `bytes` holds a descriptor of `descriptor_type` at least `min_length` long
+// and return it cut to its bLength
+pub fn header(bytes: &[u8], descriptor_type: u8, min_length: usize) -> Result<&[u8], Error> {
+    if bytes.len() < 2 {
+        return Err(Error::new(0, ErrorKind::Truncated { needed: 2, available: bytes.len() }))
+    }
+    let length = bytes[0];
+    if bytes[1] != descriptor_type {
+        return Err(Error::new(1, ErrorKind::UnexpectedType { expected: descriptor_type, found: bytes[1] }))
+    }
+    if usize::from(length) < min_length {
+        return Err(Error::new(0, ErrorKind::InvalidLength { length }))
+    }
+    if bytes.len() < usize::from(length) {
+        return Err(Error::new(0, ErrorKind::Truncated { needed: usize::from(length), available: bytes.len() }))
+    }
+    Ok(&bytes[..usize::from(length)])
+}
+
+pub(crate) fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+pub(crate) fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+// base class names from the USB-IF defined class codes
+pub fn class_name(class: u8) -> Option<&'static str> {
+    Some(match class {
+        0x00 => "Defined at Interface level",
+        0x01 => "Audio",
+        0x02 => "Communications and CDC Control",
+        0x03 => "HID",
+        0x05 => "Physical",
+        0x06 => "Image",
+        0x07 => "Printer",
+        0x08 => "Mass Storage",
+        0x09 => "Hub",
+        0x0A => "CDC-Data",
+        0x0B => "Smart Card",
+        0x0D => "Content Security",
+        0x0E => "Video",
+        0x0F => "Personal Healthcare",
+        0x10 => "Audio/Video Devices",
+        0x11 => "Billboard",
+        0x12 => "USB Type-C Bridge",
+        0x13 => "USB Bulk Display Protocol",
+        0x14 => "MCTP over USB",
+        0x3C => "I3C",
+        0xDC => "Diagnostic Device",
+        0xE0 => "Wireless Controller",
+        0xEF => "Miscellaneous",
+        0xFE => "Application Specific",
+        0xFF => "Vendor Specific",
+        _ => return None,
+    })
+}
+
+// short names of common functions by class, subclass and protocol, falling
+// back to the base class name
+pub fn function_name(class: u8, subclass: u8, protocol: u8) -> Option<&'static str> {
+    Some(match (class, subclass, protocol) {
+        (0x01, _, _) => "Audio",
+        (0x02, 0x02, _) => "CDC-ACM",
+        (0x02, 0x06, _) => "CDC-ECM",
+        (0x02, 0x0A, _) => "CDC-WMC",
+        (0x02, 0x0D, _) => "CDC-NCM",
+        (0x02, 0x0E, _) => "CDC-MBIM",
+        (0x02, 0x0C, _) => "CDC-EEM",
+        (0x08, _, 0x62) => "Mass Storage (UAS)",
+        (0x08, _, 0x50) => "Mass Storage (BOT)",
+        (0x0E, _, _) => "Video",
+        (0x10, _, _) => "Audio/Video",
+        (0xE0, 0x01, 0x03) => "RNDIS",
+        (0xEF, 0x04, 0x01) => "RNDIS",
+        (0xFE, 0x01, _) => "DFU",
+        (0xFE, 0x02, _) => "IrDA Bridge",
+        (0xFE, 0x03, _) => "USBTMC",
+        _ => return class_name(class),
+    })
+}
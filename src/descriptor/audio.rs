@@ -0,0 +1,678 @@
+use super::{
+    cdc::CS_INTERFACE,
+    configuration::{AlternateSetting, Configuration},
+    u16_at, u32_at, Bcd, Error, ErrorKind,
+};
+use alloc::vec::Vec;
+
+// USB Audio Class 1.0, 2.0 and 3.0. The AudioControl interface carries the
+// terminals, units and clock entities; following `sources` from an output
+// terminal back to input terminals gives the signal flow.
+
+pub const SUBCLASS_AUDIO_CONTROL: u8 = 0x01;
+pub const SUBCLASS_AUDIO_STREAMING: u8 = 0x02;
+
+// the RANGE request and the sampling frequency control of a clock source,
+// numbered alike in UAC2 and UAC3
+pub const REQUEST_RANGE: u8 = 0x02;
+pub const CS_SAM_FREQ_CONTROL: u8 = 0x01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AudioVersion {
+    Uac1,
+    Uac2,
+    Uac3,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioControl {
+    pub version: AudioVersion,
+    pub adc_version: Option<Bcd>, // UAC1 and UAC2 headers only
+    pub category: Option<u8>, // UAC2 and UAC3 headers only
+    pub streaming_interfaces: Vec<u8>, // UAC1 baInterfaceNr
+    pub entities: Vec<Entity>,
+    pub problems: Vec<Error>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entity {
+    pub offset: usize,
+    pub id: u8,
+    pub kind: EntityKind,
+    pub sources: Vec<u8>, // entities feeding audio into this one
+    pub clock_sources: Vec<u8>, // clock entities this one runs from (UAC2 and UAC3)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityKind {
+    InputTerminal { terminal_type: u16, associated_terminal: u8, channels: Option<u8> },
+    OutputTerminal { terminal_type: u16, associated_terminal: u8 },
+    MixerUnit,
+    SelectorUnit,
+    FeatureUnit { controls: Vec<u32> }, // master channel first
+    EffectUnit { effect_type: u16 },
+    ProcessingUnit { process_type: u16 },
+    ExtensionUnit { extension_code: u16 },
+    ClockSource { attributes: u8 },
+    ClockSelector,
+    ClockMultiplier,
+    SampleRateConverter,
+}
+
+// what an AudioStreaming alternate setting carries, merged from its general
+// and format type descriptors
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AudioStreaming {
+    pub terminal_link: Option<u8>,
+    pub format_tag: Option<u16>, // UAC1 wFormatTag
+    pub formats: Option<u64>, // UAC2 and UAC3 bmFormats
+    pub format_type: Option<u8>,
+    pub channels: Option<u8>, // UAC3 keeps these in a cluster descriptor fetched by class request
+    pub subslot_size: Option<u8>,
+    pub bit_resolution: Option<u8>,
+    pub sample_rates: Option<SampleRates>, // UAC2 and UAC3 ask the clock source instead
+    pub problems: Vec<Error>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SampleRates {
+    Discrete(Vec<u32>),
+    Continuous { min: u32, max: u32 },
+    Ranges(Vec<SampleRateRange>), // a clock source answering RANGE with real ranges
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleRateRange {
+    pub min: u32,
+    pub max: u32,
+    pub resolution: u32,
+}
+
+impl AudioVersion {
+    // from bInterfaceProtocol of an audio interface
+    pub fn from_protocol(protocol: u8) -> Option<Self> {
+        match protocol {
+            0x00 => Some(AudioVersion::Uac1),
+            0x20 => Some(AudioVersion::Uac2),
+            0x30 => Some(AudioVersion::Uac3),
+            _ => None,
+        }
+    }
+}
+
+impl AudioControl {
+    // `setting` must be an AudioControl interface of a known protocol
+    pub fn parse(setting: &AlternateSetting) -> Option<Self> {
+        let version = AudioVersion::from_protocol(setting.interface_protocol)?;
+        let mut control = AudioControl {
+            version,
+            adc_version: None,
+            category: None,
+            streaming_interfaces: Vec::new(),
+            entities: Vec::new(),
+            problems: Vec::new(),
+        };
+        for raw in setting.extra.iter().filter(|raw| raw.descriptor_type() == CS_INTERFACE) {
+            match control.parse_descriptor(&raw.bytes) {
+                Ok(Some(entity)) => control.entities.push(Entity { offset: raw.offset, ..entity }),
+                Ok(None) => {},
+                Err(err) => control.problems.push(err.at(raw.offset)),
+            }
+        }
+        control.check_references();
+        Some(control)
+    }
+
+    fn parse_descriptor(&mut self, bytes: &[u8]) -> Result<Option<Entity>, Error> {
+        let need = |length: usize| {
+            if bytes.len() < length {
+                Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+            } else {
+                Ok(())
+            }
+        };
+        // bNrInPins at `count`, followed by that many IDs
+        let pins = |count: usize| -> Result<Vec<u8>, Error> {
+            need(count + 1)?;
+            let pins = usize::from(bytes[count]);
+            need(count + 1 + pins)?;
+            Ok(bytes[count + 1..count + 1 + pins].to_vec())
+        };
+        need(3)?;
+        let subtype = bytes[2];
+        // the header is the only descriptor that is not an entity
+        if subtype == 0x01 {
+            match self.version {
+                AudioVersion::Uac1 => {
+                    need(8)?;
+                    self.adc_version = Some(Bcd(u16_at(bytes, 3)));
+                    self.streaming_interfaces = pins(7)?;
+                },
+                AudioVersion::Uac2 => {
+                    need(9)?;
+                    self.adc_version = Some(Bcd(u16_at(bytes, 3)));
+                    self.category = Some(bytes[5]);
+                },
+                AudioVersion::Uac3 => {
+                    need(10)?;
+                    self.category = Some(bytes[3]);
+                },
+            }
+            return Ok(None)
+        }
+        let kind = match (self.version, subtype) {
+            (_, 0x02) => {
+                need(match self.version { AudioVersion::Uac1 => 12, AudioVersion::Uac2 => 17, AudioVersion::Uac3 => 20 })?;
+                EntityKind::InputTerminal {
+                    terminal_type: u16_at(bytes, 4),
+                    associated_terminal: bytes[6],
+                    channels: match self.version {
+                        AudioVersion::Uac1 => Some(bytes[7]),
+                        AudioVersion::Uac2 => Some(bytes[8]),
+                        AudioVersion::Uac3 => None,
+                    },
+                }
+            },
+            (_, 0x03) => {
+                need(match self.version { AudioVersion::Uac1 => 9, AudioVersion::Uac2 => 12, AudioVersion::Uac3 => 19 })?;
+                EntityKind::OutputTerminal { terminal_type: u16_at(bytes, 4), associated_terminal: bytes[6] }
+            },
+            (AudioVersion::Uac1, 0x04) | (AudioVersion::Uac2, 0x04) | (AudioVersion::Uac3, 0x05) => EntityKind::MixerUnit,
+            (AudioVersion::Uac1, 0x05) | (AudioVersion::Uac2, 0x05) | (AudioVersion::Uac3, 0x06) => EntityKind::SelectorUnit,
+            (AudioVersion::Uac1, 0x06) => {
+                need(7)?;
+                let size = usize::from(bytes[5]).max(1);
+                let controls = bytes[6..bytes.len() - 1].chunks_exact(size)
+                    .map(|chunk| chunk.iter().rev().fold(0, |value, &byte| value << 8 | u32::from(byte)))
+                    .collect();
+                EntityKind::FeatureUnit { controls }
+            },
+            (AudioVersion::Uac2, 0x06) | (AudioVersion::Uac3, 0x07) => {
+                // UAC2 ends in iFeature, UAC3 in wFeatureDescrStr
+                let trailer = if self.version == AudioVersion::Uac2 { 1 } else { 2 };
+                need(5 + 4 + trailer)?;
+                let controls = bytes[5..bytes.len() - trailer].chunks_exact(4).map(|chunk| u32_at(chunk, 0)).collect();
+                EntityKind::FeatureUnit { controls }
+            },
+            (AudioVersion::Uac2, 0x07) | (AudioVersion::Uac3, 0x08) => {
+                need(7)?;
+                EntityKind::EffectUnit { effect_type: u16_at(bytes, 4) }
+            },
+            (AudioVersion::Uac1, 0x07) | (AudioVersion::Uac2, 0x08) | (AudioVersion::Uac3, 0x09) => {
+                need(7)?;
+                EntityKind::ProcessingUnit { process_type: u16_at(bytes, 4) }
+            },
+            (AudioVersion::Uac1, 0x08) | (AudioVersion::Uac2, 0x09) | (AudioVersion::Uac3, 0x0A) => {
+                need(7)?;
+                EntityKind::ExtensionUnit { extension_code: u16_at(bytes, 4) }
+            },
+            (AudioVersion::Uac2, 0x0A) | (AudioVersion::Uac3, 0x0B) => {
+                need(if self.version == AudioVersion::Uac2 { 8 } else { 12 })?;
+                EntityKind::ClockSource { attributes: bytes[4] }
+            },
+            (AudioVersion::Uac2, 0x0B) | (AudioVersion::Uac3, 0x0C) => EntityKind::ClockSelector,
+            (AudioVersion::Uac2, 0x0C) | (AudioVersion::Uac3, 0x0D) => EntityKind::ClockMultiplier,
+            (AudioVersion::Uac2, 0x0D) | (AudioVersion::Uac3, 0x0E) => EntityKind::SampleRateConverter,
+            // UAC3 extended terminals, connectors and power domains are no entities of the graph
+            _ => return Ok(None),
+        };
+        let (sources, clock_sources) = match kind {
+            EntityKind::InputTerminal { .. } if self.version != AudioVersion::Uac1 => (Vec::new(), alloc::vec![bytes[7]]),
+            EntityKind::InputTerminal { .. } => (Vec::new(), Vec::new()),
+            EntityKind::OutputTerminal { .. } if self.version != AudioVersion::Uac1 => {
+                (alloc::vec![bytes[7]], alloc::vec![bytes[8]])
+            },
+            EntityKind::OutputTerminal { .. } => (alloc::vec![bytes[7]], Vec::new()),
+            EntityKind::MixerUnit | EntityKind::SelectorUnit => (pins(4)?, Vec::new()),
+            EntityKind::FeatureUnit { .. } => (alloc::vec![bytes[4]], Vec::new()),
+            EntityKind::EffectUnit { .. } => (alloc::vec![bytes[6]], Vec::new()),
+            EntityKind::ProcessingUnit { .. } | EntityKind::ExtensionUnit { .. } => (pins(6)?, Vec::new()),
+            EntityKind::ClockSource { .. } => (Vec::new(), Vec::new()),
+            EntityKind::ClockSelector => (Vec::new(), pins(4)?),
+            EntityKind::ClockMultiplier => { need(5)?; (Vec::new(), alloc::vec![bytes[4]]) },
+            EntityKind::SampleRateConverter => { need(7)?; (alloc::vec![bytes[4]], alloc::vec![bytes[5], bytes[6]]) },
+        };
+        Ok(Some(Entity { offset: 0, id: bytes[3], kind, sources, clock_sources }))
+    }
+
+    // every ID must be unique and every source must name an existing entity
+    fn check_references(&mut self) {
+        for (index, entity) in self.entities.iter().enumerate() {
+            if self.entities[..index].iter().any(|other| other.id == entity.id) {
+                self.problems.push(Error::new(entity.offset + 3, ErrorKind::InvalidValue {
+                    field: "duplicate entity ID",
+                    value: entity.id.into(),
+                }));
+            }
+            let references = entity.sources.iter().map(|id| ("bSourceID", id))
+                .chain(entity.clock_sources.iter().map(|id| ("bCSourceID", id)));
+            for (field, &id) in references {
+                if self.entity(id).is_none() {
+                    self.problems.push(Error::new(entity.offset, ErrorKind::InvalidValue { field, value: id.into() }));
+                }
+            }
+        }
+        self.problems.sort_by_key(|problem| problem.offset);
+    }
+
+    pub fn entity(&self, id: u8) -> Option<&Entity> {
+        self.entities.iter().find(|entity| entity.id == id)
+    }
+
+    // every path from an input terminal to an output terminal, as entity IDs
+    // in signal order
+    pub fn signal_paths(&self) -> Vec<Vec<u8>> {
+        const MAX_PATHS: usize = 64;
+        let mut paths = Vec::new();
+        for output in self.entities.iter().filter(|entity| matches!(entity.kind, EntityKind::OutputTerminal { .. })) {
+            let mut stack = alloc::vec![alloc::vec![output.id]];
+            while let Some(path) = stack.pop() {
+                if paths.len() == MAX_PATHS {
+                    return paths;
+                }
+                let head = match self.entity(path[path.len() - 1]) {
+                    Some(head) => head,
+                    None => continue,
+                };
+                if head.sources.is_empty() {
+                    paths.push(path.iter().rev().copied().collect());
+                    continue;
+                }
+                for &source in head.sources.iter().rev() {
+                    // a loop in the graph ends the path
+                    if !path.contains(&source) {
+                        let mut longer = path.clone();
+                        longer.push(source);
+                        stack.push(longer);
+                    }
+                }
+            }
+        }
+        paths
+    }
+
+    // the clock source, selector or multiplier driving `id`, if any
+    pub fn clock_of(&self, id: u8) -> Option<u8> {
+        self.entity(id).and_then(|entity| entity.clock_sources.first().copied())
+    }
+
+    // the clock source whose sampling frequencies `id` runs at, through the
+    // first input of any clock selectors. None behind a multiplier, which
+    // changes the frequency.
+    pub fn clock_source_of(&self, id: u8) -> Option<u8> {
+        let mut clock = self.clock_of(id)?;
+        for _ in 0..self.entities.len() {
+            let entity = self.entity(clock)?;
+            match entity.kind {
+                EntityKind::ClockSource { .. } => return Some(clock),
+                EntityKind::ClockSelector => clock = *entity.clock_sources.first()?,
+                _ => return None,
+            }
+        }
+        None
+    }
+}
+
+impl AudioStreaming {
+    pub fn parse(setting: &AlternateSetting) -> Option<Self> {
+        let version = AudioVersion::from_protocol(setting.interface_protocol)?;
+        let mut streaming = AudioStreaming::default();
+        for raw in setting.extra.iter().filter(|raw| raw.descriptor_type() == CS_INTERFACE) {
+            if let Err(err) = streaming.parse_descriptor(version, &raw.bytes) {
+                streaming.problems.push(err.at(raw.offset));
+            }
+        }
+        Some(streaming)
+    }
+
+    fn parse_descriptor(&mut self, version: AudioVersion, bytes: &[u8]) -> Result<(), Error> {
+        let need = |length: usize| {
+            if bytes.len() < length {
+                Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+            } else {
+                Ok(())
+            }
+        };
+        need(3)?;
+        match (version, bytes[2]) {
+            // AS_GENERAL
+            (AudioVersion::Uac1, 0x01) => {
+                need(7)?;
+                self.terminal_link = Some(bytes[3]);
+                self.format_tag = Some(u16_at(bytes, 5));
+            },
+            (AudioVersion::Uac2, 0x01) => {
+                need(16)?;
+                self.terminal_link = Some(bytes[3]);
+                self.format_type = Some(bytes[5]);
+                self.formats = Some(u32_at(bytes, 6).into());
+                self.channels = Some(bytes[10]);
+            },
+            (AudioVersion::Uac3, 0x01) => {
+                need(23)?;
+                self.terminal_link = Some(bytes[3]);
+                self.formats = Some(u64::from(u32_at(bytes, 10)) | u64::from(u32_at(bytes, 14)) << 32);
+                self.subslot_size = Some(bytes[18]);
+                self.bit_resolution = Some(bytes[19]);
+            },
+            // FORMAT_TYPE
+            (AudioVersion::Uac1, 0x02) => {
+                need(8)?;
+                self.format_type = Some(bytes[3]);
+                let rates_at = match bytes[3] {
+                    // type II: wMaxBitRate and wSamplesPerFrame instead of the sample layout
+                    0x02 => { need(9)?; 8 },
+                    _ => {
+                        self.channels = Some(bytes[4]);
+                        self.subslot_size = Some(bytes[5]);
+                        self.bit_resolution = Some(bytes[6]);
+                        7
+                    },
+                };
+                self.sample_rates = Some(sample_rates(bytes, rates_at)?);
+            },
+            (AudioVersion::Uac2, 0x02) => {
+                need(4)?;
+                self.format_type = Some(bytes[3]);
+                if bytes[3] == 0x01 || bytes[3] == 0x03 {
+                    need(6)?;
+                    self.subslot_size = Some(bytes[4]);
+                    self.bit_resolution = Some(bytes[5]);
+                }
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+}
+
+// bSamFreqType at `at` followed by 3 byte frequencies; 0 means a continuous range
+fn sample_rates(bytes: &[u8], at: usize) -> Result<SampleRates, Error> {
+    let count = usize::from(bytes[at]);
+    let needed = at + 1 + 3 * count.max(2);
+    if bytes.len() < needed {
+        return Err(Error::new(at, ErrorKind::CountMismatch {
+            field: "bSamFreqType",
+            declared: count as u32,
+            found: (bytes.len() - at - 1) / 3,
+        }))
+    }
+    let rate = |index: usize| {
+        let offset = at + 1 + 3 * index;
+        u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], 0])
+    };
+    Ok(match count {
+        0 => SampleRates::Continuous { min: rate(0), max: rate(1) },
+        _ => SampleRates::Discrete((0..count).map(rate).collect()),
+    })
+}
+
+// the answer to RANGE(CS_SAM_FREQ_CONTROL): wNumSubRanges, then dMIN, dMAX and
+// dRES of each subrange. Subranges of one frequency each are a discrete list.
+pub fn parse_sample_rate_range(bytes: &[u8]) -> Result<SampleRates, Error> {
+    if bytes.len() < 2 {
+        return Err(Error::new(0, ErrorKind::Truncated { needed: 2, available: bytes.len() }))
+    }
+    let count = usize::from(u16_at(bytes, 0));
+    if bytes.len() < 2 + 12 * count {
+        return Err(Error::new(0, ErrorKind::CountMismatch {
+            field: "wNumSubRanges",
+            declared: count as u32,
+            found: (bytes.len() - 2) / 12,
+        }))
+    }
+    let ranges: Vec<_> = (0..count)
+        .map(|index| 2 + 12 * index)
+        .map(|at| SampleRateRange { min: u32_at(bytes, at), max: u32_at(bytes, at + 4), resolution: u32_at(bytes, at + 8) })
+        .collect();
+    Ok(match ranges.iter().all(|range| range.min == range.max) {
+        true => SampleRates::Discrete(ranges.iter().map(|range| range.min).collect()),
+        false => SampleRates::Ranges(ranges),
+    })
+}
+
+// the audio streaming settings of `configuration`; their terminal links name
+// terminals of the AudioControl interface
+pub fn streaming_settings(configuration: &Configuration) -> impl Iterator<Item = &AlternateSetting> {
+    configuration.alternate_settings().filter(|setting| {
+        setting.interface_class == 0x01 && setting.interface_subclass == SUBCLASS_AUDIO_STREAMING
+    })
+}
+
+// terminal types (Universal Serial Bus Device Class Definition for Terminal Types)
+pub fn terminal_type_name(terminal_type: u16) -> Option<&'static str> {
+    Some(match terminal_type {
+        0x0100 => "USB Undefined",
+        0x0101 => "USB Streaming",
+        0x01FF => "USB Vendor Specific",
+        0x0200 => "Input Undefined",
+        0x0201 => "Microphone",
+        0x0202 => "Desktop Microphone",
+        0x0203 => "Personal Microphone",
+        0x0204 => "Omni-directional Microphone",
+        0x0205 => "Microphone Array",
+        0x0206 => "Processing Microphone Array",
+        0x0300 => "Output Undefined",
+        0x0301 => "Speaker",
+        0x0302 => "Headphones",
+        0x0303 => "Head Mounted Display Audio",
+        0x0304 => "Desktop Speaker",
+        0x0305 => "Room Speaker",
+        0x0306 => "Communication Speaker",
+        0x0307 => "Low Frequency Effects Speaker",
+        0x0400 => "Bi-directional Undefined",
+        0x0401 => "Handset",
+        0x0402 => "Headset",
+        0x0403 => "Speakerphone",
+        0x0404 => "Echo-suppressing Speakerphone",
+        0x0405 => "Echo-canceling Speakerphone",
+        0x0500 => "Telephony Undefined",
+        0x0501 => "Phone Line",
+        0x0502 => "Telephone",
+        0x0503 => "Down Line Phone",
+        0x0600 => "External Undefined",
+        0x0601 => "Analog Connector",
+        0x0602 => "Digital Audio Interface",
+        0x0603 => "Line Connector",
+        0x0604 => "Legacy Audio Connector",
+        0x0605 => "S/PDIF Interface",
+        0x0606 => "1394 DA Stream",
+        0x0607 => "1394 DV Stream Soundtrack",
+        0x0608 => "ADAT Lightpipe",
+        0x0609 => "TDIF",
+        0x060A => "MADI",
+        0x0700 => "Embedded Undefined",
+        0x0701 => "Level Calibration Noise Source",
+        0x0702 => "Equalization Noise",
+        0x0703 => "CD Player",
+        0x0704 => "DAT",
+        0x0705 => "DCC",
+        0x0706 => "Compressed Audio Player",
+        0x0707 => "Analog Tape",
+        0x0708 => "Phonograph",
+        0x0709 => "VCR Audio",
+        0x070A => "Video Disc Audio",
+        0x070B => "DVD Audio",
+        0x070C => "TV Tuner Audio",
+        0x070D => "Satellite Receiver Audio",
+        0x070E => "Cable Tuner Audio",
+        0x070F => "DSS Audio",
+        0x0710 => "Radio Receiver",
+        0x0711 => "Radio Transmitter",
+        0x0712 => "Multi-track Recorder",
+        0x0713 => "Synthesizer",
+        _ => return None,
+    })
+}
+
+// feature unit controls in bit order; UAC1 uses one bit each, UAC2 and UAC3 two
+pub const FEATURE_CONTROLS: [&str; 15] = [
+    "Mute", "Volume", "Bass", "Mid", "Treble", "Graphic Equalizer", "AGC", "Delay", "Bass Boost",
+    "Loudness", "Input Gain", "Input Gain Pad", "Phase Inverter", "Underflow", "Overflow",
+];
+
+// bmFormats bits of Type I formats (UAC2 and UAC3)
+pub fn format_names(formats: u64) -> Vec<&'static str> {
+    let names = [(0, "PCM"), (1, "PCM8"), (2, "IEEE Float"), (3, "A-Law"), (4, "mu-Law"), (31, "Raw Data")];
+    names.iter().filter(|(bit, _)| formats & (1 << bit) != 0).map(|(_, name)| *name).collect()
+}
+
+// wFormatTag of UAC1 streaming interfaces
+pub fn format_tag_name(tag: u16) -> Option<&'static str> {
+    Some(match tag {
+        0x0001 => "PCM",
+        0x0002 => "PCM8",
+        0x0003 => "IEEE Float",
+        0x0004 => "A-Law",
+        0x0005 => "mu-Law",
+        0x1001 => "MPEG",
+        0x1002 => "AC-3",
+        _ => return None,
+    })
+}
+
+// bCategory of UAC2 and UAC3 headers
+pub fn category_name(category: u8) -> Option<&'static str> {
+    Some(match category {
+        0x01 => "Desktop Speaker",
+        0x02 => "Home Theater",
+        0x03 => "Microphone",
+        0x04 => "Headset",
+        0x05 => "Telephone",
+        0x06 => "Converter",
+        0x07 => "Voice/Sound Recorder",
+        0x08 => "I/O Box",
+        0x09 => "Musical Instrument",
+        0x0A => "Pro-Audio",
+        0x0B => "Audio/Video",
+        0x0C => "Control Panel",
+        0x0D => "Headphone",
+        0x0E => "Generic Speaker",
+        0x0F => "Headset Adapter",
+        0x10 => "Speakerphone",
+        0xFF => "Other",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const AC_INTERFACE: [u8; 9] = [0x09, 0x04, 0x00, 0x00, 0x00, 0x01, 0x01, 0x20, 0x00];
+    // UAC2 header, I/O Box, wTotalLength 77
+    const HEADER: [u8; 9] = [0x09, 0x24, 0x01, 0x00, 0x02, 0x08, 0x4D, 0x00, 0x00];
+    const CLOCK_5: [u8; 8] = [0x08, 0x24, 0x0A, 0x05, 0x01, 0x07, 0x00, 0x00];
+    const CLOCK_6: [u8; 8] = [0x08, 0x24, 0x0A, 0x06, 0x01, 0x07, 0x00, 0x00];
+    // clock selector 7 between clock sources 5 and 6
+    const SELECTOR: [u8; 9] = [0x09, 0x24, 0x0B, 0x07, 0x02, 0x05, 0x06, 0x03, 0x00];
+    // USB streaming input terminal 1, clocked by 7, 2 channels
+    const INPUT: [u8; 17] = [
+        0x11, 0x24, 0x02, 0x01, 0x01, 0x01, 0x00, 0x07, 0x02, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+    // feature unit 2 after terminal 1, mute and volume on the master channel
+    const FEATURE: [u8; 14] = [0x0E, 0x24, 0x06, 0x02, 0x01, 0x0F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    // speaker output terminal 3 after unit 2, clocked by 7
+    const OUTPUT: [u8; 12] = [0x0C, 0x24, 0x03, 0x03, 0x01, 0x03, 0x00, 0x02, 0x07, 0x00, 0x00, 0x00];
+
+    fn audio_control(descriptors: &[&[u8]]) -> AudioControl {
+        let mut bytes = vec![0x09, 0x02, 0x00, 0x00, 0x01, 0x01, 0x00, 0x80, 0x32];
+        bytes.extend_from_slice(&AC_INTERFACE);
+        for descriptor in descriptors {
+            bytes.extend_from_slice(descriptor);
+        }
+        let total_length = bytes.len() as u16;
+        bytes[2..4].copy_from_slice(&total_length.to_le_bytes());
+        let configuration = Configuration::parse(&bytes).unwrap();
+        let setting = configuration.alternate_settings().next().unwrap();
+        AudioControl::parse(setting).unwrap()
+    }
+
+    #[test]
+    fn uac2_signal_path_and_clocks() {
+        let control = audio_control(&[&HEADER, &CLOCK_5, &CLOCK_6, &SELECTOR, &INPUT, &FEATURE, &OUTPUT]);
+        assert!(control.problems.is_empty(), "{:?}", control.problems);
+        assert_eq!((control.version, control.adc_version, control.category), (AudioVersion::Uac2, Some(Bcd(0x0200)), Some(0x08)));
+        assert_eq!(control.signal_paths(), [[1, 2, 3]]);
+        assert_eq!(control.entity(2).unwrap().kind, EntityKind::FeatureUnit { controls: vec![0x0F, 0x00] });
+        // the first input of the selector
+        assert_eq!(control.clock_of(1), Some(7));
+        assert_eq!(control.clock_source_of(1), Some(5));
+        assert_eq!(control.clock_source_of(3), Some(5));
+        assert_eq!(control.clock_source_of(2), None);
+    }
+
+    #[test]
+    fn dangling_references() {
+        let control = audio_control(&[&HEADER, &CLOCK_5, &CLOCK_5, &INPUT, &OUTPUT]);
+        // clock 5 twice, terminal 1 runs from the missing selector 7, terminal
+        // 3 from the missing unit 2 and selector 7
+        assert_eq!(control.problems, [
+            Error::new(38, ErrorKind::InvalidValue { field: "duplicate entity ID", value: 5 }),
+            Error::new(43, ErrorKind::InvalidValue { field: "bCSourceID", value: 7 }),
+            Error::new(60, ErrorKind::InvalidValue { field: "bSourceID", value: 2 }),
+            Error::new(60, ErrorKind::InvalidValue { field: "bCSourceID", value: 7 }),
+        ]);
+        assert_eq!(control.clock_source_of(1), None);
+    }
+
+    #[test]
+    fn uac1_discrete_and_continuous_rates() {
+        let mut streaming = AudioStreaming::default();
+        // type I, 2 channels, 16 bit, 44100 and 48000 Hz
+        let discrete = [0x0E, 0x24, 0x02, 0x01, 0x02, 0x02, 0x10, 0x02, 0x44, 0xAC, 0x00, 0x80, 0xBB, 0x00];
+        streaming.parse_descriptor(AudioVersion::Uac1, &discrete).unwrap();
+        assert_eq!((streaming.channels, streaming.subslot_size, streaming.bit_resolution), (Some(2), Some(2), Some(16)));
+        assert_eq!(streaming.sample_rates, Some(SampleRates::Discrete(vec![44100, 48000])));
+        // 8000 to 96000 Hz
+        let continuous = [0x0E, 0x24, 0x02, 0x01, 0x01, 0x02, 0x10, 0x00, 0x40, 0x1F, 0x00, 0x00, 0x77, 0x01];
+        streaming.parse_descriptor(AudioVersion::Uac1, &continuous).unwrap();
+        assert_eq!(streaming.sample_rates, Some(SampleRates::Continuous { min: 8000, max: 96000 }));
+        // 3 frequencies declared, 2 present
+        let short = [0x0E, 0x24, 0x02, 0x01, 0x02, 0x02, 0x10, 0x03, 0x44, 0xAC, 0x00, 0x80, 0xBB, 0x00];
+        assert_eq!(
+            streaming.parse_descriptor(AudioVersion::Uac1, &short),
+            Err(Error::new(7, ErrorKind::CountMismatch { field: "bSamFreqType", declared: 3, found: 2 })),
+        );
+    }
+
+    fn subranges(declared: u16, ranges: &[[u32; 3]]) -> Vec<u8> {
+        let mut bytes = declared.to_le_bytes().to_vec();
+        for range in ranges {
+            for value in range {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn discrete_sample_rate_range() {
+        let bytes = subranges(2, &[[44100, 44100, 0], [48000, 48000, 0]]);
+        assert_eq!(parse_sample_rate_range(&bytes), Ok(SampleRates::Discrete(vec![44100, 48000])));
+    }
+
+    #[test]
+    fn ranged_sample_rate_range() {
+        // one real range makes every subrange a range
+        let bytes = subranges(2, &[[44100, 44100, 0], [8000, 96000, 8000]]);
+        assert_eq!(parse_sample_rate_range(&bytes), Ok(SampleRates::Ranges(vec![
+            SampleRateRange { min: 44100, max: 44100, resolution: 0 },
+            SampleRateRange { min: 8000, max: 96000, resolution: 8000 },
+        ])));
+    }
+
+    #[test]
+    fn short_sample_rate_range() {
+        let bytes = subranges(2, &[[44100, 44100, 0]]);
+        assert_eq!(
+            parse_sample_rate_range(&bytes),
+            Err(Error::new(0, ErrorKind::CountMismatch { field: "wNumSubRanges", declared: 2, found: 1 })),
+        );
+        assert_eq!(
+            parse_sample_rate_range(&[0x01]),
+            Err(Error::new(0, ErrorKind::Truncated { needed: 2, available: 1 })),
+        );
+    }
+}
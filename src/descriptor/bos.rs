@@ -0,0 +1,542 @@
+use super::{
+    configuration::RawDescriptor, descriptor_type, header, u16_at, u32_at, Bcd, Error, ErrorKind, Uuid,
+};
+use alloc::vec::Vec;
+
+pub mod capability_type {
+    pub const WIRELESS_USB: u8 = 0x01;
+    pub const USB_20_EXTENSION: u8 = 0x02;
+    pub const SUPERSPEED_USB: u8 = 0x03;
+    pub const CONTAINER_ID: u8 = 0x04;
+    pub const PLATFORM: u8 = 0x05;
+    pub const POWER_DELIVERY: u8 = 0x06;
+    pub const BATTERY_INFO: u8 = 0x07;
+    pub const PD_CONSUMER_PORT: u8 = 0x08;
+    pub const PD_PROVIDER_PORT: u8 = 0x09;
+    pub const SUPERSPEED_PLUS: u8 = 0x0A;
+    pub const PRECISION_TIME_MEASUREMENT: u8 = 0x0B;
+    pub const WIRELESS_USB_EXT: u8 = 0x0C;
+    pub const BILLBOARD: u8 = 0x0D;
+    pub const AUTHENTICATION: u8 = 0x0E;
+    pub const BILLBOARD_EX: u8 = 0x0F;
+    pub const CONFIGURATION_SUMMARY: u8 = 0x10;
+    pub const FW_STATUS: u8 = 0x11;
+}
+
+// Binary device Object Store (USB 3.2 9.6.2) with its device capabilities.
+// Like the configuration walker it collects problems instead of stopping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bos {
+    pub total_length: u16,
+    pub num_device_caps: u8,
+    pub capabilities: Vec<DeviceCapability>,
+    pub problems: Vec<Error>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceCapability {
+    Usb20Extension(Usb20Extension),
+    SuperSpeed(SuperSpeed),
+    SuperSpeedPlus(SuperSpeedPlus),
+    ContainerId(Uuid),
+    Platform(Platform),
+    Billboard(Billboard),
+    PowerDelivery(PowerDelivery),
+    BatteryInfo(BatteryInfo),
+    PdConsumerPort(PdConsumerPort),
+    PdProviderPort(PdProviderPort),
+    Unknown(RawDescriptor), // not decoded, header included
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usb20Extension {
+    pub attributes: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuperSpeed {
+    pub attributes: u8,
+    pub speeds_supported: u16,
+    pub functionality_support: u8, // lowest speed with full functionality
+    pub u1_exit_latency: u8, // microseconds
+    pub u2_exit_latency: u16, // microseconds
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuperSpeedPlus {
+    pub attributes: u32,
+    pub functionality_support: u16,
+    pub sublink_speeds: Vec<SublinkSpeed>,
+}
+
+// one bmSublinkSpeedAttr entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SublinkSpeed(pub u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    pub uuid: Uuid,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Billboard {
+    pub additional_info_url_index: u8,
+    pub preferred_alternate_mode: u8,
+    pub vconn_power: u16,
+    pub configured: [u8; 32], // two bits of state per alternate mode
+    pub version: Bcd,
+    pub additional_failure_info: u8,
+    pub alternate_modes: Vec<BillboardAlternateMode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BillboardAlternateMode {
+    pub svid: u16,
+    pub alternate_mode: u8,
+    pub string_index: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerDelivery {
+    pub attributes: u32,
+    pub bc_version: Bcd,
+    pub pd_version: Bcd,
+    pub type_c_version: Bcd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryInfo {
+    pub battery_index: u8,
+    pub serial_index: u8,
+    pub manufacturer_index: u8,
+    pub battery_id: u8,
+    pub charged_threshold: u32, // mWh
+    pub weak_threshold: u32, // mWh
+    pub design_capacity: u32, // mWh
+    pub last_full_charge_capacity: u32, // mWh
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PdConsumerPort {
+    pub capabilities: u16,
+    pub min_voltage: u16, // 50 mV units
+    pub max_voltage: u16, // 50 mV units
+    pub max_operating_power: u32, // 10 mW units
+    pub max_peak_power: u32, // 10 mW units
+    pub max_peak_power_time: u32, // 100 ms units
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdProviderPort {
+    pub capabilities: u16,
+    pub power_data_objects: Vec<u32>,
+}
+
+impl Bos {
+    pub const LENGTH: usize = 5;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let head = header(bytes, descriptor_type::BOS, Self::LENGTH)?;
+        let mut bos = Bos {
+            total_length: u16_at(head, 2),
+            num_device_caps: head[4],
+            capabilities: Vec::new(),
+            problems: Vec::new(),
+        };
+        let total_length = usize::from(bos.total_length);
+        if total_length < head.len() {
+            return Err(Error::new(2, ErrorKind::InvalidValue { field: "wTotalLength", value: total_length as u32 }))
+        }
+        if bytes.len() < total_length {
+            bos.problems.push(Error::new(bytes.len(), ErrorKind::Truncated { needed: total_length, available: bytes.len() }));
+        }
+        let bytes = &bytes[..total_length.min(bytes.len())];
+        let mut offset = head.len();
+        while offset < bytes.len() {
+            let rest = &bytes[offset..];
+            let length = usize::from(rest[0]);
+            if rest.len() < 2 || length < 3 {
+                bos.problems.push(Error::new(offset, ErrorKind::InvalidLength { length: rest[0] }));
+                break;
+            }
+            if rest.len() < length {
+                bos.problems.push(Error::new(offset, ErrorKind::Truncated { needed: length, available: rest.len() }));
+                break;
+            }
+            let descriptor = &rest[..length];
+            let capability = DeviceCapability::parse(descriptor).unwrap_or_else(|err| {
+                bos.problems.push(err.at(offset));
+                DeviceCapability::Unknown(RawDescriptor { offset, bytes: descriptor.to_vec() })
+            });
+            bos.capabilities.push(capability);
+            offset += length;
+        }
+        if bos.capabilities.len() != usize::from(bos.num_device_caps) {
+            bos.problems.push(Error::new(4, ErrorKind::CountMismatch {
+                field: "bNumDeviceCaps",
+                declared: bos.num_device_caps.into(),
+                found: bos.capabilities.len(),
+            }));
+        }
+        Ok(bos)
+    }
+
+    pub fn platform_capabilities(&self) -> impl Iterator<Item = &Platform> {
+        self.capabilities.iter().filter_map(|capability| match capability {
+            DeviceCapability::Platform(platform) => Some(platform),
+            _ => None,
+        })
+    }
+}
+
+impl DeviceCapability {
+    // parse one device capability descriptor; unknown types are kept raw
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::DEVICE_CAPABILITY, 3)?;
+        let min_length = |length: usize| {
+            if bytes.len() < length {
+                Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+            } else {
+                Ok(())
+            }
+        };
+        Ok(match bytes[2] {
+            capability_type::USB_20_EXTENSION => {
+                min_length(7)?;
+                DeviceCapability::Usb20Extension(Usb20Extension { attributes: u32_at(bytes, 3) })
+            },
+            capability_type::SUPERSPEED_USB => {
+                min_length(10)?;
+                DeviceCapability::SuperSpeed(SuperSpeed {
+                    attributes: bytes[3],
+                    speeds_supported: u16_at(bytes, 4),
+                    functionality_support: bytes[6],
+                    u1_exit_latency: bytes[7],
+                    u2_exit_latency: u16_at(bytes, 8),
+                })
+            },
+            capability_type::SUPERSPEED_PLUS => {
+                min_length(12)?;
+                let attributes = u32_at(bytes, 4);
+                let count = (attributes & 0x1F) as usize + 1; // SSAC is zero based
+                min_length(12 + 4 * count)?;
+                DeviceCapability::SuperSpeedPlus(SuperSpeedPlus {
+                    attributes,
+                    functionality_support: u16_at(bytes, 8),
+                    sublink_speeds: (0..count).map(|i| SublinkSpeed(u32_at(bytes, 12 + 4 * i))).collect(),
+                })
+            },
+            capability_type::CONTAINER_ID => {
+                min_length(20)?;
+                DeviceCapability::ContainerId(Uuid::from_slice(&bytes[4..20]))
+            },
+            capability_type::PLATFORM => {
+                min_length(20)?;
+                DeviceCapability::Platform(Platform {
+                    uuid: Uuid::from_slice(&bytes[4..20]),
+                    data: bytes[20..].to_vec(),
+                })
+            },
+            capability_type::BILLBOARD => {
+                min_length(44)?;
+                let count = usize::from(bytes[4]);
+                min_length(44 + 4 * count)?;
+                let mut configured = [0; 32];
+                configured.copy_from_slice(&bytes[8..40]);
+                DeviceCapability::Billboard(Billboard {
+                    additional_info_url_index: bytes[3],
+                    preferred_alternate_mode: bytes[5],
+                    vconn_power: u16_at(bytes, 6),
+                    configured,
+                    version: Bcd(u16_at(bytes, 40)),
+                    additional_failure_info: bytes[42],
+                    alternate_modes: (0..count).map(|i| {
+                        let mode = &bytes[44 + 4 * i..];
+                        BillboardAlternateMode {
+                            svid: u16_at(mode, 0),
+                            alternate_mode: mode[2],
+                            string_index: mode[3],
+                        }
+                    }).collect(),
+                })
+            },
+            capability_type::POWER_DELIVERY => {
+                min_length(14)?;
+                DeviceCapability::PowerDelivery(PowerDelivery {
+                    attributes: u32_at(bytes, 4),
+                    bc_version: Bcd(u16_at(bytes, 8)),
+                    pd_version: Bcd(u16_at(bytes, 10)),
+                    type_c_version: Bcd(u16_at(bytes, 12)),
+                })
+            },
+            capability_type::BATTERY_INFO => {
+                min_length(24)?;
+                DeviceCapability::BatteryInfo(BatteryInfo {
+                    battery_index: bytes[3],
+                    serial_index: bytes[4],
+                    manufacturer_index: bytes[5],
+                    battery_id: bytes[6],
+                    charged_threshold: u32_at(bytes, 8),
+                    weak_threshold: u32_at(bytes, 12),
+                    design_capacity: u32_at(bytes, 16),
+                    last_full_charge_capacity: u32_at(bytes, 20),
+                })
+            },
+            capability_type::PD_CONSUMER_PORT => {
+                min_length(24)?;
+                DeviceCapability::PdConsumerPort(PdConsumerPort {
+                    capabilities: u16_at(bytes, 4),
+                    min_voltage: u16_at(bytes, 6),
+                    max_voltage: u16_at(bytes, 8),
+                    max_operating_power: u32_at(bytes, 12),
+                    max_peak_power: u32_at(bytes, 16),
+                    max_peak_power_time: u32_at(bytes, 20),
+                })
+            },
+            capability_type::PD_PROVIDER_PORT => {
+                min_length(8)?;
+                let count = usize::from(bytes[6]);
+                min_length(8 + 4 * count)?;
+                DeviceCapability::PdProviderPort(PdProviderPort {
+                    capabilities: u16_at(bytes, 4),
+                    power_data_objects: (0..count).map(|i| u32_at(bytes, 8 + 4 * i)).collect(),
+                })
+            },
+            _ => DeviceCapability::Unknown(RawDescriptor { offset: 0, bytes: bytes.to_vec() }),
+        })
+    }
+}
+
+impl Usb20Extension {
+    pub fn lpm(&self) -> bool {
+        self.attributes & 0x02 != 0
+    }
+
+    pub fn besl(&self) -> bool {
+        self.attributes & 0x04 != 0
+    }
+
+    pub fn baseline_besl(&self) -> Option<u8> {
+        if self.attributes & 0x08 != 0 { Some(((self.attributes >> 8) & 0x0F) as u8) } else { None }
+    }
+
+    pub fn deep_besl(&self) -> Option<u8> {
+        if self.attributes & 0x10 != 0 { Some(((self.attributes >> 12) & 0x0F) as u8) } else { None }
+    }
+}
+
+impl SuperSpeed {
+    pub fn ltm(&self) -> bool {
+        self.attributes & 0x02 != 0
+    }
+}
+
+impl SuperSpeedPlus {
+    pub fn sublink_speed_id_count(&self) -> u8 {
+        ((self.attributes >> 5) & 0x0F) as u8 + 1
+    }
+
+    pub fn min_rx_lanes(&self) -> u8 {
+        ((self.functionality_support >> 8) & 0x0F) as u8
+    }
+
+    pub fn min_tx_lanes(&self) -> u8 {
+        ((self.functionality_support >> 12) & 0x0F) as u8
+    }
+}
+
+impl SublinkSpeed {
+    pub fn id(&self) -> u8 {
+        (self.0 & 0x0F) as u8
+    }
+
+    // lane speed in bits per second
+    pub fn bits_per_second(&self) -> u64 {
+        let mantissa = u64::from(self.0 >> 16);
+        let exponent = (self.0 >> 4) & 0x03;
+        mantissa * 1000u64.pow(exponent)
+    }
+
+    pub fn asymmetric(&self) -> bool {
+        self.0 & 0x40 != 0
+    }
+
+    pub fn transmit(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+
+    // link protocol: false for SuperSpeed, true for SuperSpeedPlus
+    pub fn superspeed_plus(&self) -> bool {
+        (self.0 >> 14) & 0x03 == 1
+    }
+}
+
+impl PowerDelivery {
+    pub fn battery_charging(&self) -> bool {
+        self.attributes & 0x02 != 0
+    }
+
+    pub fn usb_power_delivery(&self) -> bool {
+        self.attributes & 0x04 != 0
+    }
+
+    pub fn provider(&self) -> bool {
+        self.attributes & 0x08 != 0
+    }
+
+    pub fn consumer(&self) -> bool {
+        self.attributes & 0x10 != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+
+    // LPM and BESL with baseline 4 and deep 3
+    const USB_20_EXTENSION: [u8; 7] = [0x07, 0x10, 0x02, 0x1E, 0x34, 0x00, 0x00];
+
+    // full, high and 5 Gbps, fully functional from full speed
+    const SUPERSPEED: [u8; 10] = [0x0A, 0x10, 0x03, 0x00, 0x0E, 0x00, 0x01, 0x0A, 0xFF, 0x07];
+
+    // one 10 Gbps SuperSpeedPlus sublink speed ID, as a receive and a transmit
+    // attribute, one lane minimum each way
+    const SUPERSPEED_PLUS: [u8; 20] = [
+        0x14, 0x10, 0x0A, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x11, 0x00, 0x00,
+        0x30, 0x40, 0x0A, 0x00, 0xB0, 0x40, 0x0A, 0x00,
+    ];
+
+    const CONTAINER_ID: [u8; 20] = [
+        0x14, 0x10, 0x04, 0x00,
+        0x33, 0x22, 0x11, 0x00, 0x55, 0x44, 0x77, 0x66, 0x88, 0x99, 0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF,
+    ];
+
+    // DisplayPort alternate mode, configured
+    fn billboard_bytes() -> Vec<u8> {
+        let mut bytes = vec![0x30, 0x10, 0x0D, 0x01, 0x01, 0x00, 0x00, 0x80];
+        bytes.push(0x03);
+        bytes.resize(40, 0);
+        bytes.extend_from_slice(&[0x10, 0x01, 0x00, 0x00, 0x01, 0xFF, 0x01, 0x02]);
+        bytes
+    }
+
+    // 5 V to 20 V, no operating power and peak power time limits and the
+    // largest peak power that is a limit
+    const PD_CONSUMER_PORT: [u8; 24] = [
+        0x18, 0x10, 0x08, 0x00, 0x01, 0x00, 0x64, 0x00, 0x90, 0x01, 0x00, 0x00,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    ];
+
+    fn with_header(num_device_caps: u8, capabilities: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = vec![0x05, 0x0F, 0x00, 0x00, num_device_caps];
+        for capability in capabilities {
+            bytes.extend_from_slice(capability);
+        }
+        let total_length = bytes.len() as u16;
+        bytes[2..4].copy_from_slice(&total_length.to_le_bytes());
+        bytes
+    }
+
+    fn capability(bytes: &[u8]) -> DeviceCapability {
+        DeviceCapability::parse(bytes).unwrap()
+    }
+
+    #[test]
+    fn walks_capabilities() {
+        let billboard = billboard_bytes();
+        let bytes = with_header(6, &[
+            &USB_20_EXTENSION, &SUPERSPEED, &SUPERSPEED_PLUS, &CONTAINER_ID, &billboard, &PD_CONSUMER_PORT,
+        ]);
+        let bos = Bos::parse(&bytes).unwrap();
+        assert!(bos.problems.is_empty(), "{:?}", bos.problems);
+        assert_eq!(bos.total_length, 134);
+        assert_eq!(bos.capabilities, [
+            capability(&USB_20_EXTENSION), capability(&SUPERSPEED), capability(&SUPERSPEED_PLUS),
+            capability(&CONTAINER_ID), capability(&billboard), capability(&PD_CONSUMER_PORT),
+        ]);
+    }
+
+    #[test]
+    fn usb_20_extension() {
+        let DeviceCapability::Usb20Extension(extension) = capability(&USB_20_EXTENSION) else { panic!() };
+        assert!(extension.lpm() && extension.besl());
+        assert_eq!((extension.baseline_besl(), extension.deep_besl()), (Some(4), Some(3)));
+    }
+
+    #[test]
+    fn superspeed() {
+        let DeviceCapability::SuperSpeed(ss) = capability(&SUPERSPEED) else { panic!() };
+        assert_eq!((ss.speeds_supported, ss.functionality_support), (0x000E, 1));
+        assert_eq!((ss.u1_exit_latency, ss.u2_exit_latency), (10, 2047));
+        assert!(!ss.ltm());
+    }
+
+    #[test]
+    fn superspeed_plus_sublink_speeds() {
+        let DeviceCapability::SuperSpeedPlus(ssp) = capability(&SUPERSPEED_PLUS) else { panic!() };
+        assert_eq!(ssp.sublink_speed_id_count(), 1);
+        assert_eq!((ssp.min_rx_lanes(), ssp.min_tx_lanes()), (1, 1));
+        assert_eq!(ssp.sublink_speeds.len(), 2);
+        let (rx, tx) = (ssp.sublink_speeds[0], ssp.sublink_speeds[1]);
+        assert_eq!((rx.id(), rx.bits_per_second(), rx.transmit(), rx.superspeed_plus()), (0, 10_000_000_000, false, true));
+        assert_eq!((tx.id(), tx.bits_per_second(), tx.transmit(), tx.asymmetric()), (0, 10_000_000_000, true, false));
+        // SSAC announces more sublink speed attributes than there are
+        let mut bytes = SUPERSPEED_PLUS;
+        bytes[4] = 0x02;
+        assert_eq!(DeviceCapability::parse(&bytes), Err(Error::new(0, ErrorKind::InvalidLength { length: 0x14 })));
+    }
+
+    #[test]
+    fn container_id() {
+        let DeviceCapability::ContainerId(uuid) = capability(&CONTAINER_ID) else { panic!() };
+        assert_eq!(uuid.to_string(), "00112233-4455-6677-8899-aabbccddeeff");
+        assert_eq!(
+            DeviceCapability::parse(&CONTAINER_ID[..19]),
+            Err(Error::new(0, ErrorKind::Truncated { needed: 20, available: 19 })),
+        );
+    }
+
+    #[test]
+    fn billboard_alternate_modes() {
+        let DeviceCapability::Billboard(billboard) = capability(&billboard_bytes()) else { panic!() };
+        assert_eq!(billboard.version, Bcd(0x0110));
+        assert_eq!((billboard.additional_info_url_index, billboard.vconn_power), (1, 0x8000));
+        assert_eq!(billboard.configured[0] & 0x03, 0x03);
+        assert_eq!(billboard.alternate_modes, [BillboardAlternateMode { svid: 0xFF01, alternate_mode: 1, string_index: 2 }]);
+        // bNumberOfAlternateModes beyond bLength
+        let mut bytes = billboard_bytes();
+        bytes[4] = 2;
+        assert_eq!(DeviceCapability::parse(&bytes), Err(Error::new(0, ErrorKind::InvalidLength { length: 0x30 })));
+    }
+
+    #[test]
+    fn pd_consumer_port() {
+        let DeviceCapability::PdConsumerPort(port) = capability(&PD_CONSUMER_PORT) else { panic!() };
+        assert_eq!((port.min_voltage, port.max_voltage), (100, 400));
+        assert_eq!((port.max_operating_power, port.max_peak_power, port.max_peak_power_time), (u32::MAX, u32::MAX - 1, u32::MAX));
+    }
+
+    #[test]
+    fn counts_and_lengths() {
+        let bytes = with_header(2, &[&USB_20_EXTENSION]);
+        let bos = Bos::parse(&bytes).unwrap();
+        assert_eq!(bos.problems, [Error::new(4, ErrorKind::CountMismatch { field: "bNumDeviceCaps", declared: 2, found: 1 })]);
+        // a capability cut off keeps the ones before it
+        let bytes = with_header(2, &[&USB_20_EXTENSION, &SUPERSPEED]);
+        let bos = Bos::parse(&bytes[..15]).unwrap();
+        assert_eq!(bos.problems, [
+            Error::new(15, ErrorKind::Truncated { needed: 22, available: 15 }),
+            Error::new(12, ErrorKind::Truncated { needed: 10, available: 3 }),
+            Error::new(4, ErrorKind::CountMismatch { field: "bNumDeviceCaps", declared: 2, found: 1 }),
+        ]);
+        // a malformed capability is kept raw
+        let mut short = SUPERSPEED;
+        short[0] = 9;
+        let bytes = with_header(1, &[&short[..9]]);
+        let bos = Bos::parse(&bytes).unwrap();
+        assert_eq!(bos.problems, [Error::new(5, ErrorKind::InvalidLength { length: 9 })]);
+        assert!(matches!(&bos.capabilities[..], [DeviceCapability::Unknown(raw)] if raw.offset == 5));
+    }
+}
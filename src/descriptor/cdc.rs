@@ -0,0 +1,293 @@
+use super::{
+    configuration::{AlternateSetting, Configuration, Interface, RawDescriptor},
+    u16_at, u32_at, Bcd, Error, ErrorKind,
+};
+use alloc::vec::Vec;
+
+pub const CS_INTERFACE: u8 = 0x24;
+
+pub mod subtype {
+    pub const HEADER: u8 = 0x00;
+    pub const CALL_MANAGEMENT: u8 = 0x01;
+    pub const ABSTRACT_CONTROL_MANAGEMENT: u8 = 0x02;
+    pub const UNION: u8 = 0x06;
+    pub const ETHERNET_NETWORKING: u8 = 0x0F;
+    pub const NCM: u8 = 0x1A;
+    pub const MBIM: u8 = 0x1B;
+    pub const MBIM_EXTENDED: u8 = 0x1C;
+}
+
+// CDC functional descriptors (CDC 1.2 5.2.3 and the ECM, NCM and MBIM subclass
+// specifications) found behind a communication interface
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FunctionalDescriptor {
+    Header { cdc_version: Bcd },
+    CallManagement { capabilities: u8, data_interface: u8 },
+    AbstractControlManagement { capabilities: u8 },
+    Union(Union),
+    EthernetNetworking(EthernetNetworking),
+    Ncm { ncm_version: Bcd, network_capabilities: u8 },
+    Mbim(Mbim),
+    MbimExtended { mbim_extended_version: Bcd, max_outstanding_command_messages: u8, mtu: u16 },
+    Unknown(RawDescriptor),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Union {
+    pub control_interface: u8,
+    pub subordinate_interfaces: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthernetNetworking {
+    pub mac_address_index: u8, // string descriptor holding the MAC address as 12 hex digits
+    pub ethernet_statistics: u32,
+    pub max_segment_size: u16,
+    pub number_mc_filters: u16, // bit 15 set: filters are imperfect
+    pub number_power_filters: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mbim {
+    pub mbim_version: Bcd,
+    pub max_control_message: u16,
+    pub number_filters: u8,
+    pub max_filter_size: u8,
+    pub max_segment_size: u16,
+    pub network_capabilities: u8,
+}
+
+// a communication interface linked to the interfaces its union descriptor names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CdcFunction<'a> {
+    pub control: &'a AlternateSetting,
+    pub union: Union,
+    pub data: Vec<&'a Interface>,
+    pub problems: Vec<Error>,
+}
+
+impl FunctionalDescriptor {
+    pub fn parse(raw: &RawDescriptor) -> Result<Self, Error> {
+        let bytes = &raw.bytes;
+        if bytes.len() < 3 {
+            return Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+        }
+        if bytes[1] != CS_INTERFACE {
+            return Err(Error::new(1, ErrorKind::UnexpectedType { expected: CS_INTERFACE, found: bytes[1] }))
+        }
+        let min_length = match bytes[2] {
+            subtype::HEADER | subtype::CALL_MANAGEMENT | subtype::UNION => 5,
+            subtype::ABSTRACT_CONTROL_MANAGEMENT => 4,
+            subtype::ETHERNET_NETWORKING => 13,
+            subtype::NCM => 6,
+            subtype::MBIM => 12,
+            subtype::MBIM_EXTENDED => 8,
+            _ => return Ok(FunctionalDescriptor::Unknown(raw.clone())),
+        };
+        if bytes.len() < min_length {
+            return Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+        }
+        Ok(match bytes[2] {
+            subtype::HEADER => FunctionalDescriptor::Header { cdc_version: Bcd(u16_at(bytes, 3)) },
+            subtype::CALL_MANAGEMENT => FunctionalDescriptor::CallManagement {
+                capabilities: bytes[3],
+                data_interface: bytes[4],
+            },
+            subtype::ABSTRACT_CONTROL_MANAGEMENT => {
+                FunctionalDescriptor::AbstractControlManagement { capabilities: bytes[3] }
+            },
+            subtype::UNION => FunctionalDescriptor::Union(Union {
+                control_interface: bytes[3],
+                subordinate_interfaces: bytes[4..].to_vec(),
+            }),
+            subtype::ETHERNET_NETWORKING => FunctionalDescriptor::EthernetNetworking(EthernetNetworking {
+                mac_address_index: bytes[3],
+                ethernet_statistics: u32_at(bytes, 4),
+                max_segment_size: u16_at(bytes, 8),
+                number_mc_filters: u16_at(bytes, 10),
+                number_power_filters: bytes[12],
+            }),
+            subtype::NCM => FunctionalDescriptor::Ncm {
+                ncm_version: Bcd(u16_at(bytes, 3)),
+                network_capabilities: bytes[5],
+            },
+            subtype::MBIM => FunctionalDescriptor::Mbim(Mbim {
+                mbim_version: Bcd(u16_at(bytes, 3)),
+                max_control_message: u16_at(bytes, 5),
+                number_filters: bytes[7],
+                max_filter_size: bytes[8],
+                max_segment_size: u16_at(bytes, 9),
+                network_capabilities: bytes[11],
+            }),
+            _ => FunctionalDescriptor::MbimExtended {
+                mbim_extended_version: Bcd(u16_at(bytes, 3)),
+                max_outstanding_command_messages: bytes[5],
+                mtu: u16_at(bytes, 6),
+            },
+        })
+    }
+}
+
+// the functional descriptors of a communication interface, in order
+pub fn functional_descriptors(setting: &AlternateSetting) -> impl Iterator<Item = Result<FunctionalDescriptor, Error>> + '_ {
+    setting.extra.iter()
+        .filter(|raw| raw.descriptor_type() == CS_INTERFACE)
+        .map(|raw| FunctionalDescriptor::parse(raw).map_err(|err| err.at(raw.offset)))
+}
+
+// every communication interface with a union descriptor together with the
+// data interfaces it controls
+pub fn functions(configuration: &Configuration) -> Vec<CdcFunction<'_>> {
+    let mut functions = Vec::new();
+    for setting in configuration.alternate_settings().filter(|setting| setting.interface_class == 0x02) {
+        let union = setting.extra.iter()
+            .filter(|raw| raw.descriptor_type() == CS_INTERFACE && raw.subtype() == Some(subtype::UNION))
+            .find_map(|raw| match FunctionalDescriptor::parse(raw) {
+                Ok(FunctionalDescriptor::Union(union)) => Some((raw.offset, union)),
+                _ => None,
+            });
+        let (offset, union) = match union {
+            Some(union) => union,
+            None => continue,
+        };
+        let mut problems = Vec::new();
+        if union.control_interface != setting.interface_number {
+            problems.push(Error::new(offset + 3, ErrorKind::InvalidValue {
+                field: "bControlInterface",
+                value: union.control_interface.into(),
+            }));
+        }
+        let mut data = Vec::new();
+        for (index, &number) in union.subordinate_interfaces.iter().enumerate() {
+            match configuration.interfaces.iter().find(|interface| interface.number == number) {
+                Some(interface) => data.push(interface),
+                None => problems.push(Error::new(offset + 4 + index, ErrorKind::InvalidValue {
+                    field: "bSubordinateInterface",
+                    value: number.into(),
+                })),
+            }
+        }
+        functions.push(CdcFunction { control: setting, union, data, problems });
+    }
+    functions
+}
+
+// communication interface subclass codes
+pub fn subclass_name(subclass: u8) -> Option<&'static str> {
+    Some(match subclass {
+        0x01 => "Direct Line Control Model",
+        0x02 => "Abstract Control Model",
+        0x03 => "Telephone Control Model",
+        0x04 => "Multi-Channel Control Model",
+        0x05 => "CAPI Control Model",
+        0x06 => "Ethernet Networking Control Model",
+        0x07 => "ATM Networking Control Model",
+        0x08 => "Wireless Handset Control Model",
+        0x09 => "Device Management",
+        0x0A => "Mobile Direct Line Model",
+        0x0B => "OBEX",
+        0x0C => "Ethernet Emulation Model",
+        0x0D => "Network Control Model",
+        0x0E => "Mobile Broadband Interface Model",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const HEADER: [u8; 5] = [0x05, 0x24, 0x00, 0x10, 0x01];
+    const DATA_INTERFACE: [u8; 9] = [0x09, 0x04, 0x01, 0x00, 0x02, 0x0A, 0x00, 0x00, 0x00];
+    const BULK_OUT: [u8; 7] = [0x07, 0x05, 0x01, 0x02, 0x40, 0x00, 0x00];
+    const BULK_IN: [u8; 7] = [0x07, 0x05, 0x81, 0x02, 0x40, 0x00, 0x00];
+
+    fn with_header(num_interfaces: u8, descriptors: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = vec![0x09, 0x02, 0x00, 0x00, num_interfaces, 0x01, 0x00, 0x80, 0xFA];
+        for descriptor in descriptors {
+            bytes.extend_from_slice(descriptor);
+        }
+        let total_length = bytes.len() as u16;
+        bytes[2..4].copy_from_slice(&total_length.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn acm_serial_port() {
+        let bytes = with_header(2, &[
+            &[0x09, 0x04, 0x00, 0x00, 0x01, 0x02, 0x02, 0x01, 0x00],
+            &HEADER,
+            &[0x05, 0x24, 0x01, 0x00, 0x01],
+            &[0x04, 0x24, 0x02, 0x02],
+            &[0x05, 0x24, 0x06, 0x00, 0x01],
+            &[0x07, 0x05, 0x82, 0x03, 0x08, 0x00, 0x10],
+            &DATA_INTERFACE,
+            &BULK_OUT,
+            &BULK_IN,
+        ]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        let control = &configuration.interfaces[0].alternate_settings[0];
+        let descriptors: Result<Vec<_>, _> = functional_descriptors(control).collect();
+        assert_eq!(descriptors, Ok(vec![
+            FunctionalDescriptor::Header { cdc_version: Bcd(0x0110) },
+            FunctionalDescriptor::CallManagement { capabilities: 0x00, data_interface: 1 },
+            FunctionalDescriptor::AbstractControlManagement { capabilities: 0x02 },
+            FunctionalDescriptor::Union(Union { control_interface: 0, subordinate_interfaces: vec![1] }),
+        ]));
+        let linked = functions(&configuration);
+        assert_eq!(linked.len(), 1);
+        assert_eq!(linked[0].control.interface_number, 0);
+        assert_eq!(linked[0].data.iter().map(|interface| interface.number).collect::<Vec<_>>(), [1]);
+        assert!(linked[0].problems.is_empty(), "{:?}", linked[0].problems);
+    }
+
+    // an NCM function whose union names `control` and `subordinates`
+    fn ncm(control: u8, subordinates: &[u8]) -> Vec<u8> {
+        let mut union = vec![0x04 + subordinates.len() as u8, 0x24, 0x06, control];
+        union.extend_from_slice(subordinates);
+        with_header(2, &[
+            &[0x09, 0x04, 0x00, 0x00, 0x01, 0x02, 0x0D, 0x00, 0x00],
+            &HEADER,
+            &union,
+            &[0x0D, 0x24, 0x0F, 0x04, 0x00, 0x00, 0x00, 0x00, 0xEA, 0x05, 0x00, 0x00, 0x00],
+            &[0x06, 0x24, 0x1A, 0x00, 0x01, 0x00],
+            &[0x07, 0x05, 0x83, 0x03, 0x10, 0x00, 0x09],
+            // no endpoints until the host selects alternate setting 1
+            &[0x09, 0x04, 0x01, 0x00, 0x00, 0x0A, 0x00, 0x01, 0x00],
+            &[0x09, 0x04, 0x01, 0x01, 0x02, 0x0A, 0x00, 0x01, 0x00],
+            &BULK_OUT,
+            &BULK_IN,
+        ])
+    }
+
+    #[test]
+    fn ncm_network_function() {
+        let bytes = ncm(0, &[1]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        let linked = functions(&configuration);
+        assert_eq!(linked.len(), 1);
+        assert!(linked[0].problems.is_empty(), "{:?}", linked[0].problems);
+        assert_eq!(linked[0].data.len(), 1);
+        assert_eq!((linked[0].data[0].number, linked[0].data[0].alternate_settings.len()), (1, 2));
+        let networking = functional_descriptors(linked[0].control).find_map(|descriptor| match descriptor {
+            Ok(FunctionalDescriptor::EthernetNetworking(networking)) => Some(networking),
+            _ => None,
+        });
+        assert_eq!(networking.map(|networking| (networking.mac_address_index, networking.max_segment_size)), Some((4, 1514)));
+    }
+
+    #[test]
+    fn bad_union_interfaces() {
+        // the union claims interface 2 as its control and a missing interface 5
+        let bytes = ncm(2, &[1, 5]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        let linked = functions(&configuration);
+        assert_eq!(linked[0].problems, [
+            Error::new(26, ErrorKind::InvalidValue { field: "bControlInterface", value: 2 }),
+            Error::new(28, ErrorKind::InvalidValue { field: "bSubordinateInterface", value: 5 }),
+        ]);
+        // what exists is still linked
+        assert_eq!(linked[0].data.iter().map(|interface| interface.number).collect::<Vec<_>>(), [1]);
+    }
+}
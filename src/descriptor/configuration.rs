@@ -0,0 +1,938 @@
+use super::{descriptor_type, header, u16_at, u32_at, Bcd, Error, ErrorKind};
+use alloc::vec::Vec;
+use core::fmt;
+
+// A configuration descriptor together with everything returned after it:
+// configuration -> interface -> alternate setting -> endpoint. Descriptors
+// the walker does not know are kept as raw bytes on the interface or endpoint
+// they follow. The walk never gives up on the first problem; everything odd
+// it finds is collected in `problems`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Configuration {
+    pub total_length: u16,
+    pub num_interfaces: u8,
+    pub configuration_value: u8,
+    pub configuration_index: u8,
+    pub attributes: u8,
+    pub max_power: u8, // raw bMaxPower, see `max_power_milliamps`
+    pub extra: Vec<RawDescriptor>, // descriptors not owned by any interface
+    pub otg: Option<Otg>,
+    pub associations: Vec<InterfaceAssociation>,
+    pub interfaces: Vec<Interface>,
+    pub problems: Vec<Error>,
+}
+
+// interface association descriptor (USB 3.2 9.6.4): a run of consecutive
+// interfaces forming one function of a composite device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterfaceAssociation {
+    pub offset: usize,
+    pub first_interface: u8,
+    pub interface_count: u8,
+    pub function_class: u8,
+    pub function_subclass: u8,
+    pub function_protocol: u8,
+    pub function_index: u8,
+}
+
+// one function of a configuration: the interfaces an IAD groups, or a single
+// interface no IAD covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function<'a> {
+    pub association: Option<&'a InterfaceAssociation>,
+    pub interfaces: Vec<&'a Interface>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interface {
+    pub number: u8,
+    pub alternate_settings: Vec<AlternateSetting>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlternateSetting {
+    pub offset: usize,
+    pub interface_number: u8,
+    pub alternate_setting: u8,
+    pub num_endpoints: u8,
+    pub interface_class: u8,
+    pub interface_subclass: u8,
+    pub interface_protocol: u8,
+    pub interface_index: u8,
+    pub power: Option<InterfacePower>,
+    pub extra: Vec<RawDescriptor>, // class specific descriptors of this setting
+    pub endpoints: Vec<Endpoint>,
+}
+
+// OTG descriptor (OTG and EH 2.0 6.4): which of the On-The-Go protocols the
+// device supports; OTG 1.3 devices leave out bcdOTG
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Otg {
+    pub attributes: u8,
+    pub otg_version: Option<Bcd>,
+}
+
+// interface power descriptor (Interface Power Management 1.01): the device
+// power states an interface supports and what leaving D0 saves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterfacePower {
+    pub capabilities: u8,
+    pub self_power_consumed_d0: u32, // 24 bits
+    pub power_summary_id: u8,
+    pub bus_power_saved: [u8; 3], // D1, D2, D3
+    pub self_power_saved: [u8; 3],
+    pub transition_time: [u16; 3], // from D1, D2 and D3 back to D0
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub offset: usize,
+    pub address: u8,
+    pub attributes: u8,
+    pub max_packet_size: u16, // raw wMaxPacketSize
+    pub interval: u8,
+    pub refresh: Option<u8>, // audio 1.0 endpoints only
+    pub synch_address: Option<u8>, // audio 1.0 endpoints only
+    pub superspeed_companion: Option<SuperSpeedCompanion>,
+    pub superspeedplus_isochronous_companion: Option<SuperSpeedPlusIsochronousCompanion>,
+    pub extra: Vec<RawDescriptor>, // class specific descriptors of this endpoint
+}
+
+// SuperSpeed endpoint companion (USB 3.2 9.6.7)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuperSpeedCompanion {
+    pub max_burst: u8, // packets per burst minus one
+    pub attributes: u8,
+    pub bytes_per_interval: u16,
+}
+
+// SuperSpeedPlus isochronous endpoint companion (USB 3.2 9.6.8)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuperSpeedPlusIsochronousCompanion {
+    pub bytes_per_interval: u32,
+}
+
+// a descriptor kept verbatim, header included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawDescriptor {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl RawDescriptor {
+    pub fn descriptor_type(&self) -> u8 {
+        self.bytes[1]
+    }
+
+    // bDescriptorSubtype of class specific descriptors
+    pub fn subtype(&self) -> Option<u8> {
+        self.bytes.get(2).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Out,
+    In,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferType {
+    Control,
+    Isochronous,
+    Bulk,
+    Interrupt,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Direction::Out => "OUT",
+            Direction::In => "IN",
+        })
+    }
+}
+
+impl fmt::Display for TransferType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TransferType::Control => "Control",
+            TransferType::Isochronous => "Isochronous",
+            TransferType::Bulk => "Bulk",
+            TransferType::Interrupt => "Interrupt",
+        })
+    }
+}
+
+impl Configuration {
+    pub const LENGTH: usize = 9;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        Self::parse_as(bytes, descriptor_type::CONFIGURATION)
+    }
+
+    // an other speed configuration (USB 2.0 9.6.4): laid out like a
+    // configuration, describing the device at the speed it is not running at
+    pub fn parse_other_speed(bytes: &[u8]) -> Result<Self, Error> {
+        Self::parse_as(bytes, descriptor_type::OTHER_SPEED_CONFIGURATION)
+    }
+
+    fn parse_as(bytes: &[u8], expected_type: u8) -> Result<Self, Error> {
+        let head = header(bytes, expected_type, Self::LENGTH)?;
+        let mut configuration = Configuration {
+            total_length: u16_at(head, 2),
+            num_interfaces: head[4],
+            configuration_value: head[5],
+            configuration_index: head[6],
+            attributes: head[7],
+            max_power: head[8],
+            extra: Vec::new(),
+            otg: None,
+            associations: Vec::new(),
+            interfaces: Vec::new(),
+            problems: Vec::new(),
+        };
+        let total_length = usize::from(configuration.total_length);
+        if total_length < head.len() {
+            return Err(Error::new(2, ErrorKind::InvalidValue {
+                field: "wTotalLength",
+                value: total_length as u32,
+            }))
+        }
+        if bytes.len() < total_length {
+            configuration.problems.push(Error::new(bytes.len(), ErrorKind::Truncated {
+                needed: total_length,
+                available: bytes.len(),
+            }));
+        }
+        configuration.walk(&bytes[..total_length.min(bytes.len())], head.len());
+        configuration.check_counts();
+        configuration.check_associations();
+        Ok(configuration)
+    }
+
+    fn walk(&mut self, bytes: &[u8], mut offset: usize) {
+        // where the next unknown descriptor goes
+        #[derive(Clone, Copy)]
+        enum Owner { Configuration, Interface, Endpoint }
+        let mut owner = Owner::Configuration;
+        // (interface, alternate setting) indices of the setting being filled
+        let mut current: Option<(usize, usize)> = None;
+        while offset < bytes.len() {
+            let rest = &bytes[offset..];
+            if rest.len() < 2 {
+                self.problems.push(Error::new(offset, ErrorKind::Truncated { needed: 2, available: rest.len() }));
+                return;
+            }
+            let length = usize::from(rest[0]);
+            if length < 2 {
+                // without a usable bLength nothing after this point can be located
+                self.problems.push(Error::new(offset, ErrorKind::InvalidLength { length: rest[0] }));
+                return;
+            }
+            if rest.len() < length {
+                self.problems.push(Error::new(offset, ErrorKind::Truncated { needed: length, available: rest.len() }));
+                return;
+            }
+            let descriptor = &rest[..length];
+            match descriptor[1] {
+                descriptor_type::INTERFACE => match AlternateSetting::parse(descriptor) {
+                    Ok(setting) => {
+                        current = Some(self.add_setting(AlternateSetting { offset, ..setting }));
+                        owner = Owner::Interface;
+                    },
+                    Err(err) => self.problems.push(err.at(offset)),
+                },
+                descriptor_type::ENDPOINT => match (Endpoint::parse(descriptor), current) {
+                    (Ok(endpoint), Some(current)) => {
+                        self.setting_mut(current).endpoints.push(Endpoint { offset, ..endpoint });
+                        owner = Owner::Endpoint;
+                    },
+                    (Ok(_), None) => {
+                        self.problems.push(Error::new(offset, ErrorKind::Misplaced {
+                            descriptor_type: descriptor_type::ENDPOINT,
+                        }));
+                        self.extra.push(RawDescriptor { offset, bytes: descriptor.to_vec() });
+                    },
+                    (Err(err), _) => self.problems.push(err.at(offset)),
+                },
+                descriptor_type::SUPERSPEED_ENDPOINT_COMPANION
+                | descriptor_type::SUPERSPEEDPLUS_ISOCHRONOUS_ENDPOINT_COMPANION => {
+                    let endpoint = match (owner, current) {
+                        (Owner::Endpoint, Some(current)) => self.setting_mut(current).endpoints.last_mut(),
+                        _ => None,
+                    };
+                    let attached = match endpoint {
+                        Some(endpoint) => endpoint.attach_companion(descriptor).map_err(|err| err.at(offset)),
+                        None => Err(Error::new(offset, ErrorKind::Misplaced { descriptor_type: descriptor[1] })),
+                    };
+                    if let Err(err) = attached {
+                        self.problems.push(err);
+                        self.extra.push(RawDescriptor { offset, bytes: descriptor.to_vec() });
+                    }
+                },
+                descriptor_type::OTG => match (Otg::parse(descriptor), self.otg) {
+                    (Ok(otg), None) => self.otg = Some(otg),
+                    (Ok(_), Some(_)) => {
+                        self.problems.push(Error::new(offset, ErrorKind::Misplaced { descriptor_type: descriptor_type::OTG }));
+                        self.extra.push(RawDescriptor { offset, bytes: descriptor.to_vec() });
+                    },
+                    (Err(err), _) => self.problems.push(err.at(offset)),
+                },
+                descriptor_type::INTERFACE_POWER => {
+                    // belongs right behind its interface descriptor
+                    let setting = match (owner, current) {
+                        (Owner::Interface, Some(current)) => Some(self.setting_mut(current)),
+                        _ => None,
+                    };
+                    let attached = match setting {
+                        Some(setting) if setting.power.is_none() => InterfacePower::parse(descriptor)
+                            .map(|power| setting.power = Some(power))
+                            .map_err(|err| err.at(offset)),
+                        _ => Err(Error::new(offset, ErrorKind::Misplaced { descriptor_type: descriptor_type::INTERFACE_POWER })),
+                    };
+                    if let Err(err) = attached {
+                        self.problems.push(err);
+                        self.extra.push(RawDescriptor { offset, bytes: descriptor.to_vec() });
+                    }
+                },
+                descriptor_type::INTERFACE_ASSOCIATION => match InterfaceAssociation::parse(descriptor) {
+                    Ok(association) => {
+                        self.associations.push(InterfaceAssociation { offset, ..association });
+                        owner = Owner::Configuration;
+                    },
+                    Err(err) => self.problems.push(err.at(offset)),
+                },
+                descriptor_type::CONFIGURATION | descriptor_type::DEVICE => {
+                    self.problems.push(Error::new(offset, ErrorKind::Misplaced { descriptor_type: descriptor[1] }));
+                },
+                _ => {
+                    let raw = RawDescriptor { offset, bytes: descriptor.to_vec() };
+                    match (owner, current) {
+                        (Owner::Endpoint, Some(current)) => {
+                            let setting = self.setting_mut(current);
+                            match setting.endpoints.last_mut() {
+                                Some(endpoint) => endpoint.extra.push(raw),
+                                None => setting.extra.push(raw),
+                            }
+                        },
+                        (Owner::Interface, Some(current)) => self.setting_mut(current).extra.push(raw),
+                        _ => self.extra.push(raw),
+                    }
+                },
+            }
+            offset += length;
+        }
+    }
+
+    fn add_setting(&mut self, setting: AlternateSetting) -> (usize, usize) {
+        let number = setting.interface_number;
+        match self.interfaces.iter().position(|interface| interface.number == number) {
+            Some(index) => {
+                let interface = &mut self.interfaces[index];
+                interface.alternate_settings.push(setting);
+                (index, interface.alternate_settings.len() - 1)
+            },
+            None => {
+                self.interfaces.push(Interface { number, alternate_settings: alloc::vec![setting] });
+                (self.interfaces.len() - 1, 0)
+            },
+        }
+    }
+
+    fn setting_mut(&mut self, (interface, setting): (usize, usize)) -> &mut AlternateSetting {
+        &mut self.interfaces[interface].alternate_settings[setting]
+    }
+
+    fn check_counts(&mut self) {
+        self.interfaces.sort_by_key(|interface| interface.number);
+        if self.interfaces.len() != usize::from(self.num_interfaces) {
+            self.problems.push(Error::new(4, ErrorKind::CountMismatch {
+                field: "bNumInterfaces",
+                declared: self.num_interfaces.into(),
+                found: self.interfaces.len(),
+            }));
+        }
+        for setting in self.interfaces.iter().flat_map(|interface| &interface.alternate_settings) {
+            if setting.endpoints.len() != usize::from(setting.num_endpoints) {
+                self.problems.push(Error::new(setting.offset + 4, ErrorKind::CountMismatch {
+                    field: "bNumEndpoints",
+                    declared: setting.num_endpoints.into(),
+                    found: setting.endpoints.len(),
+                }));
+            }
+        }
+        self.problems.sort_by_key(|problem| problem.offset);
+    }
+
+    // IADs naming interfaces that do not exist or claiming interfaces an
+    // earlier IAD already grouped
+    fn check_associations(&mut self) {
+        for (index, association) in self.associations.iter().enumerate() {
+            let found = association.interface_numbers()
+                .filter(|number| self.interfaces.iter().any(|interface| interface.number == *number))
+                .count();
+            if found != usize::from(association.interface_count) {
+                self.problems.push(Error::new(association.offset + 3, ErrorKind::CountMismatch {
+                    field: "bInterfaceCount",
+                    declared: association.interface_count.into(),
+                    found,
+                }));
+            }
+            let overlapping = self.associations[..index].iter()
+                .any(|earlier| earlier.interface_numbers().any(|number| association.covers(number)));
+            if overlapping {
+                self.problems.push(Error::new(association.offset + 2, ErrorKind::InvalidValue {
+                    field: "bFirstInterface (overlaps an earlier IAD)",
+                    value: association.first_interface.into(),
+                }));
+            }
+        }
+        self.problems.sort_by_key(|problem| problem.offset);
+    }
+
+    pub fn self_powered(&self) -> bool {
+        self.attributes & 0x40 != 0
+    }
+
+    pub fn remote_wakeup(&self) -> bool {
+        self.attributes & 0x20 != 0
+    }
+
+    // bMaxPower is in 2 mA units, or 8 mA units when operating at SuperSpeed
+    pub fn max_power_milliamps(&self, super_speed: bool) -> u16 {
+        u16::from(self.max_power) * if super_speed { 8 } else { 2 }
+    }
+
+    pub fn alternate_settings(&self) -> impl Iterator<Item = &AlternateSetting> {
+        self.interfaces.iter().flat_map(|interface| &interface.alternate_settings)
+    }
+
+    // the functions of the configuration in interface order; interfaces no IAD
+    // covers each form a function of their own
+    pub fn functions(&self) -> Vec<Function<'_>> {
+        let mut functions: Vec<Function<'_>> = Vec::new();
+        for interface in &self.interfaces {
+            let association = self.associations.iter().find(|association| association.covers(interface.number));
+            match functions.iter_mut().find(|function| association.is_some() && function.association == association) {
+                Some(function) => function.interfaces.push(interface),
+                None => functions.push(Function { association, interfaces: alloc::vec![interface] }),
+            }
+        }
+        functions
+    }
+}
+
+impl InterfaceAssociation {
+    pub const LENGTH: usize = 8;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::INTERFACE_ASSOCIATION, Self::LENGTH)?;
+        if bytes[3] == 0 {
+            return Err(Error::new(3, ErrorKind::InvalidValue { field: "bInterfaceCount", value: 0 }))
+        }
+        Ok(InterfaceAssociation {
+            offset: 0,
+            first_interface: bytes[2],
+            interface_count: bytes[3],
+            function_class: bytes[4],
+            function_subclass: bytes[5],
+            function_protocol: bytes[6],
+            function_index: bytes[7],
+        })
+    }
+
+    pub fn interface_numbers(&self) -> impl Iterator<Item = u8> {
+        let first = self.first_interface;
+        (0..self.interface_count).map_while(move |index| first.checked_add(index))
+    }
+
+    pub fn covers(&self, interface_number: u8) -> bool {
+        interface_number >= self.first_interface
+            && usize::from(interface_number - self.first_interface) < usize::from(self.interface_count)
+    }
+}
+
+impl AlternateSetting {
+    pub const LENGTH: usize = 9;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::INTERFACE, Self::LENGTH)?;
+        Ok(AlternateSetting {
+            offset: 0,
+            interface_number: bytes[2],
+            alternate_setting: bytes[3],
+            num_endpoints: bytes[4],
+            interface_class: bytes[5],
+            interface_subclass: bytes[6],
+            interface_protocol: bytes[7],
+            interface_index: bytes[8],
+            power: None,
+            extra: Vec::new(),
+            endpoints: Vec::new(),
+        })
+    }
+}
+
+impl Otg {
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::OTG, 3)?;
+        let otg_version = match bytes.len() {
+            3 => None,
+            length if length >= 5 => Some(Bcd(u16_at(bytes, 3))),
+            length => return Err(Error::new(0, ErrorKind::InvalidLength { length: length as u8 })),
+        };
+        Ok(Otg { attributes: bytes[2], otg_version })
+    }
+
+    // session request protocol
+    pub fn srp(&self) -> bool {
+        self.attributes & 0x01 != 0
+    }
+
+    // host negotiation protocol
+    pub fn hnp(&self) -> bool {
+        self.attributes & 0x02 != 0
+    }
+
+    // attach detection protocol, OTG 2.0 only
+    pub fn adp(&self) -> bool {
+        self.attributes & 0x04 != 0
+    }
+}
+
+impl InterfacePower {
+    pub const LENGTH: usize = 19;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::INTERFACE_POWER, Self::LENGTH)?;
+        Ok(InterfacePower {
+            capabilities: bytes[2],
+            self_power_consumed_d0: u32::from_le_bytes([bytes[3], bytes[4], bytes[5], 0]),
+            power_summary_id: bytes[6],
+            bus_power_saved: [bytes[7], bytes[9], bytes[11]],
+            self_power_saved: [bytes[8], bytes[10], bytes[12]],
+            transition_time: [u16_at(bytes, 13), u16_at(bytes, 15), u16_at(bytes, 17)],
+        })
+    }
+
+    // whether the interface accepts a request to enter power state D`state`
+    pub fn supports(&self, state: u8) -> bool {
+        state < 4 && self.capabilities & 1 << state != 0
+    }
+
+    // whether the interface can wake the host from D`state`
+    pub fn wakeup_from(&self, state: u8) -> bool {
+        matches!(state, 1 | 2) && self.capabilities & 1 << (state + 3) != 0
+    }
+}
+
+impl Endpoint {
+    pub const LENGTH: usize = 7;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::ENDPOINT, Self::LENGTH)?;
+        let (refresh, synch_address) = if bytes.len() >= 9 {
+            (Some(bytes[7]), Some(bytes[8]))
+        } else {
+            (None, None)
+        };
+        Ok(Endpoint {
+            offset: 0,
+            address: bytes[2],
+            attributes: bytes[3],
+            max_packet_size: u16_at(bytes, 4),
+            interval: bytes[6],
+            refresh,
+            synch_address,
+            superspeed_companion: None,
+            superspeedplus_isochronous_companion: None,
+            extra: Vec::new(),
+        })
+    }
+
+    fn attach_companion(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        if bytes[1] == descriptor_type::SUPERSPEED_ENDPOINT_COMPANION {
+            if self.superspeed_companion.is_some() {
+                return Err(Error::new(0, ErrorKind::Misplaced { descriptor_type: bytes[1] }))
+            }
+            self.superspeed_companion = Some(SuperSpeedCompanion::parse(bytes)?);
+        } else {
+            // only valid behind a SuperSpeed companion announcing it
+            if !self.superspeed_companion.is_some_and(|companion| companion.ssp_isochronous())
+                || self.superspeedplus_isochronous_companion.is_some()
+            {
+                return Err(Error::new(0, ErrorKind::Misplaced { descriptor_type: bytes[1] }))
+            }
+            self.superspeedplus_isochronous_companion = Some(SuperSpeedPlusIsochronousCompanion::parse(bytes)?);
+        }
+        Ok(())
+    }
+
+    pub fn number(&self) -> u8 {
+        self.address & 0x0F
+    }
+
+    pub fn direction(&self) -> Direction {
+        if self.address & 0x80 != 0 { Direction::In } else { Direction::Out }
+    }
+
+    pub fn transfer_type(&self) -> TransferType {
+        match self.attributes & 0x03 {
+            0 => TransferType::Control,
+            1 => TransferType::Isochronous,
+            2 => TransferType::Bulk,
+            _ => TransferType::Interrupt,
+        }
+    }
+
+    // whether wMaxPacketSize is one USB 2.0 (5.5.3, 5.6.3, 5.7.3 and 5.8.3)
+    // allows at full or high speed
+    pub fn valid_max_packet_size(&self, high_speed: bool) -> bool {
+        let size = self.max_packet_bytes();
+        if !high_speed && self.additional_transactions() > 0 {
+            return false;
+        }
+        match (self.transfer_type(), high_speed) {
+            (TransferType::Control, false) | (TransferType::Bulk, false) => matches!(size, 8 | 16 | 32 | 64),
+            (TransferType::Control, true) => size == 64,
+            (TransferType::Bulk, true) => size == 512,
+            (TransferType::Interrupt, false) => size <= 64,
+            (TransferType::Isochronous, false) => size <= 1023,
+            (TransferType::Interrupt, true) | (TransferType::Isochronous, true) => size <= 1024,
+        }
+    }
+
+    // packet size in bytes, without the high bandwidth multiplier bits
+    pub fn max_packet_bytes(&self) -> u16 {
+        self.max_packet_size & 0x07FF
+    }
+
+    // additional transactions per microframe of high speed, high bandwidth endpoints
+    pub fn additional_transactions(&self) -> u8 {
+        ((self.max_packet_size >> 11) & 0x03) as u8
+    }
+
+    // number of streams of a SuperSpeed bulk endpoint, if it supports any
+    pub fn max_streams(&self) -> Option<u32> {
+        let companion = self.superspeed_companion?;
+        match (self.transfer_type(), companion.attributes & 0x1F) {
+            (TransferType::Bulk, exponent) if exponent > 0 => Some(1 << exponent),
+            _ => None,
+        }
+    }
+
+    // most packets the endpoint moves in one service interval (or one burst for bulk)
+    pub fn max_packets_per_interval(&self) -> u32 {
+        match self.superspeed_companion {
+            Some(companion) => {
+                let burst = u32::from(companion.max_burst) + 1;
+                match self.transfer_type() {
+                    TransferType::Isochronous if !companion.ssp_isochronous() => burst * (u32::from(companion.mult()) + 1),
+                    _ => burst,
+                }
+            },
+            None => match self.transfer_type() {
+                TransferType::Isochronous | TransferType::Interrupt => u32::from(self.additional_transactions()) + 1,
+                _ => 1,
+            },
+        }
+    }
+
+    // effective maximum bytes per service interval: taken from the companion
+    // descriptors for SuperSpeed periodic endpoints, otherwise computed from
+    // wMaxPacketSize and the burst / high bandwidth multipliers
+    pub fn max_bytes_per_interval(&self) -> u32 {
+        match (self.superspeed_companion, self.superspeedplus_isochronous_companion) {
+            (Some(companion), Some(ssp)) if companion.ssp_isochronous() => ssp.bytes_per_interval,
+            (Some(companion), _) if matches!(self.transfer_type(), TransferType::Isochronous | TransferType::Interrupt) => {
+                companion.bytes_per_interval.into()
+            },
+            _ => u32::from(self.max_packet_bytes()) * self.max_packets_per_interval(),
+        }
+    }
+}
+
+impl SuperSpeedCompanion {
+    pub const LENGTH: usize = 6;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::SUPERSPEED_ENDPOINT_COMPANION, Self::LENGTH)?;
+        if bytes[2] > 15 {
+            return Err(Error::new(2, ErrorKind::InvalidValue { field: "bMaxBurst", value: bytes[2].into() }))
+        }
+        Ok(SuperSpeedCompanion {
+            max_burst: bytes[2],
+            attributes: bytes[3],
+            bytes_per_interval: u16_at(bytes, 4),
+        })
+    }
+
+    // isochronous endpoints: bursts per service interval minus one
+    pub fn mult(&self) -> u8 {
+        self.attributes & 0x03
+    }
+
+    // isochronous endpoints: a SuperSpeedPlus isochronous companion follows
+    pub fn ssp_isochronous(&self) -> bool {
+        self.attributes & 0x80 != 0
+    }
+}
+
+impl SuperSpeedPlusIsochronousCompanion {
+    pub const LENGTH: usize = 8;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::SUPERSPEEDPLUS_ISOCHRONOUS_ENDPOINT_COMPANION, Self::LENGTH)?;
+        Ok(SuperSpeedPlusIsochronousCompanion { bytes_per_interval: u32_at(bytes, 4) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const HID_INTERFACE: [u8; 9] = [0x09, 0x04, 0x00, 0x00, 0x01, 0x03, 0x01, 0x01, 0x00];
+    const HID: [u8; 9] = [0x09, 0x21, 0x10, 0x01, 0x00, 0x01, 0x22, 0x3F, 0x00];
+    const INTERRUPT_IN: [u8; 7] = [0x07, 0x05, 0x81, 0x03, 0x08, 0x00, 0x0A];
+
+    // a configuration header with wTotalLength and bNumInterfaces filled in
+    // for `descriptors`
+    fn with_header(num_interfaces: u8, descriptors: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = vec![0x09, 0x02, 0x00, 0x00, num_interfaces, 0x01, 0x00, 0xA0, 0x32];
+        for descriptor in descriptors {
+            bytes.extend_from_slice(descriptor);
+        }
+        let total_length = bytes.len() as u16;
+        bytes[2..4].copy_from_slice(&total_length.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn walks_interfaces_and_endpoints() {
+        let bytes = with_header(1, &[&HID_INTERFACE, &HID, &INTERRUPT_IN]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert!(configuration.problems.is_empty(), "{:?}", configuration.problems);
+        assert_eq!(configuration.total_length, 34);
+        let setting = &configuration.interfaces[0].alternate_settings[0];
+        assert_eq!((setting.offset, setting.interface_class), (9, 0x03));
+        assert_eq!(setting.extra, [RawDescriptor { offset: 18, bytes: HID.to_vec() }]);
+        assert_eq!((setting.endpoints[0].offset, setting.endpoints[0].address), (27, 0x81));
+    }
+
+    #[test]
+    fn short_buffer() {
+        assert_eq!(
+            Configuration::parse(&[0x09]),
+            Err(Error::new(0, ErrorKind::Truncated { needed: 2, available: 1 })),
+        );
+        let bytes = with_header(1, &[]);
+        assert_eq!(
+            Configuration::parse(&bytes[..5]),
+            Err(Error::new(0, ErrorKind::Truncated { needed: 9, available: 5 })),
+        );
+        // a descriptor cut off inside the configuration is a problem where it starts
+        let bytes = with_header(1, &[&HID_INTERFACE, &INTERRUPT_IN[..4]]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert!(configuration.problems.contains(&Error::new(18, ErrorKind::Truncated { needed: 7, available: 4 })));
+    }
+
+    #[test]
+    fn wrong_length() {
+        let mut bytes = with_header(1, &[&HID_INTERFACE]);
+        bytes[0] = 8;
+        assert_eq!(Configuration::parse(&bytes), Err(Error::new(0, ErrorKind::InvalidLength { length: 8 })));
+        // an unusable bLength inside stops the walk there
+        let mut interface = HID_INTERFACE;
+        interface[0] = 0;
+        let bytes = with_header(1, &[&interface]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert_eq!(configuration.problems, [
+            Error::new(4, ErrorKind::CountMismatch { field: "bNumInterfaces", declared: 1, found: 0 }),
+            Error::new(9, ErrorKind::InvalidLength { length: 0 }),
+        ]);
+    }
+
+    #[test]
+    fn total_length_mismatch() {
+        let mut bytes = with_header(1, &[&HID_INTERFACE, &HID, &INTERRUPT_IN]);
+        bytes[2..4].copy_from_slice(&5u16.to_le_bytes());
+        assert_eq!(
+            Configuration::parse(&bytes),
+            Err(Error::new(2, ErrorKind::InvalidValue { field: "wTotalLength", value: 5 })),
+        );
+        // promising more than was returned
+        let bytes = with_header(1, &[&HID_INTERFACE, &HID, &INTERRUPT_IN]);
+        let configuration = Configuration::parse(&bytes[..20]).unwrap();
+        assert!(configuration.problems.contains(&Error::new(20, ErrorKind::Truncated { needed: 34, available: 20 })));
+        // bytes past wTotalLength are not walked
+        let mut bytes = with_header(1, &[&HID_INTERFACE]);
+        bytes.extend_from_slice(&INTERRUPT_IN);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert_eq!(configuration.problems, [
+            Error::new(13, ErrorKind::CountMismatch { field: "bNumEndpoints", declared: 1, found: 0 }),
+        ]);
+    }
+
+    #[test]
+    fn endpoint_before_interface() {
+        let bytes = with_header(1, &[&INTERRUPT_IN, &HID_INTERFACE]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert_eq!(configuration.problems, [
+            Error::new(9, ErrorKind::Misplaced { descriptor_type: descriptor_type::ENDPOINT }),
+            Error::new(20, ErrorKind::CountMismatch { field: "bNumEndpoints", declared: 1, found: 0 }),
+        ]);
+        // kept so it can still be shown
+        assert_eq!(configuration.extra, [RawDescriptor { offset: 9, bytes: INTERRUPT_IN.to_vec() }]);
+    }
+
+    fn association(first_interface: u8, interface_count: u8, function_class: u8) -> [u8; 8] {
+        [0x08, 0x0B, first_interface, interface_count, function_class, 0x00, 0x00, 0x00]
+    }
+
+    // an alternate setting 0 without endpoints
+    fn interface(number: u8, class: u8) -> [u8; 9] {
+        [0x09, 0x04, number, 0x00, 0x00, class, 0x00, 0x00, 0x00]
+    }
+
+    // (first interface of the IAD, interfaces) of every function
+    fn functions(configuration: &Configuration) -> Vec<(Option<u8>, Vec<u8>)> {
+        configuration.functions().iter()
+            .map(|function| (
+                function.association.map(|association| association.first_interface),
+                function.interfaces.iter().map(|interface| interface.number).collect(),
+            ))
+            .collect()
+    }
+
+    #[test]
+    fn two_association_composite() {
+        // CDC ACM on interfaces 0-1 and UVC on 2-3
+        let bytes = with_header(4, &[
+            &association(0, 2, 0x02), &interface(0, 0x02), &interface(1, 0x0A),
+            &association(2, 2, 0x0E), &interface(2, 0x0E), &interface(3, 0x0E),
+        ]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert!(configuration.problems.is_empty(), "{:?}", configuration.problems);
+        assert_eq!(configuration.associations.len(), 2);
+        assert_eq!(functions(&configuration), [(Some(0), vec![0, 1]), (Some(2), vec![2, 3])]);
+    }
+
+    #[test]
+    fn overlapping_associations() {
+        // the second IAD claims interface 1, which the first already covers
+        let bytes = with_header(3, &[
+            &association(0, 2, 0x02), &interface(0, 0x02), &interface(1, 0x0A),
+            &association(1, 2, 0x0E), &interface(2, 0x0E),
+        ]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert_eq!(configuration.problems, [Error::new(37, ErrorKind::InvalidValue {
+            field: "bFirstInterface (overlaps an earlier IAD)",
+            value: 1,
+        })]);
+        // an interface goes to the first IAD covering it
+        assert_eq!(functions(&configuration), [(Some(0), vec![0, 1]), (Some(1), vec![2])]);
+    }
+
+    #[test]
+    fn interface_outside_associations() {
+        let bytes = with_header(3, &[
+            &association(0, 2, 0x02), &interface(0, 0x02), &interface(1, 0x0A),
+            &interface(2, 0x03),
+        ]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert!(configuration.problems.is_empty(), "{:?}", configuration.problems);
+        assert_eq!(functions(&configuration), [(Some(0), vec![0, 1]), (None, vec![2])]);
+        // an IAD promising more interfaces than there are
+        let bytes = with_header(2, &[&association(0, 3, 0x02), &interface(0, 0x02), &interface(1, 0x0A)]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert_eq!(configuration.problems, [
+            Error::new(12, ErrorKind::CountMismatch { field: "bInterfaceCount", declared: 3, found: 2 }),
+        ]);
+    }
+
+    // the endpoints of a single interface holding `descriptors`
+    fn endpoints(num_endpoints: u8, descriptors: &[&[u8]]) -> Vec<Endpoint> {
+        let interface = [0x09, 0x04, 0x00, 0x00, num_endpoints, 0xFF, 0x00, 0x00, 0x00];
+        let mut all: Vec<&[u8]> = vec![&interface];
+        all.extend_from_slice(descriptors);
+        let configuration = Configuration::parse(&with_header(1, &all)).unwrap();
+        assert!(configuration.problems.is_empty(), "{:?}", configuration.problems);
+        configuration.interfaces[0].alternate_settings[0].endpoints.clone()
+    }
+
+    #[test]
+    fn superspeed_bulk_streams() {
+        // 1024 byte packets, bursts of 16, 2^4 streams
+        let endpoint = &endpoints(1, &[
+            &[0x07, 0x05, 0x81, 0x02, 0x00, 0x04, 0x00],
+            &[0x06, 0x30, 0x0F, 0x04, 0x00, 0x00],
+        ])[0];
+        assert_eq!(endpoint.max_streams(), Some(16));
+        assert_eq!(endpoint.max_packets_per_interval(), 16);
+        assert_eq!(endpoint.max_bytes_per_interval(), 16 * 1024);
+        // without streams
+        let endpoint = &endpoints(1, &[
+            &[0x07, 0x05, 0x81, 0x02, 0x00, 0x04, 0x00],
+            &[0x06, 0x30, 0x00, 0x00, 0x00, 0x00],
+        ])[0];
+        assert_eq!(endpoint.max_streams(), None);
+        assert_eq!(endpoint.max_bytes_per_interval(), 1024);
+    }
+
+    #[test]
+    fn superspeed_isochronous_mult() {
+        // bursts of 4, three bursts per interval, wBytesPerInterval 12288
+        let endpoint = &endpoints(1, &[
+            &[0x07, 0x05, 0x82, 0x01, 0x00, 0x04, 0x01],
+            &[0x06, 0x30, 0x03, 0x02, 0x00, 0x30],
+        ])[0];
+        assert_eq!(endpoint.max_streams(), None);
+        assert_eq!(endpoint.max_packets_per_interval(), 12);
+        assert_eq!(endpoint.max_bytes_per_interval(), 12288);
+        // a device may reserve less than the packets allow
+        let endpoint = &endpoints(1, &[
+            &[0x07, 0x05, 0x82, 0x01, 0x00, 0x04, 0x01],
+            &[0x06, 0x30, 0x03, 0x02, 0x00, 0x20],
+        ])[0];
+        assert_eq!(endpoint.max_bytes_per_interval(), 8192);
+    }
+
+    #[test]
+    fn superspeedplus_isochronous_companion() {
+        // the SSP companion's dwBytesPerInterval replaces wBytesPerInterval and Mult
+        let endpoint = &endpoints(1, &[
+            &[0x07, 0x05, 0x83, 0x01, 0x00, 0x04, 0x01],
+            &[0x06, 0x30, 0x0F, 0x82, 0x00, 0x00],
+            &[0x08, 0x31, 0x00, 0x00, 0x00, 0x80, 0x01, 0x00],
+        ])[0];
+        assert_eq!(endpoint.superspeedplus_isochronous_companion.unwrap().bytes_per_interval, 98304);
+        assert_eq!(endpoint.max_packets_per_interval(), 16);
+        assert_eq!(endpoint.max_bytes_per_interval(), 98304);
+        // only valid behind a companion announcing it
+        let bytes = with_header(1, &[
+            &[0x09, 0x04, 0x00, 0x00, 0x01, 0xFF, 0x00, 0x00, 0x00],
+            &[0x07, 0x05, 0x83, 0x01, 0x00, 0x04, 0x01],
+            &[0x06, 0x30, 0x0F, 0x02, 0x00, 0x00],
+            &[0x08, 0x31, 0x00, 0x00, 0x00, 0x80, 0x01, 0x00],
+        ]);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert_eq!(configuration.problems, [Error::new(31, ErrorKind::Misplaced { descriptor_type: 0x31 })]);
+    }
+
+    #[test]
+    fn high_bandwidth_interrupt() {
+        // 1024 bytes plus two additional transactions per microframe
+        let endpoint = &endpoints(1, &[&[0x07, 0x05, 0x84, 0x03, 0x00, 0x14, 0x01]])[0];
+        assert_eq!((endpoint.max_packet_bytes(), endpoint.additional_transactions()), (1024, 2));
+        assert_eq!(endpoint.max_packets_per_interval(), 3);
+        assert_eq!(endpoint.max_bytes_per_interval(), 3072);
+        assert!(endpoint.valid_max_packet_size(true));
+        // high bandwidth only exists at high speed
+        assert!(!endpoint.valid_max_packet_size(false));
+        // bulk endpoints have no additional transactions
+        let endpoint = &endpoints(1, &[&[0x07, 0x05, 0x01, 0x02, 0x00, 0x02, 0x00]])[0];
+        assert_eq!(endpoint.max_bytes_per_interval(), 512);
+    }
+}
@@ -0,0 +1,183 @@
+use super::{descriptor_type, header, u16_at, Bcd, Error, ErrorKind};
+
+// standard device descriptor (USB 2.0 9.6.1, USB 3.2 9.6.1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceDescriptor {
+    pub usb_version: Bcd,
+    pub device_class: u8,
+    pub device_subclass: u8,
+    pub device_protocol: u8,
+    pub max_packet_size0: u8, // raw bMaxPacketSize0, an exponent for SuperSpeed devices
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub device_version: Bcd,
+    pub manufacturer_index: u8,
+    pub product_index: u8,
+    pub serial_number_index: u8,
+    pub num_configurations: u8,
+}
+
+impl DeviceDescriptor {
+    pub const LENGTH: usize = 18;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::DEVICE, Self::LENGTH)?;
+        if bytes.len() != Self::LENGTH {
+            return Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+        }
+        let descriptor = DeviceDescriptor {
+            usb_version: Bcd(u16_at(bytes, 2)),
+            device_class: bytes[4],
+            device_subclass: bytes[5],
+            device_protocol: bytes[6],
+            max_packet_size0: bytes[7],
+            vendor_id: u16_at(bytes, 8),
+            product_id: u16_at(bytes, 10),
+            device_version: Bcd(u16_at(bytes, 12)),
+            manufacturer_index: bytes[14],
+            product_index: bytes[15],
+            serial_number_index: bytes[16],
+            num_configurations: bytes[17],
+        };
+        let valid_packet_size = match descriptor.max_packet_size0 {
+            8 | 16 | 32 | 64 => true,
+            9 => descriptor.usb_version >= Bcd(0x0300),
+            _ => false,
+        };
+        if !valid_packet_size {
+            return Err(Error::new(7, ErrorKind::InvalidValue {
+                field: "bMaxPacketSize0",
+                value: descriptor.max_packet_size0.into(),
+            }))
+        }
+        Ok(descriptor)
+    }
+
+    // endpoint zero packet size in bytes
+    pub fn max_packet_size0_bytes(&self) -> u16 {
+        if self.usb_version >= Bcd(0x0300) && self.max_packet_size0 == 9 {
+            1 << 9
+        } else {
+            self.max_packet_size0.into()
+        }
+    }
+}
+
+// device qualifier (USB 2.0 9.6.2): the fields of the device descriptor that
+// change when a high speed capable device runs at the other speed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceQualifier {
+    pub usb_version: Bcd,
+    pub device_class: u8,
+    pub device_subclass: u8,
+    pub device_protocol: u8,
+    pub max_packet_size0: u8,
+    pub num_configurations: u8,
+}
+
+impl DeviceQualifier {
+    pub const LENGTH: usize = 10;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::DEVICE_QUALIFIER, Self::LENGTH)?;
+        let qualifier = DeviceQualifier {
+            usb_version: Bcd(u16_at(bytes, 2)),
+            device_class: bytes[4],
+            device_subclass: bytes[5],
+            device_protocol: bytes[6],
+            max_packet_size0: bytes[7],
+            num_configurations: bytes[8],
+        };
+        if !matches!(qualifier.max_packet_size0, 8 | 16 | 32 | 64) {
+            return Err(Error::new(7, ErrorKind::InvalidValue {
+                field: "bMaxPacketSize0",
+                value: qualifier.max_packet_size0.into(),
+            }))
+        }
+        Ok(qualifier)
+    }
+}
+
+// debug descriptor (USB 2.0 Debug Device 1.0): the endpoints of the debug
+// port, only ever returned through GET_DESCRIPTOR
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugDescriptor {
+    pub debug_in_endpoint: u8,
+    pub debug_out_endpoint: u8,
+}
+
+impl DebugDescriptor {
+    pub const LENGTH: usize = 4;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, descriptor_type::DEBUG, Self::LENGTH)?;
+        Ok(DebugDescriptor { debug_in_endpoint: bytes[2], debug_out_endpoint: bytes[3] })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a full speed keyboard
+    const DEVICE: [u8; 18] = [
+        0x12, 0x01, 0x10, 0x01, 0x00, 0x00, 0x00, 0x08, 0x6D, 0x04, 0x1C, 0xC3, 0x00, 0x64, 0x01, 0x02, 0x00, 0x01,
+    ];
+
+    #[test]
+    fn parses_fields() {
+        let device = DeviceDescriptor::parse(&DEVICE).unwrap();
+        assert_eq!(device.usb_version, Bcd(0x0110));
+        assert_eq!((device.vendor_id, device.product_id), (0x046D, 0xC31C));
+        assert_eq!(device.max_packet_size0_bytes(), 8);
+        assert_eq!((device.manufacturer_index, device.product_index, device.serial_number_index), (1, 2, 0));
+        assert_eq!(device.num_configurations, 1);
+    }
+
+    #[test]
+    fn short_buffer() {
+        assert_eq!(
+            DeviceDescriptor::parse(&DEVICE[..1]),
+            Err(Error::new(0, ErrorKind::Truncated { needed: 2, available: 1 })),
+        );
+        assert_eq!(
+            DeviceDescriptor::parse(&DEVICE[..10]),
+            Err(Error::new(0, ErrorKind::Truncated { needed: 18, available: 10 })),
+        );
+    }
+
+    #[test]
+    fn wrong_length() {
+        let mut bytes = DEVICE;
+        bytes[0] = 17;
+        assert_eq!(DeviceDescriptor::parse(&bytes), Err(Error::new(0, ErrorKind::InvalidLength { length: 17 })));
+        // longer than a device descriptor can be is just as wrong
+        let mut bytes = DEVICE.to_vec();
+        bytes[0] = 19;
+        bytes.push(0);
+        assert_eq!(DeviceDescriptor::parse(&bytes), Err(Error::new(0, ErrorKind::InvalidLength { length: 19 })));
+    }
+
+    #[test]
+    fn wrong_type() {
+        let mut bytes = DEVICE;
+        bytes[1] = descriptor_type::CONFIGURATION;
+        assert_eq!(
+            DeviceDescriptor::parse(&bytes),
+            Err(Error::new(1, ErrorKind::UnexpectedType { expected: descriptor_type::DEVICE, found: 0x02 })),
+        );
+    }
+
+    #[test]
+    fn max_packet_size0() {
+        let mut bytes = DEVICE;
+        bytes[7] = 9;
+        assert_eq!(
+            DeviceDescriptor::parse(&bytes),
+            Err(Error::new(7, ErrorKind::InvalidValue { field: "bMaxPacketSize0", value: 9 })),
+        );
+        // an exponent from USB 3.0 on
+        bytes[2..4].copy_from_slice(&[0x00, 0x03]);
+        assert_eq!(DeviceDescriptor::parse(&bytes).unwrap().max_packet_size0_bytes(), 512);
+    }
+}
@@ -0,0 +1,90 @@
+use super::{
+    configuration::{AlternateSetting, Configuration, RawDescriptor},
+    header, u16_at, Bcd, Error, ErrorKind,
+};
+
+// Device Firmware Upgrade 1.1: an application specific interface whose
+// protocol tells whether the device runs its firmware (and can be detached
+// into DFU mode) or is in DFU mode waiting for an image.
+
+pub const CLASS_APPLICATION_SPECIFIC: u8 = 0xFE;
+pub const SUBCLASS_DFU: u8 = 0x01;
+pub const PROTOCOL_RUNTIME: u8 = 0x01;
+pub const PROTOCOL_DFU_MODE: u8 = 0x02;
+
+pub const FUNCTIONAL: u8 = 0x21;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DfuMode {
+    Runtime,
+    Dfu,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DfuFunctional {
+    pub attributes: u8,
+    pub detach_timeout: u16, // ms
+    pub transfer_size: u16, // bytes per control write
+    pub dfu_version: Option<Bcd>, // missing from DFU 1.0 descriptors
+}
+
+impl DfuMode {
+    // the mode `setting` advertises if it is a DFU interface
+    pub fn of(setting: &AlternateSetting) -> Option<Self> {
+        if (setting.interface_class, setting.interface_subclass) != (CLASS_APPLICATION_SPECIFIC, SUBCLASS_DFU) {
+            return None;
+        }
+        match setting.interface_protocol {
+            PROTOCOL_RUNTIME => Some(DfuMode::Runtime),
+            PROTOCOL_DFU_MODE => Some(DfuMode::Dfu),
+            _ => None,
+        }
+    }
+}
+
+impl DfuFunctional {
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, FUNCTIONAL, 7)?;
+        let dfu_version = match bytes.len() {
+            7 => None,
+            length if length >= 9 => Some(Bcd(u16_at(bytes, 7))),
+            length => return Err(Error::new(0, ErrorKind::InvalidLength { length: length as u8 })),
+        };
+        Ok(DfuFunctional {
+            attributes: bytes[2],
+            detach_timeout: u16_at(bytes, 3),
+            transfer_size: u16_at(bytes, 5),
+            dfu_version,
+        })
+    }
+
+    pub fn can_download(&self) -> bool {
+        self.attributes & 0x01 != 0
+    }
+
+    pub fn can_upload(&self) -> bool {
+        self.attributes & 0x02 != 0
+    }
+
+    // the device stays responsive to DFU requests after manifesting new firmware
+    pub fn manifestation_tolerant(&self) -> bool {
+        self.attributes & 0x04 != 0
+    }
+
+    // the device detaches and reattaches by itself on DFU_DETACH, no bus reset needed
+    pub fn will_detach(&self) -> bool {
+        self.attributes & 0x08 != 0
+    }
+}
+
+// the DFU functional descriptor among the class specific descriptors of an interface
+pub fn find_functional_descriptor(extra: &[RawDescriptor]) -> Option<Result<DfuFunctional, Error>> {
+    extra.iter()
+        .find(|raw| raw.descriptor_type() == FUNCTIONAL)
+        .map(|raw| DfuFunctional::parse(&raw.bytes).map_err(|err| err.at(raw.offset)))
+}
+
+// the first DFU interface of `configuration` with the mode it advertises
+pub fn dfu_interface(configuration: &Configuration) -> Option<(&AlternateSetting, DfuMode)> {
+    configuration.alternate_settings().find_map(|setting| Some((setting, DfuMode::of(setting)?)))
+}
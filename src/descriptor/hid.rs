@@ -0,0 +1,437 @@
+use super::{configuration::RawDescriptor, header, u16_at, Bcd, Error, ErrorKind};
+use alloc::vec::Vec;
+
+pub mod usage_tables;
+
+// HID class descriptor (HID 1.11 6.2.1), found behind a HID interface descriptor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HidDescriptor {
+    pub hid_version: Bcd,
+    pub country_code: u8,
+    pub descriptors: Vec<ClassDescriptor>, // at least the report descriptor
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassDescriptor {
+    pub descriptor_type: u8,
+    pub length: u16,
+}
+
+// A report descriptor decoded into its short (and long) items. Like the
+// configuration walker the decoder keeps going after a problem and collects
+// everything odd in `problems`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportDescriptor {
+    pub items: Vec<Item>,
+    pub problems: Vec<Error>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub offset: usize,
+    pub bytes: Vec<u8>, // the whole item, prefix included
+    pub kind: ItemKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemKind {
+    // main items
+    Input(u32),
+    Output(u32),
+    Feature(u32),
+    Collection(u8),
+    EndCollection,
+    // global items
+    UsagePage(u16),
+    LogicalMinimum(i32),
+    LogicalMaximum(i32),
+    PhysicalMinimum(i32),
+    PhysicalMaximum(i32),
+    UnitExponent(i32),
+    Unit(u32),
+    ReportSize(u32),
+    ReportId(u32),
+    ReportCount(u32),
+    Push,
+    Pop,
+    // local items; a 4 byte usage carries its own page in the upper half
+    Usage { page: Option<u16>, id: u16 },
+    UsageMinimum { page: Option<u16>, id: u16 },
+    UsageMaximum { page: Option<u16>, id: u16 },
+    DesignatorIndex(u32),
+    DesignatorMinimum(u32),
+    DesignatorMaximum(u32),
+    StringIndex(u32),
+    StringMinimum(u32),
+    StringMaximum(u32),
+    Delimiter(u32),
+    Long { tag: u8 },
+    Reserved { item_type: u8, tag: u8, data: u32 },
+}
+
+// a usage with its page resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Usage {
+    pub page: u16,
+    pub id: u16,
+}
+
+// total size of the input, output and feature reports sharing one report ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Report {
+    pub id: Option<u8>, // None when the descriptor declares no report IDs
+    pub input_bits: u32,
+    pub output_bits: u32,
+    pub feature_bits: u32,
+}
+
+pub mod report_type {
+    pub const HID: u8 = 0x21;
+    pub const REPORT: u8 = 0x22;
+    pub const PHYSICAL: u8 = 0x23;
+}
+
+impl HidDescriptor {
+    pub const LENGTH: usize = 9;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, report_type::HID, Self::LENGTH)?;
+        let count = usize::from(bytes[5]);
+        if count == 0 || bytes.len() < 6 + 3 * count {
+            return Err(Error::new(5, ErrorKind::CountMismatch {
+                field: "bNumDescriptors",
+                declared: count as u32,
+                found: (bytes.len() - 6) / 3,
+            }))
+        }
+        let descriptors = bytes[6..6 + 3 * count].chunks_exact(3)
+            .map(|chunk| ClassDescriptor { descriptor_type: chunk[0], length: u16_at(chunk, 1) })
+            .collect();
+        Ok(HidDescriptor {
+            hid_version: Bcd(u16_at(bytes, 2)),
+            country_code: bytes[4],
+            descriptors,
+        })
+    }
+
+    // wDescriptorLength of the (first) report descriptor
+    pub fn report_descriptor_length(&self) -> Option<u16> {
+        self.descriptors.iter()
+            .find(|descriptor| descriptor.descriptor_type == report_type::REPORT)
+            .map(|descriptor| descriptor.length)
+    }
+}
+
+impl ReportDescriptor {
+    pub fn parse(bytes: &[u8]) -> Self {
+        let mut descriptor = ReportDescriptor { items: Vec::new(), problems: Vec::new() };
+        let mut offset = 0;
+        let mut depth = 0usize;
+        let mut pushed = 0usize;
+        while offset < bytes.len() {
+            let prefix = bytes[offset];
+            // long items: 0xfe, bDataSize, bLongItemTag, data
+            let length = if prefix == 0xFE {
+                bytes.get(offset + 1).map_or(2, |&size| 3 + usize::from(size))
+            } else {
+                1 + [0, 1, 2, 4][usize::from(prefix & 0x03)]
+            };
+            if bytes.len() < offset + length {
+                descriptor.problems.push(Error::new(offset, ErrorKind::Truncated {
+                    needed: length,
+                    available: bytes.len() - offset,
+                }));
+                break;
+            }
+            let item = &bytes[offset..offset + length];
+            let kind = if prefix == 0xFE { ItemKind::Long { tag: item[2] } } else { short_item(item) };
+            match kind {
+                ItemKind::Collection(_) => depth += 1,
+                ItemKind::EndCollection if depth == 0 => {
+                    descriptor.problems.push(Error::new(offset, ErrorKind::Unbalanced { item: "End Collection" }));
+                },
+                ItemKind::EndCollection => depth -= 1,
+                ItemKind::Push => pushed += 1,
+                ItemKind::Pop if pushed == 0 => {
+                    descriptor.problems.push(Error::new(offset, ErrorKind::Unbalanced { item: "Pop" }));
+                },
+                ItemKind::Pop => pushed -= 1,
+                ItemKind::ReportId(id) if id == 0 || id > 0xFF => {
+                    descriptor.problems.push(Error::new(offset, ErrorKind::InvalidValue { field: "Report ID", value: id }));
+                },
+                _ => {},
+            }
+            descriptor.items.push(Item { offset, bytes: item.to_vec(), kind });
+            offset += length;
+        }
+        if depth > 0 {
+            descriptor.problems.push(Error::new(bytes.len(), ErrorKind::Unbalanced { item: "Collection" }));
+        }
+        descriptor
+    }
+
+    // report sizes per report ID, in order of first appearance
+    pub fn reports(&self) -> Vec<Report> {
+        #[derive(Clone, Copy, Default)]
+        struct Globals { report_size: u32, report_count: u32, report_id: Option<u8> }
+        let mut globals = Globals::default();
+        let mut stack = Vec::new();
+        let mut reports: Vec<Report> = Vec::new();
+        for item in &self.items {
+            let bits = globals.report_size.saturating_mul(globals.report_count);
+            match item.kind {
+                ItemKind::Input(_) => {
+                    let report = report_mut(&mut reports, globals.report_id);
+                    report.input_bits = report.input_bits.saturating_add(bits);
+                },
+                ItemKind::Output(_) => {
+                    let report = report_mut(&mut reports, globals.report_id);
+                    report.output_bits = report.output_bits.saturating_add(bits);
+                },
+                ItemKind::Feature(_) => {
+                    let report = report_mut(&mut reports, globals.report_id);
+                    report.feature_bits = report.feature_bits.saturating_add(bits);
+                },
+                ItemKind::ReportSize(size) => globals.report_size = size,
+                ItemKind::ReportCount(count) => globals.report_count = count,
+                ItemKind::ReportId(id) => globals.report_id = u8::try_from(id).ok(),
+                ItemKind::Push => stack.push(globals),
+                ItemKind::Pop => globals = stack.pop().unwrap_or(globals),
+                _ => {},
+            }
+        }
+        reports
+    }
+
+    // usages of the top level application collections, which is what hosts
+    // bind drivers to
+    pub fn applications(&self) -> Vec<Usage> {
+        let mut usage_page = 0;
+        let mut stack = Vec::new();
+        let mut usage = None;
+        let mut depth = 0usize;
+        let mut applications = Vec::new();
+        for item in &self.items {
+            match item.kind {
+                ItemKind::UsagePage(page) => usage_page = page,
+                ItemKind::Push => stack.push(usage_page),
+                ItemKind::Pop => usage_page = stack.pop().unwrap_or(usage_page),
+                ItemKind::Usage { page, id } => {
+                    // only the first usage before a main item names a collection
+                    usage.get_or_insert(Usage { page: page.unwrap_or(usage_page), id });
+                },
+                ItemKind::Collection(collection_type) => {
+                    if depth == 0 && collection_type == 0x01 {
+                        if let Some(usage) = usage {
+                            applications.push(usage);
+                        }
+                    }
+                    depth += 1;
+                    usage = None;
+                },
+                ItemKind::EndCollection => {
+                    depth = depth.saturating_sub(1);
+                    usage = None;
+                },
+                ItemKind::Input(_) | ItemKind::Output(_) | ItemKind::Feature(_) => usage = None,
+                _ => {},
+            }
+        }
+        applications
+    }
+}
+
+impl Item {
+    // data bytes following the prefix
+    pub fn data(&self) -> &[u8] {
+        match self.kind {
+            ItemKind::Long { .. } => &self.bytes[3..],
+            _ => &self.bytes[1..],
+        }
+    }
+}
+
+impl Report {
+    // size in bytes on the wire, the report ID byte included
+    pub fn bytes(&self, bits: u32) -> u32 {
+        bits.div_ceil(8) + u32::from(self.id.is_some() && bits > 0)
+    }
+}
+
+fn report_mut(reports: &mut Vec<Report>, id: Option<u8>) -> &mut Report {
+    match reports.iter().position(|report| report.id == id) {
+        Some(index) => &mut reports[index],
+        None => {
+            reports.push(Report { id, ..Report::default() });
+            reports.last_mut().unwrap()
+        },
+    }
+}
+
+fn short_item(item: &[u8]) -> ItemKind {
+    let data = &item[1..];
+    let unsigned = data.iter().rev().fold(0u32, |value, &byte| value << 8 | u32::from(byte));
+    let signed = match data.len() {
+        1 => i32::from(data[0] as i8),
+        2 => i32::from(unsigned as u16 as i16),
+        _ => unsigned as i32,
+    };
+    let usage = |data: &[u8]| match data.len() {
+        4 => (Some((unsigned >> 16) as u16), unsigned as u16),
+        _ => (None, unsigned as u16),
+    };
+    let tag = item[0] >> 4;
+    match (item[0] >> 2) & 0x03 {
+        0 => match tag {
+            0x8 => ItemKind::Input(unsigned),
+            0x9 => ItemKind::Output(unsigned),
+            0xB => ItemKind::Feature(unsigned),
+            0xA => ItemKind::Collection(unsigned as u8),
+            0xC => ItemKind::EndCollection,
+            _ => ItemKind::Reserved { item_type: 0, tag, data: unsigned },
+        },
+        1 => match tag {
+            0x0 => ItemKind::UsagePage(unsigned as u16),
+            0x1 => ItemKind::LogicalMinimum(signed),
+            0x2 => ItemKind::LogicalMaximum(signed),
+            0x3 => ItemKind::PhysicalMinimum(signed),
+            0x4 => ItemKind::PhysicalMaximum(signed),
+            // commonly a signed nibble even when sent in a whole byte
+            0x5 if unsigned < 0x10 => ItemKind::UnitExponent(((unsigned as i32) << 28) >> 28),
+            0x5 => ItemKind::UnitExponent(signed),
+            0x6 => ItemKind::Unit(unsigned),
+            0x7 => ItemKind::ReportSize(unsigned),
+            0x8 => ItemKind::ReportId(unsigned),
+            0x9 => ItemKind::ReportCount(unsigned),
+            0xA => ItemKind::Push,
+            0xB => ItemKind::Pop,
+            _ => ItemKind::Reserved { item_type: 1, tag, data: unsigned },
+        },
+        2 => match tag {
+            0x0 => { let (page, id) = usage(data); ItemKind::Usage { page, id } },
+            0x1 => { let (page, id) = usage(data); ItemKind::UsageMinimum { page, id } },
+            0x2 => { let (page, id) = usage(data); ItemKind::UsageMaximum { page, id } },
+            0x3 => ItemKind::DesignatorIndex(unsigned),
+            0x4 => ItemKind::DesignatorMinimum(unsigned),
+            0x5 => ItemKind::DesignatorMaximum(unsigned),
+            0x7 => ItemKind::StringIndex(unsigned),
+            0x8 => ItemKind::StringMinimum(unsigned),
+            0x9 => ItemKind::StringMaximum(unsigned),
+            0xA => ItemKind::Delimiter(unsigned),
+            _ => ItemKind::Reserved { item_type: 2, tag, data: unsigned },
+        },
+        item_type => ItemKind::Reserved { item_type, tag, data: unsigned },
+    }
+}
+
+pub fn collection_type_name(collection_type: u8) -> Option<&'static str> {
+    Some(match collection_type {
+        0x00 => "Physical",
+        0x01 => "Application",
+        0x02 => "Logical",
+        0x03 => "Report",
+        0x04 => "Named Array",
+        0x05 => "Usage Switch",
+        0x06 => "Usage Modifier",
+        0x80..=0xFF => "Vendor Defined",
+        _ => return None,
+    })
+}
+
+// the HID descriptor among the class specific descriptors of an interface
+pub fn find_hid_descriptor(extra: &[RawDescriptor]) -> Option<Result<HidDescriptor, Error>> {
+    extra.iter()
+        .find(|raw| raw.descriptor_type() == report_type::HID)
+        .map(|raw| HidDescriptor::parse(&raw.bytes).map_err(|err| err.at(raw.offset)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::descriptor::configuration::Configuration;
+
+    const KEYBOARD: &[u8] = include_bytes!("../../fixtures/hid/keyboard.bin");
+    const MOUSE_CONSUMER: &[u8] = include_bytes!("../../fixtures/hid/mouse-consumer.bin");
+    // the sysfs keyboard whose interface 0 carries KEYBOARD
+    const KEYBOARD_DESCRIPTORS: &[u8] =
+        include_bytes!("../../fixtures/sysfs/devices/platform/xhci-hcd.0.auto/usb1/1-1/1-1.1/descriptors");
+
+    #[test]
+    fn keyboard_items() {
+        let descriptor = ReportDescriptor::parse(KEYBOARD);
+        assert!(descriptor.problems.is_empty(), "{:?}", descriptor.problems);
+        let kinds: Vec<_> = descriptor.items.iter().map(|item| item.kind.clone()).collect();
+        assert_eq!(&kinds[..4], &[
+            ItemKind::UsagePage(0x01),
+            ItemKind::Usage { page: None, id: 0x06 },
+            ItemKind::Collection(0x01),
+            ItemKind::UsagePage(0x07),
+        ]);
+        assert_eq!(kinds[4], ItemKind::UsageMinimum { page: None, id: 0xE0 });
+        assert_eq!(kinds[10], ItemKind::Input(0x02));
+        let last = descriptor.items.last().unwrap();
+        assert_eq!((last.offset, &last.kind), (KEYBOARD.len() - 1, &ItemKind::EndCollection));
+        // every byte belongs to exactly one item
+        let covered: usize = descriptor.items.iter().map(|item| item.bytes.len()).sum();
+        assert_eq!(covered, KEYBOARD.len());
+        assert_eq!(descriptor.applications(), [Usage { page: 0x01, id: 0x06 }]);
+    }
+
+    #[test]
+    fn keyboard_report_sizes() {
+        let reports = ReportDescriptor::parse(KEYBOARD).reports();
+        assert_eq!(reports, [Report { id: None, input_bits: 64, output_bits: 8, feature_bits: 0 }]);
+        assert_eq!(reports[0].bytes(reports[0].input_bits), 8);
+        assert_eq!(reports[0].bytes(reports[0].output_bits), 1);
+    }
+
+    #[test]
+    fn keyboard_fixture_matches_sysfs_length() {
+        let configuration = Configuration::parse(&KEYBOARD_DESCRIPTORS[18..]).unwrap();
+        let setting = configuration.alternate_settings().next().unwrap();
+        let hid = find_hid_descriptor(&setting.extra).unwrap().unwrap();
+        assert_eq!(hid.report_descriptor_length(), Some(KEYBOARD.len() as u16));
+    }
+
+    #[test]
+    fn mouse_consumer_reports_per_id() {
+        let descriptor = ReportDescriptor::parse(MOUSE_CONSUMER);
+        assert!(descriptor.problems.is_empty(), "{:?}", descriptor.problems);
+        let reports = descriptor.reports();
+        assert_eq!(reports, [
+            Report { id: Some(1), input_bits: 56, output_bits: 0, feature_bits: 0 },
+            Report { id: Some(2), input_bits: 16, output_bits: 0, feature_bits: 0 },
+            Report { id: Some(3), input_bits: 0, output_bits: 504, feature_bits: 504 },
+        ]);
+        // the report ID byte is counted on the wire
+        assert_eq!(reports[0].bytes(reports[0].input_bits), 8);
+        assert_eq!(reports[1].bytes(reports[1].input_bits), 3);
+        assert_eq!(reports[2].bytes(reports[2].feature_bits), 64);
+        assert_eq!(descriptor.applications(), [
+            Usage { page: 0x01, id: 0x02 },
+            Usage { page: 0x0C, id: 0x01 },
+            Usage { page: 0xFF00, id: 0x01 },
+        ]);
+    }
+
+    #[test]
+    fn unbalanced_collections() {
+        // a collection left open is reported at the end of the descriptor
+        let descriptor = ReportDescriptor::parse(&[0x05, 0x01, 0xA1, 0x01, 0xA1, 0x00, 0xC0]);
+        assert_eq!(descriptor.problems, [Error::new(7, ErrorKind::Unbalanced { item: "Collection" })]);
+        // a stray End Collection where it occurs, and decoding carries on
+        let descriptor = ReportDescriptor::parse(&[0xA1, 0x01, 0xC0, 0xC0, 0x05, 0x01]);
+        assert_eq!(descriptor.problems, [Error::new(3, ErrorKind::Unbalanced { item: "End Collection" })]);
+        assert_eq!(descriptor.items.last().unwrap().kind, ItemKind::UsagePage(0x01));
+        let descriptor = ReportDescriptor::parse(&[0xB4]);
+        assert_eq!(descriptor.problems, [Error::new(0, ErrorKind::Unbalanced { item: "Pop" })]);
+    }
+
+    #[test]
+    fn truncated_item() {
+        let descriptor = ReportDescriptor::parse(&[0x05, 0x01, 0x26, 0xFF]);
+        assert_eq!(descriptor.problems, [Error::new(2, ErrorKind::Truncated { needed: 3, available: 2 })]);
+        assert_eq!(descriptor.items.len(), 1);
+    }
+}
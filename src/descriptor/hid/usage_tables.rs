@@ -0,0 +1,1342 @@
+// Names from the HID Usage Tables (HUT 1.5). Pages whose usages are plain
+// numbers (Button, Ordinal, Unicode) are generated, the rest are looked up
+// in one table per page.
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageName {
+    Named(&'static str),
+    Button(u16), // 0 is "No Button Pressed"
+    Instance(u16), // Ordinal page
+    Unicode(u16),
+    VendorDefined(u16), // any usage on a vendor defined page
+}
+
+impl fmt::Display for UsageName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            UsageName::Named(name) => f.write_str(name),
+            UsageName::Button(0) => f.write_str("No Button Pressed"),
+            UsageName::Button(button) => write!(f, "Button {}", button),
+            UsageName::Instance(instance) => write!(f, "Instance {}", instance),
+            UsageName::Unicode(code_point) => write!(f, "U+{:04X}", code_point),
+            UsageName::VendorDefined(id) => write!(f, "Vendor Usage {:#06x}", id),
+        }
+    }
+}
+
+// usage pages 0xff00 to 0xffff are reserved for vendors
+pub fn is_vendor_defined(page: u16) -> bool {
+    page >= 0xFF00
+}
+
+// usage page names from the HID Usage Tables
+pub fn usage_page_name(page: u16) -> Option<&'static str> {
+    Some(match page {
+        0x01 => "Generic Desktop",
+        0x02 => "Simulation Controls",
+        0x03 => "VR Controls",
+        0x04 => "Sport Controls",
+        0x05 => "Game Controls",
+        0x06 => "Generic Device Controls",
+        0x07 => "Keyboard/Keypad",
+        0x08 => "LED",
+        0x09 => "Button",
+        0x0A => "Ordinal",
+        0x0B => "Telephony Device",
+        0x0C => "Consumer",
+        0x0D => "Digitizers",
+        0x0E => "Haptics",
+        0x0F => "Physical Input Device",
+        0x10 => "Unicode",
+        0x11 => "SoC",
+        0x12 => "Eye and Head Trackers",
+        0x14 => "Auxiliary Display",
+        0x20 => "Sensors",
+        0x40 => "Medical Instrument",
+        0x41 => "Braille Display",
+        0x59 => "Lighting And Illumination",
+        0x80 => "Monitor",
+        0x81 => "Monitor Enumerated",
+        0x82 => "VESA Virtual Controls",
+        0x84 => "Power",
+        0x85 => "Battery System",
+        0x8C => "Barcode Scanner",
+        0x8D => "Scales",
+        0x8E => "Magnetic Stripe Reader",
+        0x90 => "Camera Control",
+        0x91 => "Arcade",
+        0x92 => "Gaming Device",
+        0xF1D0 => "FIDO Alliance",
+        _ => return None,
+    })
+}
+
+pub fn usage_name(page: u16, id: u16) -> Option<UsageName> {
+    let table = match page {
+        0x09 => return Some(UsageName::Button(id)),
+        0x0A if id > 0 => return Some(UsageName::Instance(id)),
+        0x10 => return Some(UsageName::Unicode(id)),
+        page if is_vendor_defined(page) => return Some(UsageName::VendorDefined(id)),
+        0x01 => generic_desktop,
+        0x02 => simulation_controls,
+        0x05 => game_controls,
+        0x06 => generic_device_controls,
+        0x07 => keyboard,
+        0x08 => led,
+        0x0B => telephony,
+        0x0C => consumer,
+        0x0D => digitizers,
+        0x20 => sensors,
+        0x84 => power,
+        0x85 => battery_system,
+        0xF1D0 => fido,
+        _ => return None,
+    };
+    table(id).map(UsageName::Named)
+}
+
+// Generic Desktop (0x01)
+fn generic_desktop(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "Pointer",
+        0x02 => "Mouse",
+        0x04 => "Joystick",
+        0x05 => "Gamepad",
+        0x06 => "Keyboard",
+        0x07 => "Keypad",
+        0x08 => "Multi-axis Controller",
+        0x09 => "Tablet PC System Controls",
+        0x0A => "Water Cooling Device",
+        0x0B => "Computer Chassis Device",
+        0x0C => "Wireless Radio Controls",
+        0x0D => "Portable Device Control",
+        0x0E => "System Multi-Axis Controller",
+        0x0F => "Spatial Controller",
+        0x10 => "Assistive Control",
+        0x11 => "Device Dock",
+        0x12 => "Dockable Device",
+        0x13 => "Call State Management Control",
+        0x30 => "X",
+        0x31 => "Y",
+        0x32 => "Z",
+        0x33 => "Rx",
+        0x34 => "Ry",
+        0x35 => "Rz",
+        0x36 => "Slider",
+        0x37 => "Dial",
+        0x38 => "Wheel",
+        0x39 => "Hat Switch",
+        0x3A => "Counted Buffer",
+        0x3B => "Byte Count",
+        0x3C => "Motion Wakeup",
+        0x3D => "Start",
+        0x3E => "Select",
+        0x40 => "Vx",
+        0x41 => "Vy",
+        0x42 => "Vz",
+        0x43 => "Vbrx",
+        0x44 => "Vbry",
+        0x45 => "Vbrz",
+        0x46 => "Vno",
+        0x47 => "Feature Notification",
+        0x48 => "Resolution Multiplier",
+        0x49 => "Qx",
+        0x4A => "Qy",
+        0x4B => "Qz",
+        0x4C => "Qw",
+        0x80 => "System Control",
+        0x81 => "System Power Down",
+        0x82 => "System Sleep",
+        0x83 => "System Wake Up",
+        0x84 => "System Context Menu",
+        0x85 => "System Main Menu",
+        0x86 => "System App Menu",
+        0x87 => "System Menu Help",
+        0x88 => "System Menu Exit",
+        0x89 => "System Menu Select",
+        0x8A => "System Menu Right",
+        0x8B => "System Menu Left",
+        0x8C => "System Menu Up",
+        0x8D => "System Menu Down",
+        0x8E => "System Cold Restart",
+        0x8F => "System Warm Restart",
+        0x90 => "D-pad Up",
+        0x91 => "D-pad Down",
+        0x92 => "D-pad Right",
+        0x93 => "D-pad Left",
+        0x94 => "Index Trigger",
+        0x95 => "Palm Trigger",
+        0x96 => "Thumbstick",
+        0x97 => "System Function Shift",
+        0x98 => "System Function Shift Lock",
+        0x99 => "System Function Shift Lock Indicator",
+        0x9A => "System Dismiss Notification",
+        0x9B => "System Do Not Disturb",
+        0xA0 => "System Dock",
+        0xA1 => "System Undock",
+        0xA2 => "System Setup",
+        0xA3 => "System Break",
+        0xA4 => "System Debugger Break",
+        0xA5 => "Application Break",
+        0xA6 => "Application Debugger Break",
+        0xA7 => "System Speaker Mute",
+        0xA8 => "System Hibernate",
+        0xA9 => "System Microphone Mute",
+        0xB0 => "System Display Invert",
+        0xB1 => "System Display Internal",
+        0xB2 => "System Display External",
+        0xB3 => "System Display Both",
+        0xB4 => "System Display Dual",
+        0xB5 => "System Display Toggle Int/Ext",
+        0xB6 => "System Display Swap Primary/Secondary",
+        0xB7 => "System Display Toggle LCD Autoscale",
+        0xC0 => "Sensor Zone",
+        0xC1 => "RPM",
+        0xC2 => "Coolant Level",
+        0xC3 => "Coolant Critical Level",
+        0xC4 => "Coolant Pump",
+        0xC5 => "Chassis Enclosure",
+        0xC6 => "Wireless Radio Button",
+        0xC7 => "Wireless Radio LED",
+        0xC8 => "Wireless Radio Slider Switch",
+        0xC9 => "System Display Rotation Lock Button",
+        0xCA => "System Display Rotation Lock Slider Switch",
+        0xCB => "Control Enable",
+        _ => return None,
+    })
+}
+
+// Simulation Controls (0x02)
+fn simulation_controls(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "Flight Simulation Device",
+        0x02 => "Automobile Simulation Device",
+        0x03 => "Tank Simulation Device",
+        0x04 => "Spaceship Simulation Device",
+        0x05 => "Submarine Simulation Device",
+        0x06 => "Sailing Simulation Device",
+        0x07 => "Motorcycle Simulation Device",
+        0x08 => "Sports Simulation Device",
+        0x09 => "Airplane Simulation Device",
+        0x0A => "Helicopter Simulation Device",
+        0x0B => "Magic Carpet Simulation Device",
+        0x0C => "Bicycle Simulation Device",
+        0x20 => "Flight Control Stick",
+        0x21 => "Flight Stick",
+        0x22 => "Cyclic Control",
+        0x23 => "Cyclic Trim",
+        0x24 => "Flight Yoke",
+        0x25 => "Track Control",
+        0xB0 => "Aileron",
+        0xB1 => "Aileron Trim",
+        0xB2 => "Anti-Torque Control",
+        0xB3 => "Autopilot Enable",
+        0xB4 => "Chaff Release",
+        0xB5 => "Collective Control",
+        0xB6 => "Dive Brake",
+        0xB7 => "Electronic Countermeasures",
+        0xB8 => "Elevator",
+        0xB9 => "Elevator Trim",
+        0xBA => "Rudder",
+        0xBB => "Throttle",
+        0xBC => "Flight Communications",
+        0xBD => "Flare Release",
+        0xBE => "Landing Gear",
+        0xBF => "Toe Brake",
+        0xC0 => "Trigger",
+        0xC1 => "Weapons Arm",
+        0xC2 => "Weapons Select",
+        0xC3 => "Wing Flaps",
+        0xC4 => "Accelerator",
+        0xC5 => "Brake",
+        0xC6 => "Clutch",
+        0xC7 => "Shifter",
+        0xC8 => "Steering",
+        0xC9 => "Turret Direction",
+        0xCA => "Barrel Elevation",
+        0xCB => "Dive Plane",
+        0xCC => "Ballast",
+        0xCD => "Bicycle Crank",
+        0xCE => "Handle Bars",
+        0xCF => "Front Brake",
+        0xD0 => "Rear Brake",
+        _ => return None,
+    })
+}
+
+// Game Controls (0x05)
+fn game_controls(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "3D Game Controller",
+        0x02 => "Pinball Device",
+        0x03 => "Gun Device",
+        0x20 => "Point of View",
+        0x21 => "Turn Right/Left",
+        0x22 => "Pitch Forward/Backward",
+        0x23 => "Roll Right/Left",
+        0x24 => "Move Right/Left",
+        0x25 => "Move Forward/Backward",
+        0x26 => "Move Up/Down",
+        0x27 => "Lean Right/Left",
+        0x28 => "Lean Forward/Backward",
+        0x29 => "Height of POV",
+        0x2A => "Flipper",
+        0x2B => "Secondary Flipper",
+        0x2C => "Bump",
+        0x2D => "New Game",
+        0x2E => "Shoot Ball",
+        0x2F => "Player",
+        0x30 => "Gun Bolt",
+        0x31 => "Gun Clip",
+        0x32 => "Gun Selector",
+        0x33 => "Gun Single Shot",
+        0x34 => "Gun Burst",
+        0x35 => "Gun Automatic",
+        0x36 => "Gun Safety",
+        0x37 => "Gamepad Fire/Jump",
+        0x39 => "Gamepad Trigger",
+        0x3A => "Form-fitting Gamepad",
+        _ => return None,
+    })
+}
+
+// Generic Device Controls (0x06)
+fn generic_device_controls(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "Background/Nonuser Controls",
+        0x20 => "Battery Strength",
+        0x21 => "Wireless Channel",
+        0x22 => "Wireless ID",
+        0x23 => "Discover Wireless Control",
+        0x24 => "Security Code Character Entered",
+        0x25 => "Security Code Character Erased",
+        0x26 => "Security Code Cleared",
+        0x27 => "Sequence ID",
+        0x28 => "Sequence ID Reset",
+        0x29 => "RF Signal Strength",
+        0x2A => "Software Version",
+        0x2B => "Protocol Version",
+        0x2C => "Hardware Version",
+        0x2D => "Major",
+        0x2E => "Minor",
+        0x2F => "Revision",
+        0x30 => "Handedness",
+        0x31 => "Either Hand",
+        0x32 => "Left Hand",
+        0x33 => "Right Hand",
+        0x34 => "Both Hands",
+        0x40 => "Grip Pose Offset",
+        0x41 => "Pointer Pose Offset",
+        _ => return None,
+    })
+}
+
+// Keyboard/Keypad (0x07)
+fn keyboard(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x00 => "Reserved",
+        0x01 => "ErrorRollOver",
+        0x02 => "POSTFail",
+        0x03 => "ErrorUndefined",
+        0x04 => "Keyboard a and A",
+        0x05 => "Keyboard b and B",
+        0x06 => "Keyboard c and C",
+        0x07 => "Keyboard d and D",
+        0x08 => "Keyboard e and E",
+        0x09 => "Keyboard f and F",
+        0x0A => "Keyboard g and G",
+        0x0B => "Keyboard h and H",
+        0x0C => "Keyboard i and I",
+        0x0D => "Keyboard j and J",
+        0x0E => "Keyboard k and K",
+        0x0F => "Keyboard l and L",
+        0x10 => "Keyboard m and M",
+        0x11 => "Keyboard n and N",
+        0x12 => "Keyboard o and O",
+        0x13 => "Keyboard p and P",
+        0x14 => "Keyboard q and Q",
+        0x15 => "Keyboard r and R",
+        0x16 => "Keyboard s and S",
+        0x17 => "Keyboard t and T",
+        0x18 => "Keyboard u and U",
+        0x19 => "Keyboard v and V",
+        0x1A => "Keyboard w and W",
+        0x1B => "Keyboard x and X",
+        0x1C => "Keyboard y and Y",
+        0x1D => "Keyboard z and Z",
+        0x1E => "Keyboard 1 and !",
+        0x1F => "Keyboard 2 and @",
+        0x20 => "Keyboard 3 and #",
+        0x21 => "Keyboard 4 and $",
+        0x22 => "Keyboard 5 and %",
+        0x23 => "Keyboard 6 and ^",
+        0x24 => "Keyboard 7 and &",
+        0x25 => "Keyboard 8 and *",
+        0x26 => "Keyboard 9 and (",
+        0x27 => "Keyboard 0 and )",
+        0x28 => "Keyboard Return (ENTER)",
+        0x29 => "Keyboard ESCAPE",
+        0x2A => "Keyboard DELETE (Backspace)",
+        0x2B => "Keyboard Tab",
+        0x2C => "Keyboard Spacebar",
+        0x2D => "Keyboard - and _",
+        0x2E => "Keyboard = and +",
+        0x2F => "Keyboard [ and {",
+        0x30 => "Keyboard ] and }",
+        0x31 => "Keyboard \\ and |",
+        0x32 => "Keyboard Non-US # and ~",
+        0x33 => "Keyboard ; and :",
+        0x34 => "Keyboard ' and \"",
+        0x35 => "Keyboard Grave Accent and Tilde",
+        0x36 => "Keyboard , and <",
+        0x37 => "Keyboard . and >",
+        0x38 => "Keyboard / and ?",
+        0x39 => "Keyboard Caps Lock",
+        0x3A => "Keyboard F1",
+        0x3B => "Keyboard F2",
+        0x3C => "Keyboard F3",
+        0x3D => "Keyboard F4",
+        0x3E => "Keyboard F5",
+        0x3F => "Keyboard F6",
+        0x40 => "Keyboard F7",
+        0x41 => "Keyboard F8",
+        0x42 => "Keyboard F9",
+        0x43 => "Keyboard F10",
+        0x44 => "Keyboard F11",
+        0x45 => "Keyboard F12",
+        0x46 => "Keyboard PrintScreen",
+        0x47 => "Keyboard Scroll Lock",
+        0x48 => "Keyboard Pause",
+        0x49 => "Keyboard Insert",
+        0x4A => "Keyboard Home",
+        0x4B => "Keyboard PageUp",
+        0x4C => "Keyboard Delete Forward",
+        0x4D => "Keyboard End",
+        0x4E => "Keyboard PageDown",
+        0x4F => "Keyboard RightArrow",
+        0x50 => "Keyboard LeftArrow",
+        0x51 => "Keyboard DownArrow",
+        0x52 => "Keyboard UpArrow",
+        0x53 => "Keypad Num Lock and Clear",
+        0x54 => "Keypad /",
+        0x55 => "Keypad *",
+        0x56 => "Keypad -",
+        0x57 => "Keypad +",
+        0x58 => "Keypad ENTER",
+        0x59 => "Keypad 1 and End",
+        0x5A => "Keypad 2 and Down Arrow",
+        0x5B => "Keypad 3 and PageDn",
+        0x5C => "Keypad 4 and Left Arrow",
+        0x5D => "Keypad 5",
+        0x5E => "Keypad 6 and Right Arrow",
+        0x5F => "Keypad 7 and Home",
+        0x60 => "Keypad 8 and Up Arrow",
+        0x61 => "Keypad 9 and PageUp",
+        0x62 => "Keypad 0 and Insert",
+        0x63 => "Keypad . and Delete",
+        0x64 => "Keyboard Non-US \\ and |",
+        0x65 => "Keyboard Application",
+        0x66 => "Keyboard Power",
+        0x67 => "Keypad =",
+        0x68 => "Keyboard F13",
+        0x69 => "Keyboard F14",
+        0x6A => "Keyboard F15",
+        0x6B => "Keyboard F16",
+        0x6C => "Keyboard F17",
+        0x6D => "Keyboard F18",
+        0x6E => "Keyboard F19",
+        0x6F => "Keyboard F20",
+        0x70 => "Keyboard F21",
+        0x71 => "Keyboard F22",
+        0x72 => "Keyboard F23",
+        0x73 => "Keyboard F24",
+        0x74 => "Keyboard Execute",
+        0x75 => "Keyboard Help",
+        0x76 => "Keyboard Menu",
+        0x77 => "Keyboard Select",
+        0x78 => "Keyboard Stop",
+        0x79 => "Keyboard Again",
+        0x7A => "Keyboard Undo",
+        0x7B => "Keyboard Cut",
+        0x7C => "Keyboard Copy",
+        0x7D => "Keyboard Paste",
+        0x7E => "Keyboard Find",
+        0x7F => "Keyboard Mute",
+        0x80 => "Keyboard Volume Up",
+        0x81 => "Keyboard Volume Down",
+        0x82 => "Keyboard Locking Caps Lock",
+        0x83 => "Keyboard Locking Num Lock",
+        0x84 => "Keyboard Locking Scroll Lock",
+        0x85 => "Keypad Comma",
+        0x86 => "Keypad Equal Sign",
+        0x87 => "Keyboard International1",
+        0x88 => "Keyboard International2",
+        0x89 => "Keyboard International3",
+        0x8A => "Keyboard International4",
+        0x8B => "Keyboard International5",
+        0x8C => "Keyboard International6",
+        0x8D => "Keyboard International7",
+        0x8E => "Keyboard International8",
+        0x8F => "Keyboard International9",
+        0x90 => "Keyboard LANG1",
+        0x91 => "Keyboard LANG2",
+        0x92 => "Keyboard LANG3",
+        0x93 => "Keyboard LANG4",
+        0x94 => "Keyboard LANG5",
+        0x95 => "Keyboard LANG6",
+        0x96 => "Keyboard LANG7",
+        0x97 => "Keyboard LANG8",
+        0x98 => "Keyboard LANG9",
+        0x99 => "Keyboard Alternate Erase",
+        0x9A => "Keyboard SysReq/Attention",
+        0x9B => "Keyboard Cancel",
+        0x9C => "Keyboard Clear",
+        0x9D => "Keyboard Prior",
+        0x9E => "Keyboard Return",
+        0x9F => "Keyboard Separator",
+        0xA0 => "Keyboard Out",
+        0xA1 => "Keyboard Oper",
+        0xA2 => "Keyboard Clear/Again",
+        0xA3 => "Keyboard CrSel/Props",
+        0xA4 => "Keyboard ExSel",
+        0xB0 => "Keypad 00",
+        0xB1 => "Keypad 000",
+        0xB2 => "Thousands Separator",
+        0xB3 => "Decimal Separator",
+        0xB4 => "Currency Unit",
+        0xB5 => "Currency Sub-unit",
+        0xB6 => "Keypad (",
+        0xB7 => "Keypad )",
+        0xB8 => "Keypad {",
+        0xB9 => "Keypad }",
+        0xBA => "Keypad Tab",
+        0xBB => "Keypad Backspace",
+        0xBC => "Keypad A",
+        0xBD => "Keypad B",
+        0xBE => "Keypad C",
+        0xBF => "Keypad D",
+        0xC0 => "Keypad E",
+        0xC1 => "Keypad F",
+        0xC2 => "Keypad XOR",
+        0xC3 => "Keypad ^",
+        0xC4 => "Keypad %",
+        0xC5 => "Keypad <",
+        0xC6 => "Keypad >",
+        0xC7 => "Keypad &",
+        0xC8 => "Keypad &&",
+        0xC9 => "Keypad |",
+        0xCA => "Keypad ||",
+        0xCB => "Keypad :",
+        0xCC => "Keypad #",
+        0xCD => "Keypad Space",
+        0xCE => "Keypad @",
+        0xCF => "Keypad !",
+        0xD0 => "Keypad Memory Store",
+        0xD1 => "Keypad Memory Recall",
+        0xD2 => "Keypad Memory Clear",
+        0xD3 => "Keypad Memory Add",
+        0xD4 => "Keypad Memory Subtract",
+        0xD5 => "Keypad Memory Multiply",
+        0xD6 => "Keypad Memory Divide",
+        0xD7 => "Keypad +/-",
+        0xD8 => "Keypad Clear",
+        0xD9 => "Keypad Clear Entry",
+        0xDA => "Keypad Binary",
+        0xDB => "Keypad Octal",
+        0xDC => "Keypad Decimal",
+        0xDD => "Keypad Hexadecimal",
+        0xE0 => "Keyboard LeftControl",
+        0xE1 => "Keyboard LeftShift",
+        0xE2 => "Keyboard LeftAlt",
+        0xE3 => "Keyboard Left GUI",
+        0xE4 => "Keyboard RightControl",
+        0xE5 => "Keyboard RightShift",
+        0xE6 => "Keyboard RightAlt",
+        0xE7 => "Keyboard Right GUI",
+        _ => return None,
+    })
+}
+
+// LED (0x08)
+fn led(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "Num Lock",
+        0x02 => "Caps Lock",
+        0x03 => "Scroll Lock",
+        0x04 => "Compose",
+        0x05 => "Kana",
+        0x06 => "Power",
+        0x07 => "Shift",
+        0x08 => "Do Not Disturb",
+        0x09 => "Mute",
+        0x0A => "Tone Enable",
+        0x0B => "High Cut Filter",
+        0x0C => "Low Cut Filter",
+        0x0D => "Equalizer Enable",
+        0x0E => "Sound Field On",
+        0x0F => "Surround On",
+        0x10 => "Repeat",
+        0x11 => "Stereo",
+        0x12 => "Sampling Rate Detect",
+        0x13 => "Spinning",
+        0x14 => "CAV",
+        0x15 => "CLV",
+        0x16 => "Recording Format Detect",
+        0x17 => "Off-Hook",
+        0x18 => "Ring",
+        0x19 => "Message Waiting",
+        0x1A => "Data Mode",
+        0x1B => "Battery Operation",
+        0x1C => "Battery OK",
+        0x1D => "Battery Low",
+        0x1E => "Speaker",
+        0x1F => "Headset",
+        0x20 => "Hold",
+        0x21 => "Microphone",
+        0x22 => "Coverage",
+        0x23 => "Night Mode",
+        0x24 => "Send Calls",
+        0x25 => "Call Pickup",
+        0x26 => "Conference",
+        0x27 => "Stand-by",
+        0x28 => "Camera On",
+        0x29 => "Camera Off",
+        0x2A => "On-Line",
+        0x2B => "Off-Line",
+        0x2C => "Busy",
+        0x2D => "Ready",
+        0x2E => "Paper-Out",
+        0x2F => "Paper-Jam",
+        0x30 => "Remote",
+        0x31 => "Forward",
+        0x32 => "Reverse",
+        0x33 => "Stop",
+        0x34 => "Rewind",
+        0x35 => "Fast Forward",
+        0x36 => "Play",
+        0x37 => "Pause",
+        0x38 => "Record",
+        0x39 => "Error",
+        0x3A => "Usage Selected Indicator",
+        0x3B => "Usage In Use Indicator",
+        0x3C => "Usage Multi Mode Indicator",
+        0x3D => "Indicator On",
+        0x3E => "Indicator Flash",
+        0x3F => "Indicator Slow Blink",
+        0x40 => "Indicator Fast Blink",
+        0x41 => "Indicator Off",
+        0x42 => "Flash On Time",
+        0x43 => "Slow Blink On Time",
+        0x44 => "Slow Blink Off Time",
+        0x45 => "Fast Blink On Time",
+        0x46 => "Fast Blink Off Time",
+        0x47 => "Usage Indicator Color",
+        0x48 => "Indicator Red",
+        0x49 => "Indicator Green",
+        0x4A => "Indicator Amber",
+        0x4B => "Generic Indicator",
+        0x4C => "System Suspend",
+        0x4D => "External Power Connected",
+        0x4E => "Indicator Blue",
+        0x4F => "Indicator Orange",
+        0x50 => "Good Status",
+        0x51 => "Warning Status",
+        0x52 => "RGB LED",
+        0x53 => "Red LED Channel",
+        0x54 => "Blue LED Channel",
+        0x55 => "Green LED Channel",
+        0x56 => "LED Intensity",
+        0x60 => "Player Indicator",
+        0x61 => "Player 1",
+        0x62 => "Player 2",
+        0x63 => "Player 3",
+        0x64 => "Player 4",
+        0x65 => "Player 5",
+        0x66 => "Player 6",
+        0x67 => "Player 7",
+        0x68 => "Player 8",
+        _ => return None,
+    })
+}
+
+// Telephony Device (0x0B)
+fn telephony(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "Phone",
+        0x02 => "Answering Machine",
+        0x03 => "Message Controls",
+        0x04 => "Handset",
+        0x05 => "Headset",
+        0x06 => "Telephony Key Pad",
+        0x07 => "Programmable Button",
+        0x20 => "Hook Switch",
+        0x21 => "Flash",
+        0x22 => "Feature",
+        0x23 => "Hold",
+        0x24 => "Redial",
+        0x25 => "Transfer",
+        0x26 => "Drop",
+        0x27 => "Park",
+        0x28 => "Forward Calls",
+        0x29 => "Alternate Function",
+        0x2A => "Line",
+        0x2B => "Speaker Phone",
+        0x2C => "Conference",
+        0x2D => "Ring Enable",
+        0x2E => "Ring Select",
+        0x2F => "Phone Mute",
+        0x30 => "Caller ID",
+        0x31 => "Send",
+        0x50 => "Speed Dial",
+        0x51 => "Store Number",
+        0x52 => "Recall Number",
+        0x53 => "Phone Directory",
+        0x70 => "Voice Mail",
+        0x71 => "Screen Calls",
+        0x72 => "Do Not Disturb",
+        0x73 => "Message",
+        0x74 => "Answer On/Off",
+        0x90 => "Inside Dial Tone",
+        0x91 => "Outside Dial Tone",
+        0x92 => "Inside Ring Tone",
+        0x93 => "Outside Ring Tone",
+        0x94 => "Priority Ring Tone",
+        0x95 => "Inside Ringback",
+        0x96 => "Priority Ringback",
+        0x97 => "Line Busy Tone",
+        0x98 => "Reorder Tone",
+        0x99 => "Call Waiting Tone",
+        0x9A => "Confirmation Tone 1",
+        0x9B => "Confirmation Tone 2",
+        0x9C => "Tones Off",
+        0x9D => "Outside Ringback",
+        0x9E => "Ringer",
+        _ => return None,
+    })
+}
+
+// Consumer (0x0C)
+fn consumer(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x0001 => "Consumer Control",
+        0x0002 => "Numeric Key Pad",
+        0x0003 => "Programmable Buttons",
+        0x0004 => "Microphone",
+        0x0005 => "Headphone",
+        0x0006 => "Graphic Equalizer",
+        0x0020 => "+10",
+        0x0021 => "+100",
+        0x0022 => "AM/PM",
+        0x0030 => "Power",
+        0x0031 => "Reset",
+        0x0032 => "Sleep",
+        0x0033 => "Sleep After",
+        0x0034 => "Sleep Mode",
+        0x0035 => "Illumination",
+        0x0036 => "Function Buttons",
+        0x0040 => "Menu",
+        0x0041 => "Menu Pick",
+        0x0042 => "Menu Up",
+        0x0043 => "Menu Down",
+        0x0044 => "Menu Left",
+        0x0045 => "Menu Right",
+        0x0046 => "Menu Escape",
+        0x0047 => "Menu Value Increase",
+        0x0048 => "Menu Value Decrease",
+        0x0060 => "Data On Screen",
+        0x0061 => "Closed Caption",
+        0x0062 => "Closed Caption Select",
+        0x0063 => "VCR/TV",
+        0x0064 => "Broadcast Mode",
+        0x0065 => "Snapshot",
+        0x0066 => "Still",
+        0x0067 => "Picture-in-Picture Toggle",
+        0x0068 => "Picture-in-Picture Swap",
+        0x0069 => "Red Menu Button",
+        0x006A => "Green Menu Button",
+        0x006B => "Blue Menu Button",
+        0x006C => "Yellow Menu Button",
+        0x006D => "Aspect",
+        0x006E => "3D Mode Select",
+        0x006F => "Display Brightness Increment",
+        0x0070 => "Display Brightness Decrement",
+        0x0071 => "Display Brightness",
+        0x0072 => "Display Backlight Toggle",
+        0x0073 => "Display Set Brightness to Minimum",
+        0x0074 => "Display Set Brightness to Maximum",
+        0x0075 => "Display Set Auto Brightness",
+        0x0076 => "Camera Access Enabled",
+        0x0077 => "Camera Access Disabled",
+        0x0078 => "Camera Access Toggle",
+        0x0079 => "Keyboard Brightness Increment",
+        0x007A => "Keyboard Brightness Decrement",
+        0x007B => "Keyboard Backlight Set Level",
+        0x007C => "Keyboard Backlight OOC",
+        0x007D => "Keyboard Backlight Set Minimum",
+        0x007E => "Keyboard Backlight Set Maximum",
+        0x007F => "Keyboard Backlight Auto",
+        0x0080 => "Selection",
+        0x0081 => "Assign Selection",
+        0x0082 => "Mode Step",
+        0x0083 => "Recall Last",
+        0x0084 => "Enter Channel",
+        0x0085 => "Order Movie",
+        0x0086 => "Channel",
+        0x0087 => "Media Selection",
+        0x0088 => "Media Select Computer",
+        0x0089 => "Media Select TV",
+        0x008A => "Media Select WWW",
+        0x008B => "Media Select DVD",
+        0x008C => "Media Select Telephone",
+        0x008D => "Media Select Program Guide",
+        0x008E => "Media Select Video Phone",
+        0x008F => "Media Select Games",
+        0x0090 => "Media Select Messages",
+        0x0091 => "Media Select CD",
+        0x0092 => "Media Select VCR",
+        0x0093 => "Media Select Tuner",
+        0x0094 => "Quit",
+        0x0095 => "Help",
+        0x0096 => "Media Select Tape",
+        0x0097 => "Media Select Cable",
+        0x0098 => "Media Select Satellite",
+        0x0099 => "Media Select Security",
+        0x009A => "Media Select Home",
+        0x009B => "Media Select Call",
+        0x009C => "Channel Increment",
+        0x009D => "Channel Decrement",
+        0x009E => "Media Select SAP",
+        0x00A0 => "VCR Plus",
+        0x00A1 => "Once",
+        0x00A2 => "Daily",
+        0x00A3 => "Weekly",
+        0x00A4 => "Monthly",
+        0x00B0 => "Play",
+        0x00B1 => "Pause",
+        0x00B2 => "Record",
+        0x00B3 => "Fast Forward",
+        0x00B4 => "Rewind",
+        0x00B5 => "Scan Next Track",
+        0x00B6 => "Scan Previous Track",
+        0x00B7 => "Stop",
+        0x00B8 => "Eject",
+        0x00B9 => "Random Play",
+        0x00BA => "Select Disc",
+        0x00BB => "Enter Disc",
+        0x00BC => "Repeat",
+        0x00BD => "Tracking",
+        0x00BE => "Track Normal",
+        0x00BF => "Slow Tracking",
+        0x00C0 => "Frame Forward",
+        0x00C1 => "Frame Back",
+        0x00C2 => "Mark",
+        0x00C3 => "Clear Mark",
+        0x00C4 => "Repeat From Mark",
+        0x00C5 => "Return To Mark",
+        0x00C6 => "Search Mark Forward",
+        0x00C7 => "Search Mark Backwards",
+        0x00C8 => "Counter Reset",
+        0x00C9 => "Show Counter",
+        0x00CA => "Tracking Increment",
+        0x00CB => "Tracking Decrement",
+        0x00CC => "Stop/Eject",
+        0x00CD => "Play/Pause",
+        0x00CE => "Play/Skip",
+        0x00CF => "Voice Command",
+        0x00D0 => "Invoke Capture Interface",
+        0x00D1 => "Start or Stop Game Recording",
+        0x00D2 => "Historical Game Capture",
+        0x00D3 => "Capture Game Screenshot",
+        0x00D4 => "Show or Hide Recording Indicator",
+        0x00D5 => "Start or Stop Microphone Capture",
+        0x00D6 => "Start or Stop Camera Capture",
+        0x00D7 => "Start or Stop Game Broadcast",
+        0x00D8 => "Start or Stop Voice Dictation Session",
+        0x00D9 => "Invoke/Dismiss Emoji Picker",
+        0x00E0 => "Volume",
+        0x00E1 => "Balance",
+        0x00E2 => "Mute",
+        0x00E3 => "Bass",
+        0x00E4 => "Treble",
+        0x00E5 => "Bass Boost",
+        0x00E6 => "Surround Mode",
+        0x00E7 => "Loudness",
+        0x00E8 => "MPX",
+        0x00E9 => "Volume Increment",
+        0x00EA => "Volume Decrement",
+        0x00F0 => "Speed Select",
+        0x00F1 => "Playback Speed",
+        0x00F2 => "Standard Play",
+        0x00F3 => "Long Play",
+        0x00F4 => "Extended Play",
+        0x00F5 => "Slow",
+        0x0100 => "Fan Enable",
+        0x0101 => "Fan Speed",
+        0x0102 => "Light Enable",
+        0x0103 => "Light Illumination Level",
+        0x0104 => "Climate Control Enable",
+        0x0105 => "Room Temperature",
+        0x0106 => "Security Enable",
+        0x0107 => "Fire Alarm",
+        0x0108 => "Police Alarm",
+        0x0109 => "Proximity",
+        0x010A => "Motion",
+        0x010B => "Duress Alarm",
+        0x010C => "Holdup Alarm",
+        0x010D => "Medical Alarm",
+        0x0150 => "Balance Right",
+        0x0151 => "Balance Left",
+        0x0152 => "Bass Increment",
+        0x0153 => "Bass Decrement",
+        0x0154 => "Treble Increment",
+        0x0155 => "Treble Decrement",
+        0x0160 => "Speaker System",
+        0x0161 => "Channel Left",
+        0x0162 => "Channel Right",
+        0x0163 => "Channel Center",
+        0x0164 => "Channel Front",
+        0x0165 => "Channel Center Front",
+        0x0166 => "Channel Side",
+        0x0167 => "Channel Surround",
+        0x0168 => "Channel Low Frequency Enhancement",
+        0x0169 => "Channel Top",
+        0x016A => "Channel Unknown",
+        0x0170 => "Sub-channel",
+        0x0171 => "Sub-channel Increment",
+        0x0172 => "Sub-channel Decrement",
+        0x0173 => "Alternate Audio Increment",
+        0x0174 => "Alternate Audio Decrement",
+        0x0180 => "Application Launch Buttons",
+        0x0181 => "AL Launch Button Configuration Tool",
+        0x0182 => "AL Programmable Button Configuration",
+        0x0183 => "AL Consumer Control Configuration",
+        0x0184 => "AL Word Processor",
+        0x0185 => "AL Text Editor",
+        0x0186 => "AL Spreadsheet",
+        0x0187 => "AL Graphics Editor",
+        0x0188 => "AL Presentation App",
+        0x0189 => "AL Database App",
+        0x018A => "AL Email Reader",
+        0x018B => "AL Newsreader",
+        0x018C => "AL Voicemail",
+        0x018D => "AL Contacts/Address Book",
+        0x018E => "AL Calendar/Schedule",
+        0x018F => "AL Task/Project Manager",
+        0x0190 => "AL Log/Journal/Timecard",
+        0x0191 => "AL Checkbook/Finance",
+        0x0192 => "AL Calculator",
+        0x0193 => "AL A/V Capture/Playback",
+        0x0194 => "AL Local Machine Browser",
+        0x0195 => "AL LAN/WAN Browser",
+        0x0196 => "AL Internet Browser",
+        0x0197 => "AL Remote Networking/ISP Connect",
+        0x0198 => "AL Network Conference",
+        0x0199 => "AL Network Chat",
+        0x019A => "AL Telephony/Dialer",
+        0x019B => "AL Logon",
+        0x019C => "AL Logoff",
+        0x019D => "AL Logon/Logoff",
+        0x019E => "AL Terminal Lock/Screensaver",
+        0x019F => "AL Control Panel",
+        0x01A0 => "AL Command Line Processor/Run",
+        0x01A1 => "AL Process/Task Manager",
+        0x01A2 => "AL Select Task/Application",
+        0x01A3 => "AL Next Task/Application",
+        0x01A4 => "AL Previous Task/Application",
+        0x01A5 => "AL Preemptive Halt Task/Application",
+        0x01A6 => "AL Integrated Help Center",
+        0x01A7 => "AL Documents",
+        0x01A8 => "AL Thesaurus",
+        0x01A9 => "AL Dictionary",
+        0x01AA => "AL Desktop",
+        0x01AB => "AL Spell Check",
+        0x01AC => "AL Grammar Check",
+        0x01AD => "AL Wireless Status",
+        0x01AE => "AL Keyboard Layout",
+        0x01AF => "AL Virus Protection",
+        0x01B0 => "AL Encryption",
+        0x01B1 => "AL Screen Saver",
+        0x01B2 => "AL Alarms",
+        0x01B3 => "AL Clock",
+        0x01B4 => "AL File Browser",
+        0x01B5 => "AL Power Status",
+        0x01B6 => "AL Image Browser",
+        0x01B7 => "AL Audio Browser",
+        0x01B8 => "AL Movie Browser",
+        0x01B9 => "AL Digital Rights Manager",
+        0x01BA => "AL Digital Wallet",
+        0x01BC => "AL Instant Messaging",
+        0x01BD => "AL OEM Features/Tips/Tutorial Browser",
+        0x01BE => "AL OEM Help",
+        0x01BF => "AL Online Community",
+        0x01C0 => "AL Entertainment Content Browser",
+        0x01C1 => "AL Online Shopping Browser",
+        0x01C2 => "AL SmartCard Information/Help",
+        0x01C3 => "AL Market Monitor/Finance Browser",
+        0x01C4 => "AL Customized Corporate News Browser",
+        0x01C5 => "AL Online Activity Browser",
+        0x01C6 => "AL Research/Search Browser",
+        0x01C7 => "AL Audio Player",
+        0x01C8 => "AL Message Status",
+        0x01C9 => "AL Contact Sync",
+        0x01CA => "AL Navigation",
+        0x01CB => "AL Context-aware Desktop Assistant",
+        0x0200 => "Generic GUI Application Controls",
+        0x0201 => "AC New",
+        0x0202 => "AC Open",
+        0x0203 => "AC Close",
+        0x0204 => "AC Exit",
+        0x0205 => "AC Maximize",
+        0x0206 => "AC Minimize",
+        0x0207 => "AC Save",
+        0x0208 => "AC Print",
+        0x0209 => "AC Properties",
+        0x021A => "AC Undo",
+        0x021B => "AC Copy",
+        0x021C => "AC Cut",
+        0x021D => "AC Paste",
+        0x021E => "AC Select All",
+        0x021F => "AC Find",
+        0x0220 => "AC Find and Replace",
+        0x0221 => "AC Search",
+        0x0222 => "AC Go To",
+        0x0223 => "AC Home",
+        0x0224 => "AC Back",
+        0x0225 => "AC Forward",
+        0x0226 => "AC Stop",
+        0x0227 => "AC Refresh",
+        0x0228 => "AC Previous Link",
+        0x0229 => "AC Next Link",
+        0x022A => "AC Bookmarks",
+        0x022B => "AC History",
+        0x022C => "AC Subscriptions",
+        0x022D => "AC Zoom In",
+        0x022E => "AC Zoom Out",
+        0x022F => "AC Zoom",
+        0x0230 => "AC Full Screen View",
+        0x0231 => "AC Normal View",
+        0x0232 => "AC View Toggle",
+        0x0233 => "AC Scroll Up",
+        0x0234 => "AC Scroll Down",
+        0x0235 => "AC Scroll",
+        0x0236 => "AC Pan Left",
+        0x0237 => "AC Pan Right",
+        0x0238 => "AC Pan",
+        0x0239 => "AC New Window",
+        0x023A => "AC Tile Horizontally",
+        0x023B => "AC Tile Vertically",
+        0x023C => "AC Format",
+        0x023D => "AC Edit",
+        0x0279 => "AC Redo/Repeat",
+        0x0289 => "AC Reply",
+        0x028A => "AC Reply All",
+        0x028B => "AC Forward Msg",
+        0x028C => "AC Send",
+        0x029D => "AC Next Keyboard Layout Select",
+        0x029F => "AC Desktop Show All Windows",
+        0x02A2 => "AC Desktop Show All Applications",
+        _ => return None,
+    })
+}
+
+// Digitizers (0x0D)
+fn digitizers(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "Digitizer",
+        0x02 => "Pen",
+        0x03 => "Light Pen",
+        0x04 => "Touch Screen",
+        0x05 => "Touch Pad",
+        0x06 => "Whiteboard",
+        0x07 => "Coordinate Measuring Machine",
+        0x08 => "3D Digitizer",
+        0x09 => "Stereo Plotter",
+        0x0A => "Articulated Arm",
+        0x0B => "Armature",
+        0x0C => "Multiple Point Digitizer",
+        0x0D => "Free Space Wand",
+        0x0E => "Device Configuration",
+        0x0F => "Capacitive Heat Map Digitizer",
+        0x20 => "Stylus",
+        0x21 => "Puck",
+        0x22 => "Finger",
+        0x23 => "Device Settings",
+        0x24 => "Character Gesture",
+        0x30 => "Tip Pressure",
+        0x31 => "Barrel Pressure",
+        0x32 => "In Range",
+        0x33 => "Touch",
+        0x34 => "Untouch",
+        0x35 => "Tap",
+        0x36 => "Quality",
+        0x37 => "Data Valid",
+        0x38 => "Transducer Index",
+        0x39 => "Tablet Function Keys",
+        0x3A => "Program Change Keys",
+        0x3B => "Battery Strength",
+        0x3C => "Invert",
+        0x3D => "X Tilt",
+        0x3E => "Y Tilt",
+        0x3F => "Azimuth",
+        0x40 => "Altitude",
+        0x41 => "Twist",
+        0x42 => "Tip Switch",
+        0x43 => "Secondary Tip Switch",
+        0x44 => "Barrel Switch",
+        0x45 => "Eraser",
+        0x46 => "Tablet Pick",
+        0x47 => "Touch Valid",
+        0x48 => "Width",
+        0x49 => "Height",
+        0x51 => "Contact Identifier",
+        0x52 => "Device Mode",
+        0x53 => "Device Identifier",
+        0x54 => "Contact Count",
+        0x55 => "Contact Count Maximum",
+        0x56 => "Scan Time",
+        0x57 => "Surface Switch",
+        0x58 => "Button Switch",
+        0x59 => "Pad Type",
+        0x5A => "Secondary Barrel Switch",
+        0x5B => "Transducer Serial Number",
+        0x5C => "Preferred Color",
+        0x5D => "Preferred Color is Locked",
+        0x5E => "Preferred Line Width",
+        0x5F => "Preferred Line Width is Locked",
+        0x60 => "Latency Mode",
+        0x61 => "Gesture Character Quality",
+        0x62 => "Character Gesture Data Length",
+        0x63 => "Character Gesture Data",
+        0x64 => "Gesture Character Encoding",
+        0x6E => "Transducer Serial Number Part 2",
+        0x6F => "No Preferred Color",
+        0x70 => "Preferred Line Style",
+        0x71 => "Preferred Line Style is Locked",
+        _ => return None,
+    })
+}
+
+// Sensors (0x20), sensor categories and types only
+fn sensors(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "Sensor",
+        0x10 => "Biometric",
+        0x11 => "Biometric: Human Presence",
+        0x12 => "Biometric: Human Proximity",
+        0x13 => "Biometric: Human Touch",
+        0x20 => "Electrical",
+        0x30 => "Environmental",
+        0x31 => "Environmental: Atmospheric Pressure",
+        0x32 => "Environmental: Humidity",
+        0x33 => "Environmental: Temperature",
+        0x40 => "Light",
+        0x41 => "Light: Ambient Light",
+        0x50 => "Location",
+        0x60 => "Mechanical",
+        0x70 => "Motion",
+        0x73 => "Motion: Accelerometer 3D",
+        0x76 => "Motion: Gyrometer 3D",
+        0x77 => "Motion: Motion Detector",
+        0x80 => "Orientation",
+        0x83 => "Orientation: Compass 3D",
+        0x86 => "Orientation: Inclinometer 3D",
+        0x8A => "Orientation: Device Orientation",
+        0x90 => "Scanner",
+        0xA0 => "Time",
+        0xE0 => "Other",
+        _ => return None,
+    })
+}
+
+// Power (0x84)
+fn power(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "iName",
+        0x02 => "Present Status",
+        0x03 => "Changed Status",
+        0x04 => "UPS",
+        0x05 => "Power Supply",
+        0x10 => "Battery System",
+        0x11 => "Battery System ID",
+        0x12 => "Battery",
+        0x13 => "Battery ID",
+        0x14 => "Charger",
+        0x15 => "Charger ID",
+        0x16 => "Power Converter",
+        0x17 => "Power Converter ID",
+        0x18 => "Outlet System",
+        0x19 => "Outlet System ID",
+        0x1A => "Input",
+        0x1B => "Input ID",
+        0x1C => "Output",
+        0x1D => "Output ID",
+        0x1E => "Flow",
+        0x1F => "Flow ID",
+        0x20 => "Outlet",
+        0x21 => "Outlet ID",
+        0x22 => "Gang",
+        0x23 => "Gang ID",
+        0x24 => "Power Summary",
+        0x25 => "Power Summary ID",
+        0x30 => "Voltage",
+        0x31 => "Current",
+        0x32 => "Frequency",
+        0x33 => "Apparent Power",
+        0x34 => "Active Power",
+        0x35 => "Percent Load",
+        0x36 => "Temperature",
+        0x37 => "Humidity",
+        0x38 => "Bad Count",
+        0x40 => "Config Voltage",
+        0x41 => "Config Current",
+        0x42 => "Config Frequency",
+        0x43 => "Config Apparent Power",
+        0x44 => "Config Active Power",
+        0x45 => "Config Percent Load",
+        0x46 => "Config Temperature",
+        0x47 => "Config Humidity",
+        0x50 => "Switch On Control",
+        0x51 => "Switch Off Control",
+        0x52 => "Toggle Control",
+        0x53 => "Low Voltage Transfer",
+        0x54 => "High Voltage Transfer",
+        0x55 => "Delay Before Reboot",
+        0x56 => "Delay Before Startup",
+        0x57 => "Delay Before Shutdown",
+        0x58 => "Test",
+        0x59 => "Module Reset",
+        0x5A => "Audible Alarm Control",
+        0x60 => "Present",
+        0x61 => "Good",
+        0x62 => "Internal Failure",
+        0x63 => "Voltage Out Of Range",
+        0x64 => "Frequency Out Of Range",
+        0x65 => "Overload",
+        0x66 => "Over Charged",
+        0x67 => "Over Temperature",
+        0x68 => "Shutdown Requested",
+        0x69 => "Shutdown Imminent",
+        0x6B => "Switch On/Off",
+        0x6C => "Switchable",
+        0x6D => "Used",
+        0x6E => "Boost",
+        0x6F => "Buck",
+        0x70 => "Initialized",
+        0x71 => "Tested",
+        0x72 => "Awaiting Power",
+        0x73 => "Communication Lost",
+        0xFD => "iManufacturer",
+        0xFE => "iProduct",
+        0xFF => "iSerialNumber",
+        _ => return None,
+    })
+}
+
+// Battery System (0x85)
+fn battery_system(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "Smart Battery Battery Mode",
+        0x02 => "Smart Battery Battery Status",
+        0x03 => "Smart Battery Alarm Warning",
+        0x04 => "Smart Battery Charger Mode",
+        0x05 => "Smart Battery Charger Status",
+        0x06 => "Smart Battery Charger Spec Info",
+        0x07 => "Smart Battery Selector State",
+        0x08 => "Smart Battery Selector Presets",
+        0x09 => "Smart Battery Selector Info",
+        0x10 => "Optional Mfg Function 1",
+        0x11 => "Optional Mfg Function 2",
+        0x12 => "Optional Mfg Function 3",
+        0x13 => "Optional Mfg Function 4",
+        0x14 => "Optional Mfg Function 5",
+        0x15 => "Connection To SM Bus",
+        0x16 => "Output Connection",
+        0x17 => "Charger Connection",
+        0x18 => "Battery Insertion",
+        0x19 => "Use Next",
+        0x1A => "OK To Use",
+        0x1B => "Battery Supported",
+        0x1C => "Selector Revision",
+        0x1D => "Charging Indicator",
+        0x28 => "Manufacturer Access",
+        0x29 => "Remaining Capacity Limit",
+        0x2A => "Remaining Time Limit",
+        0x2B => "At Rate",
+        0x2C => "Capacity Mode",
+        0x2D => "Broadcast To Charger",
+        0x2E => "Primary Battery",
+        0x2F => "Charge Controller",
+        0x40 => "Terminate Charge",
+        0x41 => "Terminate Discharge",
+        0x42 => "Below Remaining Capacity Limit",
+        0x43 => "Remaining Time Limit Expired",
+        0x44 => "Charging",
+        0x45 => "Discharging",
+        0x46 => "Fully Charged",
+        0x47 => "Fully Discharged",
+        0x48 => "Conditioning Flag",
+        0x49 => "At Rate OK",
+        0x4A => "Smart Battery Error Code",
+        0x4B => "Need Replacement",
+        0x60 => "At Rate Time To Full",
+        0x61 => "At Rate Time To Empty",
+        0x62 => "Average Current",
+        0x63 => "Max Error",
+        0x64 => "Relative State Of Charge",
+        0x65 => "Absolute State Of Charge",
+        0x66 => "Remaining Capacity",
+        0x67 => "Full Charge Capacity",
+        0x68 => "Run Time To Empty",
+        0x69 => "Average Time To Empty",
+        0x6A => "Average Time To Full",
+        0x6B => "Cycle Count",
+        0x80 => "Battery Pack Model Level",
+        0x81 => "Internal Charge Controller",
+        0x82 => "Primary Battery Support",
+        0x83 => "Design Capacity",
+        0x84 => "Specification Info",
+        0x85 => "Manufacture Date",
+        0x86 => "Serial Number",
+        0x87 => "iManufacturer Name",
+        0x88 => "iDevice Name",
+        0x89 => "iDevice Chemistry",
+        0x8A => "Manufacturer Data",
+        0x8B => "Rechargable",
+        0x8C => "Warning Capacity Limit",
+        0x8D => "Capacity Granularity 1",
+        0x8E => "Capacity Granularity 2",
+        0x8F => "iOEM Information",
+        0xC0 => "Inhibit Charge",
+        0xC1 => "Enable Polling",
+        0xC2 => "Reset To Zero",
+        0xD0 => "AC Present",
+        0xD1 => "Battery Present",
+        0xD2 => "Power Fail",
+        0xD3 => "Alarm Inhibited",
+        0xD4 => "Thermistor Under Range",
+        0xD5 => "Thermistor Hot",
+        0xD6 => "Thermistor Cold",
+        0xD7 => "Thermistor Over Range",
+        0xD8 => "Voltage Out Of Range",
+        0xD9 => "Current Out Of Range",
+        0xDA => "Current Not Regulated",
+        0xDB => "Voltage Not Regulated",
+        0xDC => "Master Mode",
+        0xF0 => "Charger Selector Support",
+        0xF1 => "Charger Spec",
+        0xF2 => "Level 2",
+        0xF3 => "Level 3",
+        _ => return None,
+    })
+}
+
+// FIDO Alliance (0xF1D0)
+fn fido(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "U2F Authenticator Device",
+        0x20 => "Input Report Data",
+        0x21 => "Output Report Data",
+        _ => return None,
+    })
+}
@@ -0,0 +1,205 @@
+use super::{descriptor_type, u16_at, Error, ErrorKind};
+use alloc::vec::Vec;
+
+// hub class descriptor (USB 2.0 11.23.2.1) or SuperSpeed hub descriptor
+// (USB 3.2 10.15.2.1), read from the hub itself rather than from a port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HubDescriptor {
+    pub descriptor_type: u8,
+    pub num_ports: u8,
+    pub characteristics: u16,
+    pub power_on_to_power_good: u8, // 2 ms units
+    pub controller_current: u8, // mA, 4 mA units on SuperSpeed hubs
+    pub header_decode_latency: Option<u8>, // SuperSpeed only, 0.1 us units
+    pub hub_delay: Option<u16>, // SuperSpeed only, ns
+    removable: Vec<u8>, // DeviceRemovable, bit N for port N, set if the device is not removable
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortControl {
+    Ganged, // all ports at once
+    Individual,
+    None,
+}
+
+impl HubDescriptor {
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < 2 {
+            return Err(Error::new(0, ErrorKind::Truncated { needed: 2, available: bytes.len() }))
+        }
+        let min_length = match bytes[1] {
+            descriptor_type::HUB => 7,
+            descriptor_type::SUPERSPEED_HUB => 12,
+            found => return Err(Error::new(1, ErrorKind::UnexpectedType { expected: descriptor_type::HUB, found })),
+        };
+        let length = usize::from(bytes[0]);
+        if length < min_length {
+            return Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+        }
+        if bytes.len() < length {
+            return Err(Error::new(0, ErrorKind::Truncated { needed: length, available: bytes.len() }))
+        }
+        let num_ports = bytes[2];
+        let (removable, header_decode_latency, hub_delay) = if bytes[1] == descriptor_type::SUPERSPEED_HUB {
+            if num_ports > 15 {
+                return Err(Error::new(2, ErrorKind::InvalidValue { field: "bNbrPorts", value: num_ports.into() }))
+            }
+            (bytes[10..12].to_vec(), Some(bytes[7]), Some(u16_at(bytes, 8)))
+        } else {
+            // one bit per port plus the reserved bit 0, rounded up to whole bytes
+            let removable_length = usize::from(num_ports) / 8 + 1;
+            if length < 7 + removable_length {
+                return Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+            }
+            (bytes[7..7 + removable_length].to_vec(), None, None)
+        };
+        Ok(HubDescriptor {
+            descriptor_type: bytes[1],
+            num_ports,
+            characteristics: u16_at(bytes, 3),
+            power_on_to_power_good: bytes[5],
+            controller_current: bytes[6],
+            header_decode_latency,
+            hub_delay,
+            removable,
+        })
+    }
+
+    pub fn superspeed(&self) -> bool {
+        self.descriptor_type == descriptor_type::SUPERSPEED_HUB
+    }
+
+    // logical power switching mode, wHubCharacteristics D1..D0
+    pub fn power_switching(&self) -> PortControl {
+        match self.characteristics & 0x03 {
+            0 => PortControl::Ganged,
+            1 => PortControl::Individual,
+            _ => PortControl::None,
+        }
+    }
+
+    // over-current protection mode, wHubCharacteristics D4..D3
+    pub fn over_current_protection(&self) -> PortControl {
+        match self.characteristics >> 3 & 0x03 {
+            0 => PortControl::Ganged,
+            1 => PortControl::Individual,
+            _ => PortControl::None,
+        }
+    }
+
+    pub fn compound_device(&self) -> bool {
+        self.characteristics & 0x04 != 0
+    }
+
+    // full speed bit times the transaction translator needs between transactions
+    pub fn tt_think_time(&self) -> Option<u8> {
+        match self.superspeed() {
+            true => None,
+            false => Some(8 * ((self.characteristics >> 5 & 0x03) as u8 + 1)),
+        }
+    }
+
+    pub fn port_indicators(&self) -> bool {
+        !self.superspeed() && self.characteristics & 0x80 != 0
+    }
+
+    pub fn power_on_to_power_good_ms(&self) -> u16 {
+        u16::from(self.power_on_to_power_good) * 2
+    }
+
+    pub fn controller_current_ma(&self) -> u16 {
+        match self.superspeed() {
+            true => u16::from(self.controller_current) * 4,
+            false => u16::from(self.controller_current),
+        }
+    }
+
+    // whether the device attached to `port` (numbered from 1) can be unplugged
+    pub fn removable(&self, port: u8) -> bool {
+        let port = usize::from(port);
+        !matches!(self.removable.get(port / 8), Some(byte) if byte & 1 << (port % 8) != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // four ports, individual power switching and over-current protection,
+    // 32 FS bit times TT think time, port indicators, port 2 not removable
+    const HUB: [u8; 9] = [0x09, 0x29, 0x04, 0xE9, 0x00, 0x32, 0x64, 0x04, 0xFF];
+
+    // four ports, 100 mA in 4 mA units, port 2 not removable
+    const SUPERSPEED_HUB: [u8; 12] = [0x0C, 0x2A, 0x04, 0x09, 0x00, 0x0A, 0x19, 0x04, 0x00, 0x01, 0x04, 0x00];
+
+    #[test]
+    fn parses_hub() {
+        let hub = HubDescriptor::parse(&HUB).unwrap();
+        assert!(!hub.superspeed());
+        assert_eq!(hub.num_ports, 4);
+        assert_eq!((hub.power_switching(), hub.over_current_protection()), (PortControl::Individual, PortControl::Individual));
+        assert!(!hub.compound_device());
+        assert_eq!(hub.tt_think_time(), Some(32));
+        assert!(hub.port_indicators());
+        assert_eq!((hub.power_on_to_power_good_ms(), hub.controller_current_ma()), (100, 100));
+        assert_eq!((1..=4).map(|port| hub.removable(port)).collect::<Vec<_>>(), [true, false, true, true]);
+    }
+
+    #[test]
+    fn tt_think_time() {
+        let mut bytes = HUB;
+        for (bits, think_time) in [(0x00, 8), (0x20, 16), (0x40, 24), (0x60, 32)] {
+            bytes[3] = bits;
+            assert_eq!(HubDescriptor::parse(&bytes).unwrap().tt_think_time(), Some(think_time));
+        }
+    }
+
+    #[test]
+    fn reserved_removable_bit() {
+        // bit 0 of DeviceRemovable is reserved, not port 0 or port 1
+        let mut bytes = HUB;
+        bytes[7] = 0x01;
+        let hub = HubDescriptor::parse(&bytes).unwrap();
+        assert!((1..=4).all(|port| hub.removable(port)));
+        // eight ports and the reserved bit need a second byte
+        let bytes = [0x0B, 0x29, 0x08, 0x00, 0x00, 0x32, 0x64, 0x00, 0x01, 0xFF, 0xFF];
+        let hub = HubDescriptor::parse(&bytes).unwrap();
+        assert!((1..=7).all(|port| hub.removable(port)));
+        assert!(!hub.removable(8));
+        let mut bytes = HUB;
+        bytes[2] = 8;
+        assert_eq!(HubDescriptor::parse(&bytes[..8]), Err(Error::new(0, ErrorKind::Truncated { needed: 9, available: 8 })));
+        bytes[0] = 8;
+        assert_eq!(HubDescriptor::parse(&bytes[..8]), Err(Error::new(0, ErrorKind::InvalidLength { length: 8 })));
+    }
+
+    #[test]
+    fn parses_superspeed_hub() {
+        let hub = HubDescriptor::parse(&SUPERSPEED_HUB).unwrap();
+        assert!(hub.superspeed());
+        // bMaxHubCurrent counts 4 mA units
+        assert_eq!(hub.controller_current, 25);
+        assert_eq!(hub.controller_current_ma(), 100);
+        assert_eq!((hub.header_decode_latency, hub.hub_delay), (Some(4), Some(256)));
+        // no transaction translator and no port indicators
+        assert_eq!(hub.tt_think_time(), None);
+        assert!(!hub.port_indicators());
+        assert_eq!((1..=4).map(|port| hub.removable(port)).collect::<Vec<_>>(), [true, false, true, true]);
+        let mut bytes = SUPERSPEED_HUB;
+        bytes[2] = 16;
+        assert_eq!(
+            HubDescriptor::parse(&bytes),
+            Err(Error::new(2, ErrorKind::InvalidValue { field: "bNbrPorts", value: 16 })),
+        );
+    }
+
+    #[test]
+    fn wrong_type() {
+        let mut bytes = HUB;
+        bytes[1] = descriptor_type::DEVICE;
+        assert_eq!(
+            HubDescriptor::parse(&bytes),
+            Err(Error::new(1, ErrorKind::UnexpectedType { expected: descriptor_type::HUB, found: 0x01 })),
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use super::{
+    configuration::{AlternateSetting, Endpoint},
+    header, Error, ErrorKind,
+};
+use alloc::vec::Vec;
+
+// Mass Storage Class: the interface subclass names the command set and the
+// protocol the transport. A UAS (USB Attached SCSI) setting tags each of its
+// four bulk endpoints with a pipe usage descriptor.
+
+pub const CLASS_MASS_STORAGE: u8 = 0x08;
+
+pub const SUBCLASS_SCSI_TRANSPARENT: u8 = 0x06;
+
+pub const PROTOCOL_CBI_COMPLETION_INTERRUPT: u8 = 0x00;
+pub const PROTOCOL_CBI: u8 = 0x01;
+pub const PROTOCOL_BOT: u8 = 0x50;
+pub const PROTOCOL_UAS: u8 = 0x62;
+
+// bDescriptorType of a pipe usage descriptor (UAS 1.0 5.3.3.1)
+pub const PIPE_USAGE: u8 = 0x24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Cbi { completion_interrupt: bool }, // Control/Bulk/Interrupt, floppy drives only
+    BulkOnly,
+    Uas,
+    Other(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Pipe {
+    Command,
+    Status,
+    DataIn,
+    DataOut,
+}
+
+// the endpoints of a UAS setting by the pipe they carry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UasPipes {
+    pub command: Option<u8>, // endpoint address
+    pub status: Option<u8>,
+    pub data_in: Option<u8>,
+    pub data_out: Option<u8>,
+    pub problems: Vec<Error>,
+}
+
+impl Transport {
+    // the transport of `setting` if it is a mass storage interface
+    pub fn of(setting: &AlternateSetting) -> Option<Self> {
+        if setting.interface_class != CLASS_MASS_STORAGE {
+            return None;
+        }
+        Some(match setting.interface_protocol {
+            PROTOCOL_CBI_COMPLETION_INTERRUPT => Transport::Cbi { completion_interrupt: true },
+            PROTOCOL_CBI => Transport::Cbi { completion_interrupt: false },
+            PROTOCOL_BOT => Transport::BulkOnly,
+            PROTOCOL_UAS => Transport::Uas,
+            protocol => Transport::Other(protocol),
+        })
+    }
+}
+
+impl Pipe {
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = header(bytes, PIPE_USAGE, 4)?;
+        Ok(match bytes[2] {
+            1 => Pipe::Command,
+            2 => Pipe::Status,
+            3 => Pipe::DataIn,
+            4 => Pipe::DataOut,
+            id => return Err(Error::new(2, ErrorKind::InvalidValue { field: "bPipeID", value: id.into() })),
+        })
+    }
+
+    // the pipe usage descriptor following `endpoint`, if any
+    pub fn of(endpoint: &Endpoint) -> Option<Result<Self, Error>> {
+        endpoint.extra.iter()
+            .find(|raw| raw.descriptor_type() == PIPE_USAGE)
+            .map(|raw| Pipe::parse(&raw.bytes).map_err(|err| err.at(raw.offset)))
+    }
+
+    // bPipeID
+    pub fn id(self) -> u8 {
+        match self {
+            Pipe::Command => 1,
+            Pipe::Status => 2,
+            Pipe::DataIn => 3,
+            Pipe::DataOut => 4,
+        }
+    }
+
+    // status and data-in pipes flow to the host
+    pub fn is_in(self) -> bool {
+        matches!(self, Pipe::Status | Pipe::DataIn)
+    }
+}
+
+impl UasPipes {
+    // assign the endpoints of `setting` to pipes, reporting pipes that are
+    // missing, repeated or on an endpoint of the wrong direction
+    pub fn parse(setting: &AlternateSetting) -> Self {
+        let mut pipes = UasPipes { command: None, status: None, data_in: None, data_out: None, problems: Vec::new() };
+        for endpoint in &setting.endpoints {
+            let pipe = match Pipe::of(endpoint) {
+                Some(Ok(pipe)) => pipe,
+                Some(Err(err)) => {
+                    pipes.problems.push(err);
+                    continue;
+                },
+                None => {
+                    let kind = ErrorKind::CountMismatch { field: "pipe usage descriptors", declared: 1, found: 0 };
+                    pipes.problems.push(Error::new(endpoint.offset, kind));
+                    continue;
+                },
+            };
+            if pipe.is_in() != (endpoint.address & 0x80 != 0) {
+                let kind = ErrorKind::InvalidValue { field: "bEndpointAddress", value: endpoint.address.into() };
+                pipes.problems.push(Error::new(endpoint.offset + 2, kind));
+            }
+            let slot = match pipe {
+                Pipe::Command => &mut pipes.command,
+                Pipe::Status => &mut pipes.status,
+                Pipe::DataIn => &mut pipes.data_in,
+                Pipe::DataOut => &mut pipes.data_out,
+            };
+            match slot {
+                Some(_) => {
+                    let kind = ErrorKind::InvalidValue { field: "repeated bPipeID", value: pipe.id().into() };
+                    pipes.problems.push(Error::new(endpoint.offset, kind));
+                },
+                None => *slot = Some(endpoint.address),
+            }
+        }
+        let found = [pipes.command, pipes.status, pipes.data_in, pipes.data_out].iter().flatten().count();
+        if found < 4 {
+            pipes.problems.push(Error::new(setting.offset, ErrorKind::CountMismatch { field: "UAS pipes", declared: 4, found }));
+        }
+        pipes
+    }
+}
+
+pub fn subclass_name(subclass: u8) -> Option<&'static str> {
+    Some(match subclass {
+        0x00 => "SCSI (not reported)",
+        0x01 => "RBC",
+        0x02 => "MMC-5 (ATAPI)",
+        0x03 => "QIC-157",
+        0x04 => "UFI",
+        0x05 => "SFF-8070i",
+        SUBCLASS_SCSI_TRANSPARENT => "SCSI Transparent",
+        0x07 => "LSD FS",
+        0x08 => "IEEE 1667",
+        0xFF => "Vendor Specific",
+        _ => return None,
+    })
+}
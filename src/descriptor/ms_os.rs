@@ -0,0 +1,714 @@
+use super::{bos::Platform, u16_at, u32_at, Bcd, Error, ErrorKind, Uuid};
+use alloc::{string::String, vec::Vec};
+
+// Microsoft OS descriptors. Version 2.0 devices announce a descriptor set in a
+// BOS platform capability and return it for a vendor request; version 1.0
+// devices answer string descriptor 0xEE with the vendor code used to fetch the
+// extended compat ID and extended properties feature descriptors.
+
+// D8DD60DF-4589-4CC7-9CD2-659D9E648A9F
+pub const PLATFORM_UUID: Uuid = Uuid([
+    0xDF, 0x60, 0xDD, 0xD8, 0x89, 0x45, 0xC7, 0x4C, 0x9C, 0xD2, 0x65, 0x9D, 0x9E, 0x64, 0x8A, 0x9F,
+]);
+
+// wIndex of the vendor requests
+pub const DESCRIPTOR_SET_INDEX: u16 = 0x07;
+pub const EXTENDED_COMPAT_ID_INDEX: u16 = 0x04;
+pub const EXTENDED_PROPERTIES_INDEX: u16 = 0x05;
+
+// string descriptor index of the OS string descriptor
+pub const OS_STRING_INDEX: u8 = 0xEE;
+
+pub mod descriptor_type {
+    pub const SET_HEADER: u16 = 0x00;
+    pub const SUBSET_HEADER_CONFIGURATION: u16 = 0x01;
+    pub const SUBSET_HEADER_FUNCTION: u16 = 0x02;
+    pub const FEATURE_COMPATIBLE_ID: u16 = 0x03;
+    pub const FEATURE_REG_PROPERTY: u16 = 0x04;
+    pub const FEATURE_MIN_RESUME_TIME: u16 = 0x05;
+    pub const FEATURE_MODEL_ID: u16 = 0x06;
+    pub const FEATURE_CCGP_DEVICE: u16 = 0x07;
+    pub const FEATURE_VENDOR_REVISION: u16 = 0x08;
+}
+
+// one entry of the MS OS 2.0 platform capability
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DescriptorSetInfo {
+    pub windows_version: u32,
+    pub total_length: u16,
+    pub vendor_code: u8, // bRequest of the vendor request returning the set
+    pub alt_enum_code: u8, // nonzero if the device supports alternate enumeration
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptorSet {
+    pub windows_version: u32,
+    pub total_length: u16,
+    pub features: Vec<Feature>, // apply to the whole device
+    pub configurations: Vec<ConfigurationSubset>,
+    pub problems: Vec<Error>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigurationSubset {
+    pub configuration_value: u8,
+    pub features: Vec<Feature>,
+    pub functions: Vec<FunctionSubset>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSubset {
+    pub first_interface: u8,
+    pub features: Vec<Feature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Feature {
+    CompatibleId { compatible_id: String, sub_compatible_id: String },
+    RegistryProperty(RegistryProperty),
+    MinResumeTime { recovery_time: u8, signaling_time: u8 }, // ms
+    ModelId(Uuid),
+    CcgpDevice,
+    VendorRevision(u16),
+    Unknown { descriptor_type: u16, bytes: Vec<u8> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryProperty {
+    pub data_type: u32,
+    pub name: String,
+    pub value: PropertyValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropertyValue {
+    String(String),
+    MultiString(Vec<String>),
+    Dword(u32),
+    Binary(Vec<u8>),
+}
+
+// the answer to GET_DESCRIPTOR(string, 0xEE)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OsStringDescriptor {
+    pub vendor_code: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedCompatId {
+    pub version: Bcd,
+    pub functions: Vec<CompatIdFunction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatIdFunction {
+    pub first_interface: u8,
+    pub compatible_id: String,
+    pub sub_compatible_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedProperties {
+    pub version: Bcd,
+    pub properties: Vec<RegistryProperty>,
+}
+
+impl DescriptorSetInfo {
+    // the descriptor set entries of `platform` if it is the MS OS 2.0 capability
+    pub fn parse(platform: &Platform) -> Option<Result<Vec<Self>, Error>> {
+        if platform.uuid != PLATFORM_UUID {
+            return None;
+        }
+        // the entries follow the 20 bytes of capability header and UUID
+        if platform.data.is_empty() || !platform.data.len().is_multiple_of(8) {
+            return Some(Err(Error::new(20, ErrorKind::InvalidLength { length: (20 + platform.data.len()) as u8 })))
+        }
+        Some(Ok(platform.data.chunks_exact(8).map(|entry| DescriptorSetInfo {
+            windows_version: u32_at(entry, 0),
+            total_length: u16_at(entry, 4),
+            vendor_code: entry[6],
+            alt_enum_code: entry[7],
+        }).collect()))
+    }
+}
+
+impl DescriptorSet {
+    pub const HEADER_LENGTH: usize = 10;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < Self::HEADER_LENGTH {
+            return Err(Error::new(0, ErrorKind::Truncated { needed: Self::HEADER_LENGTH, available: bytes.len() }))
+        }
+        if u16_at(bytes, 2) != descriptor_type::SET_HEADER {
+            return Err(Error::new(2, ErrorKind::InvalidValue { field: "wDescriptorType", value: u16_at(bytes, 2).into() }))
+        }
+        if usize::from(u16_at(bytes, 0)) != Self::HEADER_LENGTH {
+            return Err(Error::new(0, ErrorKind::InvalidValue { field: "wLength", value: u16_at(bytes, 0).into() }))
+        }
+        let mut set = DescriptorSet {
+            windows_version: u32_at(bytes, 4),
+            total_length: u16_at(bytes, 8),
+            features: Vec::new(),
+            configurations: Vec::new(),
+            problems: Vec::new(),
+        };
+        let total_length = usize::from(set.total_length);
+        if total_length != bytes.len() {
+            set.problems.push(Error::new(8, ErrorKind::CountMismatch {
+                field: "wTotalLength",
+                declared: total_length as u32,
+                found: bytes.len(),
+            }));
+        }
+        let bytes = &bytes[..total_length.clamp(Self::HEADER_LENGTH, bytes.len())];
+        // end offsets of the configuration and function subsets being filled
+        let mut configuration_end = None;
+        let mut function_end = None;
+        let mut offset = Self::HEADER_LENGTH;
+        while offset < bytes.len() {
+            let rest = &bytes[offset..];
+            if rest.len() < 4 {
+                set.problems.push(Error::new(offset, ErrorKind::Truncated { needed: 4, available: rest.len() }));
+                break;
+            }
+            let length = usize::from(u16_at(rest, 0));
+            let kind = u16_at(rest, 2);
+            if length < 4 || length > rest.len() {
+                set.problems.push(Error::new(offset, ErrorKind::InvalidValue { field: "wLength", value: length as u32 }));
+                break;
+            }
+            let descriptor = &rest[..length];
+            if function_end.is_some_and(|end| offset >= end) {
+                function_end = None;
+            }
+            if configuration_end.is_some_and(|end| offset >= end) {
+                configuration_end = None;
+                function_end = None;
+            }
+            match kind {
+                descriptor_type::SUBSET_HEADER_CONFIGURATION | descriptor_type::SUBSET_HEADER_FUNCTION => {
+                    if length != 8 {
+                        set.problems.push(Error::new(offset, ErrorKind::InvalidValue { field: "wLength", value: length as u32 }));
+                        offset += length;
+                        continue;
+                    }
+                    let end = offset + usize::from(u16_at(descriptor, 6));
+                    let parent_end = match kind {
+                        descriptor_type::SUBSET_HEADER_CONFIGURATION => bytes.len(),
+                        _ => configuration_end.unwrap_or(bytes.len()),
+                    };
+                    if end < offset + length || end > parent_end {
+                        set.problems.push(Error::new(offset + 6, ErrorKind::InvalidValue {
+                            field: "wTotalLength",
+                            value: u16_at(descriptor, 6).into(),
+                        }));
+                    }
+                    let end = end.clamp(offset + length, parent_end);
+                    if kind == descriptor_type::SUBSET_HEADER_CONFIGURATION {
+                        if configuration_end.is_some() {
+                            set.problems.push(Error::new(offset, ErrorKind::Misplaced { descriptor_type: kind as u8 }));
+                        }
+                        set.configurations.push(ConfigurationSubset {
+                            configuration_value: descriptor[4],
+                            features: Vec::new(),
+                            functions: Vec::new(),
+                        });
+                        configuration_end = Some(end);
+                        function_end = None;
+                    } else {
+                        match set.configurations.last_mut() {
+                            Some(configuration) if configuration_end.is_some() => {
+                                configuration.functions.push(FunctionSubset { first_interface: descriptor[4], features: Vec::new() });
+                                function_end = Some(end);
+                            },
+                            // function subsets only exist inside a configuration subset
+                            _ => set.problems.push(Error::new(offset, ErrorKind::Misplaced { descriptor_type: kind as u8 })),
+                        }
+                    }
+                },
+                descriptor_type::SET_HEADER => {
+                    set.problems.push(Error::new(offset, ErrorKind::Misplaced { descriptor_type: kind as u8 }));
+                },
+                _ => {
+                    let feature = match Feature::parse(descriptor) {
+                        Ok(feature) => feature,
+                        Err(err) => {
+                            set.problems.push(err.at(offset));
+                            offset += length;
+                            continue;
+                        },
+                    };
+                    let features = match (set.configurations.last_mut(), configuration_end, function_end) {
+                        (Some(configuration), Some(_), Some(_)) => match configuration.functions.last_mut() {
+                            Some(function) => &mut function.features,
+                            None => &mut configuration.features,
+                        },
+                        (Some(configuration), Some(_), None) => &mut configuration.features,
+                        _ => &mut set.features,
+                    };
+                    features.push(feature);
+                },
+            }
+            offset += length;
+        }
+        Ok(set)
+    }
+}
+
+impl Feature {
+    fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let kind = u16_at(bytes, 2);
+        let expected = match kind {
+            descriptor_type::FEATURE_COMPATIBLE_ID | descriptor_type::FEATURE_MODEL_ID => Some(20),
+            descriptor_type::FEATURE_MIN_RESUME_TIME | descriptor_type::FEATURE_VENDOR_REVISION => Some(6),
+            descriptor_type::FEATURE_CCGP_DEVICE => Some(4),
+            _ => None,
+        };
+        if expected.is_some_and(|expected| expected != bytes.len()) {
+            return Err(Error::new(0, ErrorKind::InvalidValue { field: "wLength", value: bytes.len() as u32 }))
+        }
+        Ok(match kind {
+            descriptor_type::FEATURE_COMPATIBLE_ID => Feature::CompatibleId {
+                compatible_id: ascii_id(&bytes[4..12]),
+                sub_compatible_id: ascii_id(&bytes[12..20]),
+            },
+            descriptor_type::FEATURE_REG_PROPERTY => {
+                // wPropertyDataType, wPropertyNameLength, name, wPropertyDataLength, data
+                let truncated = || Error::new(0, ErrorKind::InvalidValue { field: "wLength", value: bytes.len() as u32 });
+                if bytes.len() < 8 {
+                    return Err(truncated())
+                }
+                let name_length = usize::from(u16_at(bytes, 6));
+                let data_length_at = 8 + name_length;
+                if bytes.len() < data_length_at + 2 {
+                    return Err(truncated())
+                }
+                let data_length = usize::from(u16_at(bytes, data_length_at));
+                if bytes.len() != data_length_at + 2 + data_length {
+                    return Err(truncated())
+                }
+                Feature::RegistryProperty(RegistryProperty::new(
+                    u16_at(bytes, 4).into(),
+                    &bytes[8..data_length_at],
+                    &bytes[data_length_at + 2..],
+                ))
+            },
+            descriptor_type::FEATURE_MIN_RESUME_TIME => Feature::MinResumeTime {
+                recovery_time: bytes[4],
+                signaling_time: bytes[5],
+            },
+            descriptor_type::FEATURE_MODEL_ID => Feature::ModelId(Uuid::from_slice(&bytes[4..20])),
+            descriptor_type::FEATURE_CCGP_DEVICE => Feature::CcgpDevice,
+            descriptor_type::FEATURE_VENDOR_REVISION => Feature::VendorRevision(u16_at(bytes, 4)),
+            _ => Feature::Unknown { descriptor_type: kind, bytes: bytes.to_vec() },
+        })
+    }
+}
+
+impl RegistryProperty {
+    fn new(data_type: u32, name: &[u8], data: &[u8]) -> Self {
+        let value = match data_type {
+            // REG_SZ, REG_EXPAND_SZ, REG_LINK
+            1 | 2 | 6 => PropertyValue::String(utf16(data)),
+            // REG_DWORD_LITTLE_ENDIAN
+            4 if data.len() == 4 => PropertyValue::Dword(u32_at(data, 0)),
+            // REG_DWORD_BIG_ENDIAN
+            5 if data.len() == 4 => PropertyValue::Dword(u32::from_be_bytes([data[0], data[1], data[2], data[3]])),
+            // REG_MULTI_SZ
+            7 => PropertyValue::MultiString(
+                utf16(data).split('\0').filter(|string| !string.is_empty()).map(String::from).collect()
+            ),
+            _ => PropertyValue::Binary(data.to_vec()),
+        };
+        RegistryProperty { data_type, name: utf16(name), value }
+    }
+}
+
+impl OsStringDescriptor {
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        const SIGNATURE: &[u8] = b"M\0S\0F\0T\x001\x000\x000\0";
+        let bytes = super::header(bytes, super::descriptor_type::STRING, 0x12)?;
+        if &bytes[2..16] != SIGNATURE {
+            return Err(Error::new(2, ErrorKind::InvalidValue { field: "qwSignature", value: u16_at(bytes, 2).into() }))
+        }
+        Ok(OsStringDescriptor { vendor_code: bytes[16] })
+    }
+}
+
+// dwLength, bcdVersion and wIndex common to both feature descriptors
+fn feature_header(bytes: &[u8], index: u16, header_length: usize) -> Result<(Bcd, &[u8]), Error> {
+    if bytes.len() < header_length {
+        return Err(Error::new(0, ErrorKind::Truncated { needed: header_length, available: bytes.len() }))
+    }
+    let length = u32_at(bytes, 0) as usize;
+    if length < header_length {
+        return Err(Error::new(0, ErrorKind::InvalidValue { field: "dwLength", value: length as u32 }))
+    }
+    if bytes.len() < length {
+        return Err(Error::new(0, ErrorKind::Truncated { needed: length, available: bytes.len() }))
+    }
+    if u16_at(bytes, 6) != index {
+        return Err(Error::new(6, ErrorKind::InvalidValue { field: "wIndex", value: u16_at(bytes, 6).into() }))
+    }
+    Ok((Bcd(u16_at(bytes, 4)), &bytes[..length]))
+}
+
+impl ExtendedCompatId {
+    pub const HEADER_LENGTH: usize = 16;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let (version, bytes) = feature_header(bytes, EXTENDED_COMPAT_ID_INDEX, Self::HEADER_LENGTH)?;
+        let count = usize::from(bytes[8]);
+        if bytes.len() != Self::HEADER_LENGTH + 24 * count {
+            return Err(Error::new(8, ErrorKind::CountMismatch {
+                field: "bCount",
+                declared: count as u32,
+                found: (bytes.len() - Self::HEADER_LENGTH) / 24,
+            }))
+        }
+        let functions = bytes[Self::HEADER_LENGTH..].chunks_exact(24).map(|function| CompatIdFunction {
+            first_interface: function[0],
+            compatible_id: ascii_id(&function[2..10]),
+            sub_compatible_id: ascii_id(&function[10..18]),
+        }).collect();
+        Ok(ExtendedCompatId { version, functions })
+    }
+}
+
+impl ExtendedProperties {
+    pub const HEADER_LENGTH: usize = 10;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let (version, bytes) = feature_header(bytes, EXTENDED_PROPERTIES_INDEX, Self::HEADER_LENGTH)?;
+        let count = u16_at(bytes, 8);
+        let mut properties = Vec::new();
+        let mut offset = Self::HEADER_LENGTH;
+        while offset < bytes.len() {
+            // dwSize, dwPropertyDataType, wPropertyNameLength, name, dwPropertyDataLength, data
+            let rest = &bytes[offset..];
+            let invalid = || Error::new(offset, ErrorKind::InvalidValue { field: "dwSize", value: u32_at(rest, 0) });
+            if rest.len() < 14 {
+                return Err(Error::new(offset, ErrorKind::Truncated { needed: 14, available: rest.len() }))
+            }
+            let size = u32_at(rest, 0) as usize;
+            let name_length = usize::from(u16_at(rest, 8));
+            if size > rest.len() || size < 14 + name_length {
+                return Err(invalid())
+            }
+            let data_length = u32_at(rest, 10 + name_length) as usize;
+            if size != 14 + name_length + data_length {
+                return Err(invalid())
+            }
+            properties.push(RegistryProperty::new(
+                u32_at(rest, 4),
+                &rest[10..10 + name_length],
+                &rest[14 + name_length..size],
+            ));
+            offset += size;
+        }
+        if properties.len() != usize::from(count) {
+            return Err(Error::new(8, ErrorKind::CountMismatch {
+                field: "wCount",
+                declared: count.into(),
+                found: properties.len(),
+            }))
+        }
+        Ok(ExtendedProperties { version, properties })
+    }
+}
+
+// an 8 byte compatible ID, padded with zeros
+fn ascii_id(bytes: &[u8]) -> String {
+    bytes.iter().take_while(|&&byte| byte != 0).map(|&byte| char::from(byte)).collect()
+}
+
+// UTF-16LE without its terminating null
+fn utf16(bytes: &[u8]) -> String {
+    let units = bytes.chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]]));
+    let string: String = char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect();
+    String::from(string.trim_end_matches('\0'))
+}
+
+// dwWindowsVersion of a descriptor set
+pub fn windows_version_name(version: u32) -> Option<&'static str> {
+    Some(match version {
+        0x0603_0000 => "Windows 8.1",
+        0x0A00_0000 => "Windows 10",
+        _ => return None,
+    })
+}
+
+// registry value types of registry properties
+pub fn data_type_name(data_type: u32) -> Option<&'static str> {
+    Some(match data_type {
+        1 => "REG_SZ",
+        2 => "REG_EXPAND_SZ",
+        3 => "REG_BINARY",
+        4 => "REG_DWORD_LITTLE_ENDIAN",
+        5 => "REG_DWORD_BIG_ENDIAN",
+        6 => "REG_LINK",
+        7 => "REG_MULTI_SZ",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{borrow::ToOwned, vec};
+
+    const CCGP: [u8; 4] = [0x04, 0x00, 0x07, 0x00];
+
+    fn compat_id_feature(id: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0x14, 0x00, 0x03, 0x00];
+        bytes.extend_from_slice(id);
+        bytes.resize(20, 0);
+        bytes
+    }
+
+    fn configuration_subset(configuration_value: u8, total_length: u16) -> [u8; 8] {
+        let [low, high] = total_length.to_le_bytes();
+        [0x08, 0x00, 0x01, 0x00, configuration_value, 0x00, low, high]
+    }
+
+    fn function_subset(first_interface: u8, total_length: u16) -> [u8; 8] {
+        let [low, high] = total_length.to_le_bytes();
+        [0x08, 0x00, 0x02, 0x00, first_interface, 0x00, low, high]
+    }
+
+    // a Windows 8.1 set header with wTotalLength covering `descriptors`
+    fn descriptor_set(descriptors: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = vec![0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x06, 0x00, 0x00];
+        for descriptor in descriptors {
+            bytes.extend_from_slice(descriptor);
+        }
+        let total_length = bytes.len() as u16;
+        bytes[8..10].copy_from_slice(&total_length.to_le_bytes());
+        bytes
+    }
+
+    fn compatible_id(compatible_id: &str) -> Feature {
+        Feature::CompatibleId { compatible_id: compatible_id.to_owned(), sub_compatible_id: String::new() }
+    }
+
+    #[test]
+    fn nests_subsets() {
+        let bytes = descriptor_set(&[
+            &CCGP,
+            &configuration_subset(1, 8 + 28 + 28),
+            &function_subset(0, 28),
+            &compat_id_feature(b"WINUSB"),
+            &function_subset(2, 28),
+            &compat_id_feature(b"RNDIS"),
+        ]);
+        let set = DescriptorSet::parse(&bytes).unwrap();
+        assert!(set.problems.is_empty(), "{:?}", set.problems);
+        assert_eq!((set.windows_version, set.total_length), (0x0603_0000, 78));
+        assert_eq!(set.features, [Feature::CcgpDevice]);
+        assert_eq!(set.configurations, [ConfigurationSubset {
+            configuration_value: 1,
+            features: vec![],
+            functions: vec![
+                FunctionSubset { first_interface: 0, features: vec![compatible_id("WINUSB")] },
+                FunctionSubset { first_interface: 2, features: vec![compatible_id("RNDIS")] },
+            ],
+        }]);
+    }
+
+    #[test]
+    fn subset_overruns_its_parent() {
+        // the function subset claims 4 bytes past its configuration subset,
+        // so the CCGP descriptor after both still belongs to the device
+        let bytes = descriptor_set(&[
+            &configuration_subset(1, 8 + 28),
+            &function_subset(0, 28 + 4),
+            &compat_id_feature(b"WINUSB"),
+            &CCGP,
+        ]);
+        let set = DescriptorSet::parse(&bytes).unwrap();
+        assert_eq!(set.problems, [Error::new(24, ErrorKind::InvalidValue { field: "wTotalLength", value: 32 })]);
+        assert_eq!(set.configurations[0].functions[0].features, [compatible_id("WINUSB")]);
+        assert_eq!(set.features, [Feature::CcgpDevice]);
+        // and a configuration subset running past the set
+        let bytes = descriptor_set(&[&configuration_subset(1, 8 + 20 + 1), &compat_id_feature(b"WINUSB")]);
+        let set = DescriptorSet::parse(&bytes).unwrap();
+        assert_eq!(set.problems, [Error::new(16, ErrorKind::InvalidValue { field: "wTotalLength", value: 29 })]);
+        assert_eq!(set.configurations[0].features, [compatible_id("WINUSB")]);
+        // a subset shorter than its own header
+        let bytes = descriptor_set(&[&configuration_subset(1, 4), &CCGP]);
+        let set = DescriptorSet::parse(&bytes).unwrap();
+        assert_eq!(set.problems, [Error::new(16, ErrorKind::InvalidValue { field: "wTotalLength", value: 4 })]);
+        assert_eq!(set.features, [Feature::CcgpDevice]);
+    }
+
+    #[test]
+    fn function_subset_outside_configuration() {
+        let bytes = descriptor_set(&[&function_subset(0, 28), &compat_id_feature(b"WINUSB")]);
+        let set = DescriptorSet::parse(&bytes).unwrap();
+        assert_eq!(set.problems, [Error::new(10, ErrorKind::Misplaced { descriptor_type: 0x02 })]);
+        assert!(set.configurations.is_empty());
+        assert_eq!(set.features, [compatible_id("WINUSB")]);
+    }
+
+    #[test]
+    fn total_length_mismatch() {
+        // fewer bytes than announced: walk what is there
+        let bytes = descriptor_set(&[&CCGP, &compat_id_feature(b"WINUSB")]);
+        let set = DescriptorSet::parse(&bytes[..14]).unwrap();
+        assert_eq!(set.problems, [Error::new(8, ErrorKind::CountMismatch { field: "wTotalLength", declared: 34, found: 14 })]);
+        assert_eq!(set.features, [Feature::CcgpDevice]);
+        // more bytes than announced: ignore the rest
+        let mut bytes = descriptor_set(&[&CCGP]);
+        bytes.extend_from_slice(&compat_id_feature(b"WINUSB"));
+        let set = DescriptorSet::parse(&bytes).unwrap();
+        assert_eq!(set.problems, [Error::new(8, ErrorKind::CountMismatch { field: "wTotalLength", declared: 14, found: 34 })]);
+        assert_eq!(set.features, [Feature::CcgpDevice]);
+        // a descriptor cut off by wTotalLength
+        let mut bytes = descriptor_set(&[&compat_id_feature(b"WINUSB")]);
+        bytes[8] = 20;
+        let set = DescriptorSet::parse(&bytes).unwrap();
+        assert_eq!(set.problems, [
+            Error::new(8, ErrorKind::CountMismatch { field: "wTotalLength", declared: 20, found: 30 }),
+            Error::new(10, ErrorKind::InvalidValue { field: "wLength", value: 20 }),
+        ]);
+        assert_eq!(
+            DescriptorSet::parse(&bytes[..6]),
+            Err(Error::new(0, ErrorKind::Truncated { needed: 10, available: 6 })),
+        );
+    }
+
+    // a version 1.00 extended compat ID descriptor with `functions`
+    fn extended_compat_id(count: u8, functions: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut bytes = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x04, 0x00, count, 0, 0, 0, 0, 0, 0, 0];
+        for (first_interface, compatible_id) in functions {
+            let mut function = vec![*first_interface, 0x01];
+            function.extend_from_slice(compatible_id);
+            function.resize(24, 0);
+            bytes.extend_from_slice(&function);
+        }
+        let length = bytes.len() as u32;
+        bytes[..4].copy_from_slice(&length.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn compat_id_functions() {
+        let bytes = extended_compat_id(2, &[(0, b"WINUSB"), (2, b"RNDIS\0\0\x005162001")]);
+        assert_eq!(ExtendedCompatId::parse(&bytes), Ok(ExtendedCompatId {
+            version: Bcd(0x0100),
+            functions: vec![
+                CompatIdFunction { first_interface: 0, compatible_id: "WINUSB".to_owned(), sub_compatible_id: String::new() },
+                CompatIdFunction {
+                    first_interface: 2,
+                    compatible_id: "RNDIS".to_owned(),
+                    sub_compatible_id: "5162001".to_owned(),
+                },
+            ],
+        }));
+    }
+
+    #[test]
+    fn compat_id_lengths() {
+        let bytes = extended_compat_id(2, &[(0, b"WINUSB")]);
+        assert_eq!(
+            ExtendedCompatId::parse(&bytes),
+            Err(Error::new(8, ErrorKind::CountMismatch { field: "bCount", declared: 2, found: 1 })),
+        );
+        let bytes = extended_compat_id(1, &[(0, b"WINUSB")]);
+        assert_eq!(
+            ExtendedCompatId::parse(&bytes[..30]),
+            Err(Error::new(0, ErrorKind::Truncated { needed: 40, available: 30 })),
+        );
+        let mut short = bytes.clone();
+        short[0] = 12;
+        assert_eq!(
+            ExtendedCompatId::parse(&short),
+            Err(Error::new(0, ErrorKind::InvalidValue { field: "dwLength", value: 12 })),
+        );
+        let mut wrong_index = bytes;
+        wrong_index[6] = 0x05;
+        assert_eq!(
+            ExtendedCompatId::parse(&wrong_index),
+            Err(Error::new(6, ErrorKind::InvalidValue { field: "wIndex", value: 5 })),
+        );
+    }
+
+    fn utf16z(text: &str) -> Vec<u8> {
+        text.encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect()
+    }
+
+    // one property section of an extended properties descriptor
+    fn property(data_type: u32, name: &str, data: &[u8]) -> Vec<u8> {
+        let name = utf16z(name);
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&((14 + name.len() + data.len()) as u32).to_le_bytes());
+        bytes.extend_from_slice(&data_type.to_le_bytes());
+        bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&name);
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn extended_properties(count: u16, properties: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x05, 0x00];
+        bytes.extend_from_slice(&count.to_le_bytes());
+        for property in properties {
+            bytes.extend_from_slice(property);
+        }
+        let length = bytes.len() as u32;
+        bytes[..4].copy_from_slice(&length.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn extended_properties_values() {
+        let guid = property(1, "DeviceInterfaceGUID", &utf16z("{88bae032-5a81-49f0-bc3d-a4ff138216d6}"));
+        let idle = property(4, "DeviceIdleEnabled", &[0x01, 0x00, 0x00, 0x00]);
+        let bytes = extended_properties(2, &[&guid, &idle]);
+        let properties = ExtendedProperties::parse(&bytes).unwrap();
+        assert_eq!(properties.version, Bcd(0x0100));
+        assert_eq!(properties.properties, [
+            RegistryProperty {
+                data_type: 1,
+                name: "DeviceInterfaceGUID".to_owned(),
+                value: PropertyValue::String("{88bae032-5a81-49f0-bc3d-a4ff138216d6}".to_owned()),
+            },
+            RegistryProperty {
+                data_type: 4,
+                name: "DeviceIdleEnabled".to_owned(),
+                value: PropertyValue::Dword(1),
+            },
+        ]);
+    }
+
+    #[test]
+    fn extended_properties_lengths() {
+        let idle = property(4, "DeviceIdleEnabled", &[0x01, 0x00, 0x00, 0x00]);
+        assert_eq!(
+            ExtendedProperties::parse(&extended_properties(2, &[&idle])),
+            Err(Error::new(8, ErrorKind::CountMismatch { field: "wCount", declared: 2, found: 1 })),
+        );
+        // dwSize disagreeing with the name and data lengths
+        let mut bad_size = idle.clone();
+        bad_size[0] += 2;
+        bad_size.extend_from_slice(&[0, 0]);
+        let size = bad_size.len() as u32;
+        assert_eq!(
+            ExtendedProperties::parse(&extended_properties(1, &[&bad_size])),
+            Err(Error::new(10, ErrorKind::InvalidValue { field: "dwSize", value: size })),
+        );
+        // dwSize running past dwLength
+        let bytes = extended_properties(1, &[&idle]);
+        let mut overrun = bytes.clone();
+        overrun[10] += 1;
+        assert_eq!(
+            ExtendedProperties::parse(&overrun),
+            Err(Error::new(10, ErrorKind::InvalidValue { field: "dwSize", value: u32::from(overrun[10]) })),
+        );
+        assert_eq!(
+            ExtendedProperties::parse(&bytes[..16]),
+            Err(Error::new(0, ErrorKind::Truncated { needed: bytes.len(), available: 16 })),
+        );
+    }
+}
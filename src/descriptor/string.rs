@@ -0,0 +1,274 @@
+use super::{descriptor_type, u16_at, Error, ErrorKind};
+use alloc::{string::String, vec::Vec};
+
+// String descriptors (USB 2.0 9.6.7): index 0 lists the LANGIDs the device
+// supports, every other index holds UTF-16LE text in one of them.
+
+pub const ENGLISH_UNITED_STATES: u16 = 0x0409;
+
+// the LANGIDs of string descriptor 0
+pub fn parse_language_ids(bytes: &[u8]) -> Result<Vec<u16>, Error> {
+    let units = code_units(bytes)?;
+    if units.is_empty() {
+        return Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+    }
+    Ok(units)
+}
+
+// the text of a string descriptor other than 0. Malformed descriptors are
+// errors rather than lossy decodes so they can be reported.
+pub fn parse_string(bytes: &[u8]) -> Result<String, Error> {
+    let units = code_units(bytes)?;
+    let mut text = String::with_capacity(units.len());
+    let mut position = 0; // in code units
+    for unit in char::decode_utf16(units.iter().copied()) {
+        match unit {
+            Ok(c) => {
+                text.push(c);
+                position += c.len_utf16();
+            },
+            Err(err) => return Err(Error::new(2 + 2 * position, ErrorKind::InvalidValue {
+                field: "unpaired surrogate",
+                value: err.unpaired_surrogate().into(),
+            })),
+        }
+    }
+    Ok(text)
+}
+
+// the UTF-16 code units after the header, checking that bLength is even and
+// agrees with the number of bytes returned
+fn code_units(bytes: &[u8]) -> Result<Vec<u16>, Error> {
+    if bytes.len() < 2 {
+        return Err(Error::new(0, ErrorKind::Truncated { needed: 2, available: bytes.len() }))
+    }
+    if bytes[1] != descriptor_type::STRING {
+        return Err(Error::new(1, ErrorKind::UnexpectedType { expected: descriptor_type::STRING, found: bytes[1] }))
+    }
+    let length = usize::from(bytes[0]);
+    if length < 2 || length % 2 != 0 {
+        return Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+    }
+    if bytes.len() != length {
+        return Err(Error::new(0, ErrorKind::CountMismatch { field: "bLength", declared: length as u32, found: bytes.len() }))
+    }
+    Ok((2..length).step_by(2).map(|offset| u16_at(bytes, offset)).collect())
+}
+
+// the language `language_id` identifies, from the USB-IF LANGID table
+pub fn language_name(language_id: u16) -> Option<&'static str> {
+    Some(match language_id {
+        0x0436 => "Afrikaans",
+        0x041C => "Albanian",
+        0x0401 => "Arabic (Saudi Arabia)",
+        0x0801 => "Arabic (Iraq)",
+        0x0C01 => "Arabic (Egypt)",
+        0x1001 => "Arabic (Libya)",
+        0x1401 => "Arabic (Algeria)",
+        0x1801 => "Arabic (Morocco)",
+        0x1C01 => "Arabic (Tunisia)",
+        0x2001 => "Arabic (Oman)",
+        0x2401 => "Arabic (Yemen)",
+        0x2801 => "Arabic (Syria)",
+        0x2C01 => "Arabic (Jordan)",
+        0x3001 => "Arabic (Lebanon)",
+        0x3401 => "Arabic (Kuwait)",
+        0x3801 => "Arabic (U.A.E.)",
+        0x3C01 => "Arabic (Bahrain)",
+        0x4001 => "Arabic (Qatar)",
+        0x042B => "Armenian",
+        0x044D => "Assamese",
+        0x042C => "Azeri (Latin)",
+        0x082C => "Azeri (Cyrillic)",
+        0x042D => "Basque",
+        0x0423 => "Belarussian",
+        0x0445 => "Bengali",
+        0x0402 => "Bulgarian",
+        0x0455 => "Burmese",
+        0x0403 => "Catalan",
+        0x0404 => "Chinese (Taiwan)",
+        0x0804 => "Chinese (PRC)",
+        0x0C04 => "Chinese (Hong Kong SAR, PRC)",
+        0x1004 => "Chinese (Singapore)",
+        0x1404 => "Chinese (Macau SAR)",
+        0x041A => "Croatian",
+        0x0405 => "Czech",
+        0x0406 => "Danish",
+        0x0413 => "Dutch (Netherlands)",
+        0x0813 => "Dutch (Belgium)",
+        ENGLISH_UNITED_STATES => "English (United States)",
+        0x0809 => "English (United Kingdom)",
+        0x0C09 => "English (Australian)",
+        0x1009 => "English (Canadian)",
+        0x1409 => "English (New Zealand)",
+        0x1809 => "English (Ireland)",
+        0x1C09 => "English (South Africa)",
+        0x2009 => "English (Jamaica)",
+        0x2409 => "English (Caribbean)",
+        0x2809 => "English (Belize)",
+        0x2C09 => "English (Trinidad)",
+        0x3009 => "English (Zimbabwe)",
+        0x3409 => "English (Philippines)",
+        0x0425 => "Estonian",
+        0x0438 => "Faeroese",
+        0x0429 => "Farsi",
+        0x040B => "Finnish",
+        0x040C => "French (Standard)",
+        0x080C => "French (Belgian)",
+        0x0C0C => "French (Canadian)",
+        0x100C => "French (Switzerland)",
+        0x140C => "French (Luxembourg)",
+        0x180C => "French (Monaco)",
+        0x0437 => "Georgian",
+        0x0407 => "German (Standard)",
+        0x0807 => "German (Switzerland)",
+        0x0C07 => "German (Austria)",
+        0x1007 => "German (Luxembourg)",
+        0x1407 => "German (Liechtenstein)",
+        0x0408 => "Greek",
+        0x0447 => "Gujarati",
+        0x040D => "Hebrew",
+        0x0439 => "Hindi",
+        0x040E => "Hungarian",
+        0x040F => "Icelandic",
+        0x0421 => "Indonesian",
+        0x0410 => "Italian (Standard)",
+        0x0810 => "Italian (Switzerland)",
+        0x0411 => "Japanese",
+        0x044B => "Kannada",
+        0x0860 => "Kashmiri (India)",
+        0x043F => "Kazakh",
+        0x0457 => "Konkani",
+        0x0412 => "Korean",
+        0x0812 => "Korean (Johab)",
+        0x0426 => "Latvian",
+        0x0427 => "Lithuanian",
+        0x0827 => "Lithuanian (Classic)",
+        0x042F => "Macedonian",
+        0x043E => "Malay (Malaysian)",
+        0x083E => "Malay (Brunei Darussalam)",
+        0x044C => "Malayalam",
+        0x0458 => "Manipuri",
+        0x044E => "Marathi",
+        0x0861 => "Nepali (India)",
+        0x0414 => "Norwegian (Bokmal)",
+        0x0814 => "Norwegian (Nynorsk)",
+        0x0448 => "Oriya",
+        0x0415 => "Polish",
+        0x0416 => "Portuguese (Brazil)",
+        0x0816 => "Portuguese (Standard)",
+        0x0446 => "Punjabi",
+        0x0418 => "Romanian",
+        0x0419 => "Russian",
+        0x044F => "Sanskrit",
+        0x0C1A => "Serbian (Cyrillic)",
+        0x081A => "Serbian (Latin)",
+        0x0459 => "Sindhi",
+        0x041B => "Slovak",
+        0x0424 => "Slovenian",
+        0x040A => "Spanish (Traditional Sort)",
+        0x080A => "Spanish (Mexican)",
+        0x0C0A => "Spanish (Modern Sort)",
+        0x100A => "Spanish (Guatemala)",
+        0x140A => "Spanish (Costa Rica)",
+        0x180A => "Spanish (Panama)",
+        0x1C0A => "Spanish (Dominican Republic)",
+        0x200A => "Spanish (Venezuela)",
+        0x240A => "Spanish (Colombia)",
+        0x280A => "Spanish (Peru)",
+        0x2C0A => "Spanish (Argentina)",
+        0x300A => "Spanish (Ecuador)",
+        0x340A => "Spanish (Chile)",
+        0x380A => "Spanish (Uruguay)",
+        0x3C0A => "Spanish (Paraguay)",
+        0x400A => "Spanish (Bolivia)",
+        0x440A => "Spanish (El Salvador)",
+        0x480A => "Spanish (Honduras)",
+        0x4C0A => "Spanish (Nicaragua)",
+        0x500A => "Spanish (Puerto Rico)",
+        0x0430 => "Sutu",
+        0x0441 => "Swahili (Kenya)",
+        0x041D => "Swedish",
+        0x081D => "Swedish (Finland)",
+        0x0449 => "Tamil",
+        0x0444 => "Tatar (Tatarstan)",
+        0x044A => "Telugu",
+        0x041E => "Thai",
+        0x041F => "Turkish",
+        0x0422 => "Ukrainian",
+        0x0420 => "Urdu (Pakistan)",
+        0x0820 => "Urdu (India)",
+        0x0443 => "Uzbek (Latin)",
+        0x0843 => "Uzbek (Cyrillic)",
+        0x042A => "Vietnamese",
+        0x04FF => "HID (Usage Data Descriptor)",
+        0xF0FF => "HID (Vendor Defined 1)",
+        0xF4FF => "HID (Vendor Defined 2)",
+        0xF8FF => "HID (Vendor Defined 3)",
+        0xFCFF => "HID (Vendor Defined 4)",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn language_ids() {
+        assert_eq!(parse_language_ids(&[0x06, 0x03, 0x09, 0x04, 0x07, 0x04]), Ok(vec![0x0409, 0x0407]));
+        // a device has to support at least one language
+        assert_eq!(parse_language_ids(&[0x02, 0x03]), Err(Error::new(0, ErrorKind::InvalidLength { length: 2 })));
+    }
+
+    #[test]
+    fn surrogate_pair() {
+        // "a" and U+1F600 as D83D DE00
+        let bytes = [0x08, 0x03, b'a', 0x00, 0x3D, 0xD8, 0x00, 0xDE];
+        assert_eq!(parse_string(&bytes).as_deref(), Ok("a\u{1F600}"));
+    }
+
+    #[test]
+    fn unpaired_surrogate() {
+        // a high surrogate followed by a letter
+        assert_eq!(
+            parse_string(&[0x06, 0x03, 0x3D, 0xD8, b'a', 0x00]),
+            Err(Error::new(2, ErrorKind::InvalidValue { field: "unpaired surrogate", value: 0xD83D })),
+        );
+        // a low surrogate after a complete pair is found at its own offset
+        assert_eq!(
+            parse_string(&[0x08, 0x03, 0x3D, 0xD8, 0x00, 0xDE, 0x00, 0xDC]),
+            Err(Error::new(6, ErrorKind::InvalidValue { field: "unpaired surrogate", value: 0xDC00 })),
+        );
+    }
+
+    #[test]
+    fn odd_length() {
+        assert_eq!(
+            parse_string(&[0x05, 0x03, b'a', 0x00, b'b']),
+            Err(Error::new(0, ErrorKind::InvalidLength { length: 5 })),
+        );
+    }
+
+    #[test]
+    fn length_mismatch() {
+        assert_eq!(
+            parse_string(&[0x06, 0x03, b'a', 0x00]),
+            Err(Error::new(0, ErrorKind::CountMismatch { field: "bLength", declared: 6, found: 4 })),
+        );
+        assert_eq!(
+            parse_string(&[0x04, 0x03, b'a', 0x00, b'b', 0x00]),
+            Err(Error::new(0, ErrorKind::CountMismatch { field: "bLength", declared: 4, found: 6 })),
+        );
+        assert_eq!(parse_string(&[0x04]), Err(Error::new(0, ErrorKind::Truncated { needed: 2, available: 1 })));
+    }
+
+    #[test]
+    fn wrong_type() {
+        assert_eq!(
+            parse_string(&[0x04, 0x02, b'a', 0x00]),
+            Err(Error::new(1, ErrorKind::UnexpectedType { expected: descriptor_type::STRING, found: 0x02 })),
+        );
+    }
+}
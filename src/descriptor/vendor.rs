@@ -0,0 +1,180 @@
+use super::{configuration::RawDescriptor, Error};
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use core::fmt;
+
+// Decoders for vendor specific descriptors, registered by the crate user and
+// looked up for every descriptor the standard parsers leave raw, e.g.
+//
+// let mut registry = VendorRegistry::default();
+// registry.register(Selector::Product { vendor_id: 0x1234, product_id: 0x5678 }, 0x41, "Calibration",
+//     |bytes: &[u8]| match bytes.get(2) {
+//         Some(gain) => Ok(vec![Field::new("Gain", gain)]),
+//         None => Err(Error::new(0, ErrorKind::Truncated { needed: 3, available: bytes.len() })),
+//     });
+//
+// A decoder gets the descriptor as the device sent it, however short. It must
+// check the length and return an error rather than index past the end; the
+// descriptor is then dumped as hex with the error as a problem.
+
+// which devices or interfaces a decoder applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selector {
+    Product { vendor_id: u16, product_id: u16 },
+    Class(u8), // interface class, or device class outside any interface
+}
+
+// one decoded value, rendered as "name: value"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub value: String,
+}
+
+// turns the raw bytes of a descriptor, header included, into named fields
+pub trait VendorDecoder: Send + Sync {
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<Field>, Error>;
+}
+
+impl<F> VendorDecoder for F
+where
+    F: Fn(&[u8]) -> Result<Vec<Field>, Error> + Send + Sync,
+{
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<Field>, Error> {
+        self(bytes)
+    }
+}
+
+#[derive(Default)]
+pub struct VendorRegistry {
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    selector: Selector,
+    descriptor_type: u8,
+    name: String,
+    decoder: Box<dyn VendorDecoder>,
+}
+
+// the decoders that apply to one device
+#[derive(Clone, Copy)]
+pub struct DeviceDecoders<'a> {
+    registry: &'a VendorRegistry,
+    vendor_id: u16,
+    product_id: u16,
+    device_class: u8,
+}
+
+// a descriptor a registered decoder took
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded<'a> {
+    pub name: &'a str,
+    pub fields: Result<Vec<Field>, Error>,
+}
+
+impl Field {
+    pub fn new(name: impl Into<String>, value: impl fmt::Display) -> Self {
+        Field { name: name.into(), value: format!("{}", value) }
+    }
+}
+
+impl VendorRegistry {
+    // decode descriptors of `descriptor_type` on whatever `selector` matches
+    // with `decoder`, showing them as `name`
+    pub fn register(
+        &mut self,
+        selector: Selector,
+        descriptor_type: u8,
+        name: impl Into<String>,
+        decoder: impl VendorDecoder + 'static,
+    ) -> &mut Self {
+        self.entries.push(Entry { selector, descriptor_type, name: name.into(), decoder: Box::new(decoder) });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn for_device(&self, vendor_id: u16, product_id: u16, device_class: u8) -> DeviceDecoders<'_> {
+        DeviceDecoders { registry: self, vendor_id, product_id, device_class }
+    }
+}
+
+impl fmt::Debug for VendorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|entry| (entry.selector, entry.descriptor_type, &entry.name)))
+            .finish()
+    }
+}
+
+impl<'a> DeviceDecoders<'a> {
+    // decode `raw`, found on an interface of `interface_class` or outside any
+    // interface if `None`. A decoder registered for the product wins over one
+    // registered for the class.
+    pub fn decode(&self, interface_class: Option<u8>, raw: &RawDescriptor) -> Option<Decoded<'a>> {
+        let class = interface_class.unwrap_or(self.device_class);
+        let rank = |selector: &Selector| match *selector {
+            Selector::Product { vendor_id, product_id } if (vendor_id, product_id) == (self.vendor_id, self.product_id) => Some(0),
+            Selector::Class(selector_class) if selector_class == class => Some(1),
+            _ => None,
+        };
+        let entry = self.registry.entries.iter()
+            .filter(|entry| entry.descriptor_type == raw.descriptor_type())
+            .filter_map(|entry| Some((rank(&entry.selector)?, entry)))
+            .min_by_key(|(rank, _)| *rank)?
+            .1;
+        Some(Decoded {
+            name: &entry.name,
+            fields: entry.decoder.decode(&raw.bytes).map_err(|err| err.at(raw.offset)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::descriptor::ErrorKind;
+    use alloc::vec;
+
+    fn gain(bytes: &[u8]) -> Result<Vec<Field>, Error> {
+        match bytes.get(2) {
+            Some(gain) => Ok(vec![Field::new("Gain", gain)]),
+            None => Err(Error::new(0, ErrorKind::Truncated { needed: 3, available: bytes.len() })),
+        }
+    }
+
+    fn raw(offset: usize, bytes: &[u8]) -> RawDescriptor {
+        RawDescriptor { offset, bytes: bytes.to_vec() }
+    }
+
+    #[test]
+    fn product_beats_class() {
+        let mut registry = VendorRegistry::default();
+        registry
+            .register(Selector::Class(0xFF), 0x41, "Class", gain)
+            .register(Selector::Product { vendor_id: 0x1234, product_id: 0x5678 }, 0x41, "Product", gain);
+        let decoders = registry.for_device(0x1234, 0x5678, 0x00);
+        assert_eq!(decoders.decode(Some(0xFF), &raw(18, &[0x03, 0x41, 0x07])).map(|decoded| decoded.name), Some("Product"));
+        // another product of the same class
+        let decoders = registry.for_device(0x1234, 0x0001, 0x00);
+        assert_eq!(decoders.decode(Some(0xFF), &raw(18, &[0x03, 0x41, 0x07])).map(|decoded| decoded.name), Some("Class"));
+        // outside any interface the device class counts
+        assert!(decoders.decode(None, &raw(9, &[0x03, 0x41, 0x07])).is_none());
+    }
+
+    #[test]
+    fn unmatched_descriptors() {
+        let mut registry = VendorRegistry::default();
+        registry.register(Selector::Product { vendor_id: 0x1234, product_id: 0x5678 }, 0x41, "Calibration", gain);
+        let decoders = registry.for_device(0x1234, 0x5678, 0x00);
+        assert!(decoders.decode(Some(0xFF), &raw(18, &[0x03, 0x42, 0x07])).is_none());
+        assert!(registry.for_device(0x1234, 0x0001, 0x00).decode(Some(0xFF), &raw(18, &[0x03, 0x41, 0x07])).is_none());
+        // a short descriptor is the decoder's error, placed in the configuration
+        assert_eq!(decoders.decode(Some(0xFF), &raw(18, &[0x02, 0x41])), Some(Decoded {
+            name: "Calibration",
+            fields: Err(Error::new(18, ErrorKind::Truncated { needed: 3, available: 2 })),
+        }));
+    }
+}
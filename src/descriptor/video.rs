@@ -0,0 +1,630 @@
+use super::{
+    cdc::CS_INTERFACE,
+    configuration::{AlternateSetting, RawDescriptor},
+    u16_at, u32_at, Bcd, Error, ErrorKind, Uuid,
+};
+use alloc::vec::Vec;
+
+// USB Video Class 1.1 and 1.5. The VideoControl interface describes terminals
+// and units like audio does; each VideoStreaming interface lists its formats,
+// every format followed by its frames.
+
+pub const SUBCLASS_VIDEO_CONTROL: u8 = 0x01;
+pub const SUBCLASS_VIDEO_STREAMING: u8 = 0x02;
+
+// terminal type of a camera sensor input terminal
+pub const ITT_CAMERA: u16 = 0x0201;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VideoControlDescriptor {
+    Header { uvc_version: Bcd, clock_frequency: u32, streaming_interfaces: Vec<u8> },
+    InputTerminal { id: u8, terminal_type: u16, associated_terminal: u8 },
+    CameraTerminal(CameraTerminal),
+    OutputTerminal { id: u8, terminal_type: u16, associated_terminal: u8, source: u8 },
+    SelectorUnit { id: u8, sources: Vec<u8> },
+    ProcessingUnit { id: u8, source: u8, max_multiplier: u16, controls: u32 },
+    ExtensionUnit(ExtensionUnit),
+    EncodingUnit { id: u8, source: u8, controls: u32 },
+    Unknown(RawDescriptor),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CameraTerminal {
+    pub id: u8,
+    pub associated_terminal: u8,
+    pub objective_focal_length_min: u16,
+    pub objective_focal_length_max: u16,
+    pub ocular_focal_length: u16,
+    pub controls: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionUnit {
+    pub id: u8,
+    pub guid: Uuid, // vendor defined, identifies the extension to drivers
+    pub num_controls: u8,
+    pub sources: Vec<u8>,
+    pub controls: Vec<u8>,
+}
+
+// the class specific descriptors of a VideoStreaming interface, frames and
+// still image and color matching descriptors grouped under their format
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VideoStreaming {
+    pub header: Option<StreamingHeader>,
+    pub formats: Vec<Format>,
+    pub problems: Vec<Error>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamingHeader {
+    pub offset: usize,
+    pub input: bool, // false for output (display) streams
+    pub num_formats: u8,
+    pub endpoint_address: u8,
+    pub terminal_link: u8,
+    pub still_capture_method: u8, // input headers only
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Format {
+    pub offset: usize,
+    pub index: u8,
+    pub kind: FormatKind,
+    pub guid: Option<Uuid>, // uncompressed, frame based and stream based formats
+    pub bits_per_pixel: Option<u8>,
+    pub default_frame_index: u8,
+    pub num_frames: u8,
+    pub frames: Vec<Frame>,
+    pub still_image: Option<StillImageFrame>,
+    pub color_matching: Option<ColorMatching>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatKind {
+    Uncompressed,
+    Mjpeg,
+    MpegTs,
+    Dv,
+    FrameBased,
+    StreamBased,
+    H264,
+    Vp8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub offset: usize,
+    pub index: u8,
+    pub width: u16,
+    pub height: u16,
+    pub default_interval: u32, // 100 ns units
+    pub intervals: FrameIntervals,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameIntervals {
+    Discrete(Vec<u32>),
+    Continuous { min: u32, max: u32, step: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StillImageFrame {
+    pub endpoint_address: u8, // 0 unless still capture method 3 uses a dedicated bulk endpoint
+    pub sizes: Vec<(u16, u16)>,
+    pub compressions: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorMatching {
+    pub color_primaries: u8,
+    pub transfer_characteristics: u8,
+    pub matrix_coefficients: u8,
+}
+
+fn need(bytes: &[u8], length: usize) -> Result<(), Error> {
+    if bytes.len() < length {
+        Err(Error::new(0, ErrorKind::InvalidLength { length: bytes[0] }))
+    } else {
+        Ok(())
+    }
+}
+
+// a count at `at` followed by that many IDs
+fn pins(bytes: &[u8], at: usize) -> Result<Vec<u8>, Error> {
+    need(bytes, at + 1)?;
+    let count = usize::from(bytes[at]);
+    need(bytes, at + 1 + count)?;
+    Ok(bytes[at + 1..at + 1 + count].to_vec())
+}
+
+// bmControls of `size` bytes at `at`, little endian
+fn controls(bytes: &[u8], at: usize, size: usize) -> Result<u32, Error> {
+    need(bytes, at + size)?;
+    Ok(bytes[at..at + size.min(4)].iter().rev().fold(0, |value, &byte| value << 8 | u32::from(byte)))
+}
+
+impl VideoControlDescriptor {
+    pub fn parse(raw: &RawDescriptor) -> Result<Self, Error> {
+        let bytes = &raw.bytes;
+        need(bytes, 3)?;
+        if bytes[1] != CS_INTERFACE {
+            return Err(Error::new(1, ErrorKind::UnexpectedType { expected: CS_INTERFACE, found: bytes[1] }))
+        }
+        Ok(match bytes[2] {
+            0x01 => {
+                need(bytes, 12)?;
+                VideoControlDescriptor::Header {
+                    uvc_version: Bcd(u16_at(bytes, 3)),
+                    clock_frequency: u32_at(bytes, 7),
+                    streaming_interfaces: pins(bytes, 11)?,
+                }
+            },
+            0x02 => {
+                need(bytes, 8)?;
+                let terminal_type = u16_at(bytes, 4);
+                if terminal_type != ITT_CAMERA {
+                    return Ok(VideoControlDescriptor::InputTerminal {
+                        id: bytes[3],
+                        terminal_type,
+                        associated_terminal: bytes[6],
+                    })
+                }
+                need(bytes, 15)?;
+                VideoControlDescriptor::CameraTerminal(CameraTerminal {
+                    id: bytes[3],
+                    associated_terminal: bytes[6],
+                    objective_focal_length_min: u16_at(bytes, 8),
+                    objective_focal_length_max: u16_at(bytes, 10),
+                    ocular_focal_length: u16_at(bytes, 12),
+                    controls: controls(bytes, 15, usize::from(bytes[14]))?,
+                })
+            },
+            0x03 => {
+                need(bytes, 9)?;
+                VideoControlDescriptor::OutputTerminal {
+                    id: bytes[3],
+                    terminal_type: u16_at(bytes, 4),
+                    associated_terminal: bytes[6],
+                    source: bytes[7],
+                }
+            },
+            0x04 => VideoControlDescriptor::SelectorUnit { id: bytes[3], sources: pins(bytes, 4)? },
+            0x05 => {
+                need(bytes, 8)?;
+                VideoControlDescriptor::ProcessingUnit {
+                    id: bytes[3],
+                    source: bytes[4],
+                    max_multiplier: u16_at(bytes, 5),
+                    controls: controls(bytes, 8, usize::from(bytes[7]))?,
+                }
+            },
+            0x06 => {
+                need(bytes, 22)?;
+                let sources = pins(bytes, 21)?;
+                let size_at = 22 + sources.len();
+                need(bytes, size_at + 1)?;
+                let size = usize::from(bytes[size_at]);
+                need(bytes, size_at + 1 + size)?;
+                VideoControlDescriptor::ExtensionUnit(ExtensionUnit {
+                    id: bytes[3],
+                    guid: Uuid::from_slice(&bytes[4..20]),
+                    num_controls: bytes[20],
+                    sources,
+                    controls: bytes[size_at + 1..size_at + 1 + size].to_vec(),
+                })
+            },
+            0x07 => {
+                // bSourceID, iEncoding, bControlSize, bmControls, bmControlsRuntime
+                need(bytes, 10)?;
+                VideoControlDescriptor::EncodingUnit {
+                    id: bytes[3],
+                    source: bytes[4],
+                    controls: controls(bytes, 7, usize::from(bytes[6]))?,
+                }
+            },
+            _ => VideoControlDescriptor::Unknown(raw.clone()),
+        })
+    }
+}
+
+// the class specific descriptors of a VideoControl interface, in order
+pub fn control_descriptors(setting: &AlternateSetting) -> impl Iterator<Item = Result<VideoControlDescriptor, Error>> + '_ {
+    setting.extra.iter()
+        .filter(|raw| raw.descriptor_type() == CS_INTERFACE)
+        .map(|raw| VideoControlDescriptor::parse(raw).map_err(|err| err.at(raw.offset)))
+}
+
+impl FormatKind {
+    // the frame descriptor subtype belonging to this format, if it has frames
+    pub fn frame_subtype(self) -> Option<u8> {
+        match self {
+            FormatKind::Uncompressed => Some(0x05),
+            FormatKind::Mjpeg => Some(0x07),
+            FormatKind::FrameBased => Some(0x11),
+            FormatKind::H264 => Some(0x14),
+            FormatKind::Vp8 => Some(0x17),
+            FormatKind::MpegTs | FormatKind::Dv | FormatKind::StreamBased => None,
+        }
+    }
+}
+
+impl Frame {
+    fn parse(subtype: u8, bytes: &[u8]) -> Result<Self, Error> {
+        // (wWidth, dwDefaultFrameInterval, bFrameIntervalType, first interval)
+        let (width_at, default_at, type_at, intervals_at) = match subtype {
+            0x14 => (4, 39, 43, 44),
+            0x17 => (4, 26, 30, 31),
+            0x11 => (5, 17, 21, 26),
+            _ => (5, 21, 25, 26),
+        };
+        need(bytes, intervals_at)?;
+        let interval_type = usize::from(bytes[type_at]);
+        let interval = |index: usize| u32_at(bytes, intervals_at + 4 * index);
+        // H.264 and VP8 frames always list discrete intervals
+        let intervals = if interval_type == 0 && !matches!(subtype, 0x14 | 0x17) {
+            need(bytes, intervals_at + 12)?;
+            FrameIntervals::Continuous { min: interval(0), max: interval(1), step: interval(2) }
+        } else {
+            if bytes.len() < intervals_at + 4 * interval_type {
+                return Err(Error::new(type_at, ErrorKind::CountMismatch {
+                    field: "bFrameIntervalType",
+                    declared: interval_type as u32,
+                    found: (bytes.len() - intervals_at) / 4,
+                }))
+            }
+            FrameIntervals::Discrete((0..interval_type).map(interval).collect())
+        };
+        Ok(Frame {
+            offset: 0,
+            index: bytes[3],
+            width: u16_at(bytes, width_at),
+            height: u16_at(bytes, width_at + 2),
+            default_interval: u32_at(bytes, default_at),
+            intervals,
+        })
+    }
+}
+
+impl Format {
+    fn parse(subtype: u8, bytes: &[u8]) -> Result<Self, Error> {
+        let (kind, min_length) = match subtype {
+            0x04 => (FormatKind::Uncompressed, 27),
+            0x06 => (FormatKind::Mjpeg, 11),
+            0x0A => (FormatKind::MpegTs, 7),
+            0x0C => (FormatKind::Dv, 9),
+            0x10 => (FormatKind::FrameBased, 28),
+            0x12 => (FormatKind::StreamBased, 24),
+            0x13 => (FormatKind::H264, 52),
+            _ => (FormatKind::Vp8, 14),
+        };
+        need(bytes, min_length)?;
+        let (guid, bits_per_pixel, num_frames, default_frame_index) = match kind {
+            FormatKind::Uncompressed | FormatKind::FrameBased => {
+                (Some(Uuid::from_slice(&bytes[5..21])), Some(bytes[21]), bytes[4], bytes[22])
+            },
+            FormatKind::StreamBased => (Some(Uuid::from_slice(&bytes[4..20])), None, 0, 0),
+            FormatKind::Mjpeg => (None, None, bytes[4], bytes[6]),
+            FormatKind::H264 | FormatKind::Vp8 => (None, None, bytes[4], bytes[5]),
+            FormatKind::MpegTs | FormatKind::Dv => (None, None, 0, 0),
+        };
+        Ok(Format {
+            offset: 0,
+            index: bytes[3],
+            kind,
+            guid,
+            bits_per_pixel,
+            default_frame_index,
+            num_frames,
+            frames: Vec::new(),
+            still_image: None,
+            color_matching: None,
+        })
+    }
+
+    // the four character code of a GUID following the Windows media subtype pattern
+    pub fn fourcc(&self) -> Option<&str> {
+        const SUFFIX: [u8; 12] = [0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71];
+        let guid = self.guid.as_ref()?;
+        if guid.0[4..] != SUFFIX || !guid.0[..4].iter().all(|byte| byte.is_ascii_graphic() || *byte == b' ') {
+            return None;
+        }
+        core::str::from_utf8(&guid.0[..4]).ok()
+    }
+
+    pub fn frame(&self, index: u8) -> Option<&Frame> {
+        self.frames.iter().find(|frame| frame.index == index)
+    }
+}
+
+impl StillImageFrame {
+    fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        need(bytes, 5)?;
+        let count = usize::from(bytes[4]);
+        let compressions_at = 5 + 4 * count;
+        need(bytes, compressions_at + 1)?;
+        Ok(StillImageFrame {
+            endpoint_address: bytes[3],
+            sizes: (0..count).map(|index| (u16_at(bytes, 5 + 4 * index), u16_at(bytes, 7 + 4 * index))).collect(),
+            compressions: pins(bytes, compressions_at)?,
+        })
+    }
+}
+
+impl VideoStreaming {
+    pub fn parse(setting: &AlternateSetting) -> Self {
+        let mut streaming = VideoStreaming::default();
+        for raw in setting.extra.iter().filter(|raw| raw.descriptor_type() == CS_INTERFACE) {
+            if let Err(err) = streaming.parse_descriptor(raw) {
+                streaming.problems.push(err.at(raw.offset));
+            }
+        }
+        for format in &streaming.formats {
+            if format.frames.len() != usize::from(format.num_frames) {
+                streaming.problems.push(Error::new(format.offset + 4, ErrorKind::CountMismatch {
+                    field: "bNumFrameDescriptors",
+                    declared: format.num_frames.into(),
+                    found: format.frames.len(),
+                }));
+            }
+            if format.kind.frame_subtype().is_some() && format.frame(format.default_frame_index).is_none() {
+                streaming.problems.push(Error::new(format.offset, ErrorKind::InvalidValue {
+                    field: "bDefaultFrameIndex",
+                    value: format.default_frame_index.into(),
+                }));
+            }
+        }
+        if let Some(header) = streaming.header {
+            if usize::from(header.num_formats) != streaming.formats.len() {
+                streaming.problems.push(Error::new(header.offset + 3, ErrorKind::CountMismatch {
+                    field: "bNumFormats",
+                    declared: header.num_formats.into(),
+                    found: streaming.formats.len(),
+                }));
+            }
+        }
+        streaming.problems.sort_by_key(|problem| problem.offset);
+        streaming
+    }
+
+    fn parse_descriptor(&mut self, raw: &RawDescriptor) -> Result<(), Error> {
+        let bytes = &raw.bytes;
+        need(bytes, 3)?;
+        let misplaced = || Error::new(0, ErrorKind::Misplaced { descriptor_type: bytes[2] });
+        match bytes[2] {
+            0x01 => {
+                need(bytes, 13)?;
+                self.header = Some(StreamingHeader {
+                    offset: raw.offset,
+                    input: true,
+                    num_formats: bytes[3],
+                    endpoint_address: bytes[6],
+                    terminal_link: bytes[8],
+                    still_capture_method: bytes[9],
+                });
+            },
+            0x02 => {
+                need(bytes, 8)?;
+                self.header = Some(StreamingHeader {
+                    offset: raw.offset,
+                    input: false,
+                    num_formats: bytes[3],
+                    endpoint_address: bytes[6],
+                    terminal_link: bytes[7],
+                    still_capture_method: 0,
+                });
+            },
+            0x03 => {
+                let still_image = StillImageFrame::parse(bytes)?;
+                self.formats.last_mut().ok_or_else(misplaced)?.still_image = Some(still_image);
+            },
+            0x04 | 0x06 | 0x0A | 0x0C | 0x10 | 0x12 | 0x13 | 0x16 => {
+                let format = Format::parse(bytes[2], bytes)?;
+                self.formats.push(Format { offset: raw.offset, ..format });
+            },
+            0x05 | 0x07 | 0x11 | 0x14 | 0x17 => {
+                let format = self.formats.last_mut().ok_or_else(misplaced)?;
+                if format.kind.frame_subtype() != Some(bytes[2]) {
+                    return Err(misplaced())
+                }
+                let frame = Frame::parse(bytes[2], bytes)?;
+                format.frames.push(Frame { offset: raw.offset, ..frame });
+            },
+            0x0D => {
+                need(bytes, 6)?;
+                self.formats.last_mut().ok_or_else(misplaced)?.color_matching = Some(ColorMatching {
+                    color_primaries: bytes[3],
+                    transfer_characteristics: bytes[4],
+                    matrix_coefficients: bytes[5],
+                });
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+}
+
+// frames per second of a frame interval in 100 ns units, in thousandths
+pub fn millihertz(interval: u32) -> u64 {
+    match interval {
+        0 => 0,
+        interval => (10_000_000_000 + u64::from(interval) / 2) / u64::from(interval),
+    }
+}
+
+// input and output terminal types of the video class specification
+pub fn terminal_type_name(terminal_type: u16) -> Option<&'static str> {
+    Some(match terminal_type {
+        0x0100 => "USB Vendor Specific",
+        0x0101 => "USB Streaming",
+        0x0200 => "Input Vendor Specific",
+        0x0201 => "Camera Sensor",
+        0x0202 => "Media Transport Input",
+        0x0300 => "Output Vendor Specific",
+        0x0301 => "Display",
+        0x0302 => "Media Transport Output",
+        0x0400 => "External Vendor Specific",
+        0x0401 => "Composite Connector",
+        0x0402 => "S-Video Connector",
+        0x0403 => "Component Connector",
+        _ => return None,
+    })
+}
+
+// camera terminal bmControls in bit order
+pub const CAMERA_CONTROLS: [&str; 22] = [
+    "Scanning Mode", "Auto-Exposure Mode", "Auto-Exposure Priority", "Exposure Time (Absolute)",
+    "Exposure Time (Relative)", "Focus (Absolute)", "Focus (Relative)", "Iris (Absolute)", "Iris (Relative)",
+    "Zoom (Absolute)", "Zoom (Relative)", "PanTilt (Absolute)", "PanTilt (Relative)", "Roll (Absolute)",
+    "Roll (Relative)", "Reserved", "Reserved", "Focus Auto", "Privacy", "Focus Simple", "Window",
+    "Region of Interest",
+];
+
+// processing unit bmControls in bit order
+pub const PROCESSING_CONTROLS: [&str; 19] = [
+    "Brightness", "Contrast", "Hue", "Saturation", "Sharpness", "Gamma", "White Balance Temperature",
+    "White Balance Component", "Backlight Compensation", "Gain", "Power Line Frequency", "Hue Auto",
+    "White Balance Temperature Auto", "White Balance Component Auto", "Digital Multiplier",
+    "Digital Multiplier Limit", "Analog Video Standard", "Analog Video Lock Status", "Contrast Auto",
+];
+
+// bColorPrimaries of the color matching descriptor
+pub fn color_primaries_name(value: u8) -> Option<&'static str> {
+    Some(match value {
+        0x01 => "BT.709/sRGB",
+        0x02 => "BT.470-2 (M)",
+        0x03 => "BT.470-2 (B, G)",
+        0x04 => "SMPTE 170M",
+        0x05 => "SMPTE 240M",
+        _ => return None,
+    })
+}
+
+// bTransferCharacteristics of the color matching descriptor
+pub fn transfer_characteristics_name(value: u8) -> Option<&'static str> {
+    Some(match value {
+        0x01 => "BT.709",
+        0x02 => "BT.470-2 (M)",
+        0x03 => "BT.470-2 (B, G)",
+        0x04 => "SMPTE 170M",
+        0x05 => "SMPTE 240M",
+        0x06 => "Linear",
+        0x07 => "sRGB",
+        _ => return None,
+    })
+}
+
+// bMatrixCoefficients of the color matching descriptor
+pub fn matrix_coefficients_name(value: u8) -> Option<&'static str> {
+    Some(match value {
+        0x01 => "BT.709",
+        0x02 => "FCC",
+        0x03 => "BT.470-2 (B, G)",
+        0x04 => "SMPTE 170M (BT.601)",
+        0x05 => "SMPTE 240M",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::descriptor::configuration::Configuration;
+    use alloc::vec;
+
+    fn control(bytes: &[u8]) -> Result<VideoControlDescriptor, Error> {
+        VideoControlDescriptor::parse(&RawDescriptor { offset: 0, bytes: bytes.to_vec() })
+    }
+
+    // the class specific descriptors of a VideoStreaming interface 1
+    fn streaming(descriptors: &[&[u8]]) -> VideoStreaming {
+        let mut bytes = vec![0x09, 0x02, 0x00, 0x00, 0x01, 0x01, 0x00, 0x80, 0xFA];
+        bytes.extend_from_slice(&[0x09, 0x04, 0x01, 0x00, 0x00, 0x0E, 0x02, 0x00, 0x00]);
+        for descriptor in descriptors {
+            bytes.extend_from_slice(descriptor);
+        }
+        let total_length = bytes.len() as u16;
+        bytes[2..4].copy_from_slice(&total_length.to_le_bytes());
+        let configuration = Configuration::parse(&bytes).unwrap();
+        VideoStreaming::parse(&configuration.interfaces[0].alternate_settings[0])
+    }
+
+    // one format, endpoint 0x81, linked to terminal 3
+    const INPUT_HEADER: [u8; 14] = [0x0E, 0x24, 0x01, 0x01, 0x00, 0x00, 0x81, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00];
+
+    #[test]
+    fn encoding_unit_control_size() {
+        // bControlSize 3 follows iEncoding 5
+        let bytes = [0x0D, 0x24, 0x07, 0x04, 0x03, 0x05, 0x03, 0xFF, 0x1F, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(control(&bytes), Ok(VideoControlDescriptor::EncodingUnit { id: 4, source: 3, controls: 0x1FFF }));
+        assert_eq!(control(&bytes[..9]), Err(Error::new(0, ErrorKind::InvalidLength { length: 0x0D })));
+    }
+
+    #[test]
+    fn camera_terminal_and_processing_unit() {
+        let camera = [0x12, 0x24, 0x02, 0x01, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x0A, 0x00, 0x00];
+        assert_eq!(control(&camera), Ok(VideoControlDescriptor::CameraTerminal(CameraTerminal {
+            id: 1,
+            associated_terminal: 0,
+            objective_focal_length_min: 0,
+            objective_focal_length_max: 0,
+            ocular_focal_length: 0,
+            controls: 0x0A,
+        })));
+        let processing = [0x0D, 0x24, 0x05, 0x02, 0x01, 0x00, 0x40, 0x03, 0x7F, 0x15, 0x00, 0x00, 0x00];
+        assert_eq!(control(&processing), Ok(VideoControlDescriptor::ProcessingUnit {
+            id: 2,
+            source: 1,
+            max_multiplier: 0x4000,
+            controls: 0x157F,
+        }));
+    }
+
+    #[test]
+    fn vp8_frames() {
+        let format = [0x0E, 0x24, 0x16, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        // 1920x1080, 30 fps by default, 30 and 15 fps
+        let mut frame = vec![0x27, 0x24, 0x17, 0x01, 0x80, 0x07, 0x38, 0x04];
+        frame.extend_from_slice(&[0x00; 18]);
+        frame.extend_from_slice(&[0x15, 0x16, 0x05, 0x00, 0x02, 0x15, 0x16, 0x05, 0x00, 0x2A, 0x2C, 0x0A, 0x00]);
+        let streaming = streaming(&[&INPUT_HEADER, &format, &frame]);
+        assert!(streaming.problems.is_empty(), "{:?}", streaming.problems);
+        assert_eq!(streaming.formats[0].kind, FormatKind::Vp8);
+        assert_eq!(streaming.formats[0].frames, [Frame {
+            offset: 46,
+            index: 1,
+            width: 1920,
+            height: 1080,
+            default_interval: 333_333,
+            intervals: FrameIntervals::Discrete(vec![333_333, 666_666]),
+        }]);
+    }
+
+    #[test]
+    fn uncompressed_frames() {
+        // YUY2, two frames declared
+        let mut format = vec![0x1B, 0x24, 0x04, 0x01, 0x02, b'Y', b'U', b'Y', b'2'];
+        format.extend_from_slice(&[0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71]);
+        format.extend_from_slice(&[0x10, 0x01, 0x00, 0x00, 0x00, 0x00]);
+        // 640x480, 30 to 5 fps in steps of 100 ns
+        let mut continuous = vec![0x26, 0x24, 0x05, 0x01, 0x00, 0x80, 0x02, 0xE0, 0x01];
+        continuous.extend_from_slice(&[0x00; 12]);
+        continuous.extend_from_slice(&[0x15, 0x16, 0x05, 0x00, 0x00]);
+        continuous.extend_from_slice(&[0x15, 0x16, 0x05, 0x00, 0x80, 0x96, 0x98, 0x00, 0x01, 0x00, 0x00, 0x00]);
+        // three intervals declared, two present
+        let mut short = vec![0x22, 0x24, 0x05, 0x02, 0x00, 0x40, 0x01, 0xF0, 0x00];
+        short.extend_from_slice(&[0x00; 12]);
+        short.extend_from_slice(&[0x15, 0x16, 0x05, 0x00, 0x03]);
+        short.extend_from_slice(&[0x15, 0x16, 0x05, 0x00, 0x2A, 0x2C, 0x0A, 0x00]);
+        let streaming = streaming(&[&INPUT_HEADER, &format, &continuous, &short]);
+        let format = &streaming.formats[0];
+        assert_eq!((format.fourcc(), format.bits_per_pixel), (Some("YUY2"), Some(16)));
+        assert_eq!(format.frames.len(), 1);
+        assert_eq!((format.frames[0].width, format.frames[0].height), (640, 480));
+        assert_eq!(format.frames[0].intervals, FrameIntervals::Continuous { min: 333_333, max: 10_000_000, step: 1 });
+        assert_eq!(streaming.problems, [
+            Error::new(36, ErrorKind::CountMismatch { field: "bNumFrameDescriptors", declared: 2, found: 1 }),
+            Error::new(97 + 25, ErrorKind::CountMismatch { field: "bFrameIntervalType", declared: 3, found: 2 }),
+        ]);
+    }
+}
@@ -0,0 +1,80 @@
+use super::{bos::Platform, u16_at, Bcd, Error, ErrorKind, Uuid};
+use alloc::string::String;
+use core::fmt;
+
+// WebUSB (WICG WebUSB API 3.2 and 4.3): a BOS platform capability naming the
+// vendor request that returns URL descriptors, and the landing page URL index.
+
+// 3408B638-09A9-47A0-8BFD-A0768815B665
+pub const PLATFORM_UUID: Uuid = Uuid([
+    0x38, 0xB6, 0x08, 0x34, 0xA9, 0x09, 0xA0, 0x47, 0x8B, 0xFD, 0xA0, 0x76, 0x88, 0x15, 0xB6, 0x65,
+]);
+
+// wIndex of the vendor request returning a URL descriptor
+pub const GET_URL: u16 = 0x02;
+
+// bDescriptorType of a URL descriptor
+pub const URL_DESCRIPTOR_TYPE: u8 = 0x03;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WebUsb {
+    pub version: Bcd,
+    pub vendor_code: u8, // bRequest of GET_URL
+    pub landing_page: u8, // URL index, 0 if there is none
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlDescriptor {
+    pub scheme: u8,
+    pub url: String, // without the scheme prefix
+}
+
+impl WebUsb {
+    // the capability carried by `platform` if it is the WebUSB one
+    pub fn parse(platform: &Platform) -> Option<Result<Self, Error>> {
+        if platform.uuid != PLATFORM_UUID {
+            return None;
+        }
+        // bcdVersion, bVendorCode and iLandingPage follow the 20 bytes of header and UUID
+        if platform.data.len() < 4 {
+            return Some(Err(Error::new(0, ErrorKind::InvalidLength { length: (20 + platform.data.len()) as u8 })))
+        }
+        Some(Ok(WebUsb {
+            version: Bcd(u16_at(&platform.data, 0)),
+            vendor_code: platform.data[2],
+            landing_page: platform.data[3],
+        }))
+    }
+}
+
+impl UrlDescriptor {
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = super::header(bytes, URL_DESCRIPTOR_TYPE, 3)?;
+        let scheme = bytes[2];
+        if scheme_prefix(scheme).is_none() {
+            return Err(Error::new(2, ErrorKind::InvalidValue { field: "bScheme", value: scheme.into() }))
+        }
+        let url = core::str::from_utf8(&bytes[3..]).map_err(|err| {
+            let offset = 3 + err.valid_up_to();
+            Error::new(offset, ErrorKind::InvalidValue { field: "URL byte", value: bytes[offset].into() })
+        })?;
+        Ok(UrlDescriptor { scheme, url: String::from(url) })
+    }
+}
+
+// the full URL, scheme included
+impl fmt::Display for UrlDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", scheme_prefix(self.scheme).unwrap_or(""), self.url)
+    }
+}
+
+// bScheme of a URL descriptor; 255 means the URL carries its own scheme
+pub fn scheme_prefix(scheme: u8) -> Option<&'static str> {
+    Some(match scheme {
+        0 => "http://",
+        1 => "https://",
+        255 => "",
+        _ => return None,
+    })
+}
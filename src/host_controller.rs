@@ -9,6 +9,7 @@ use core::fmt;
 use std::io;
 use std::ffi::OsString;
 use std::os::windows::ffi::OsStringExt;
+use crate::usb_subsystem::DriverKey;
 
 pub struct HostController {
     path: OsString, // path
@@ -40,6 +41,11 @@ impl HostController {
         Ok(Self { path, h_hc_dev })
     }
 
+    // device interface path of this host controller
+    pub fn path(&self) -> &OsString {
+        &self.path
+    }
+
     // get usb hcd driver key name
     pub fn driver_key(&self) -> io::Result<DriverKey> {
         #![allow(non_snake_case, non_camel_case_types, non_upper_case_globals)]
@@ -69,8 +75,7 @@ impl HostController {
         let new_len = unsafe { driver_key_name.assume_init_ref() }.ActualLength as usize / size_of::<u16>();
         // println!("new-len = {}", new_len); // 47
         // println!("returned-bytes = {}", unsafe { *required_length_bytes.as_ptr() }); // 6
-        let mut buf: Vec<u16> = Vec::new();
-        buf.reserve(new_len);
+        let mut buf: Vec<u16> = Vec::with_capacity(new_len);
         let success = unsafe { 
             DeviceIoControl(
                 self.h_hc_dev,
@@ -93,9 +98,58 @@ impl HostController {
                 new_len - size_of::<ULONG>(),
             )
         };
-        Ok(DriverKey {
-            name: OsString::from_wide(string)
-        })
+        Ok(DriverKey::from(OsString::from_wide(string)))
+    }
+
+    // get symbolic name of the root hub attached to this host controller
+    pub fn root_hub_name(&self) -> io::Result<OsString> {
+        #![allow(non_snake_case, non_camel_case_types, non_upper_case_globals)]
+        use winapi::um::ioapiset::DeviceIoControl;
+        const IOCTL_USB_GET_ROOT_HUB_NAME: DWORD = 0x220408;
+        winapi::STRUCT!{struct USB_ROOT_HUB_NAME {
+            ActualLength: ULONG,
+            RootHubName: [WCHAR; 1],
+        }}
+        let mut required_length_bytes = MaybeUninit::uninit();
+        let mut root_hub_name = MaybeUninit::<USB_ROOT_HUB_NAME>::uninit();
+        let success = unsafe {
+            DeviceIoControl(
+                self.h_hc_dev,
+                IOCTL_USB_GET_ROOT_HUB_NAME,
+                core::ptr::null_mut(), // input buffer
+                0, // input buffer
+                root_hub_name.as_mut_ptr() as LPVOID,
+                size_of::<USB_ROOT_HUB_NAME>() as DWORD,
+                required_length_bytes.as_mut_ptr(),
+                core::ptr::null_mut()
+            )
+        };
+        if success == FALSE {
+            return Err(io::Error::last_os_error())
+        }
+        // ActualLength counts the ULONG header and the trailing \0
+        let new_len = unsafe { root_hub_name.assume_init_ref() }.ActualLength as usize / size_of::<u16>();
+        let mut buf: Vec<u16> = Vec::with_capacity(new_len);
+        let success = unsafe {
+            DeviceIoControl(
+                self.h_hc_dev,
+                IOCTL_USB_GET_ROOT_HUB_NAME,
+                core::ptr::null_mut(), // input buffer
+                0, // input buffer
+                buf.as_mut_ptr() as LPVOID,
+                (buf.capacity() * size_of::<u16>()) as u32,
+                required_length_bytes.as_mut_ptr(),
+                core::ptr::null_mut()
+            )
+        };
+        if success == FALSE {
+            return Err(io::Error::last_os_error())
+        }
+        unsafe { buf.set_len(new_len) };
+        let offset = size_of::<ULONG>() / size_of::<u16>();
+        let name = &buf[offset..];
+        let tail = name.iter().position(|&c| c == 0).unwrap_or(name.len()); // remove \0
+        Ok(OsString::from_wide(&name[..tail]))
     }
 }
 
@@ -227,17 +281,3 @@ impl Drop for HostControllers {
         unsafe { SetupDiDestroyDeviceInfoList(self.device_info_set) };
     }
 }
-
-pub struct DriverKey {
-    name: OsString,
-}
-
-impl DriverKey {
-    
-}
-
-impl fmt::Debug for DriverKey {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", &self.name.to_string_lossy())
-    }
-}
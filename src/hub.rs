@@ -0,0 +1,231 @@
+use winapi::{
+    shared::minwindef::*,
+    um::{fileapi::*, winnt::*, handleapi::*, ioapiset::DeviceIoControl},
+};
+use core::mem::size_of;
+use core::fmt;
+use std::io;
+use std::ffi::{OsStr, OsString};
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+
+const IOCTL_USB_GET_NODE_INFORMATION: DWORD = 0x220408;
+const IOCTL_USB_GET_DESCRIPTOR_FROM_NODE_CONNECTION: DWORD = 0x220410;
+const IOCTL_USB_GET_NODE_CONNECTION_NAME: DWORD = 0x220414;
+const IOCTL_USB_GET_NODE_CONNECTION_DRIVERKEY_NAME: DWORD = 0x220420;
+const IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX: DWORD = 0x220448;
+const IOCTL_USB_GET_HUB_INFORMATION_EX: DWORD = 0x220454;
+
+// USB_HUB_DESCRIPTOR with its 64 byte bRemoveAndPowerMask, the largest hub descriptor Windows returns
+const HUB_DESCRIPTOR_SIZE: usize = 71;
+
+// USB_DESCRIPTOR_REQUEST: ConnectionIndex followed by the setup packet
+const DESCRIPTOR_REQUEST_HEADER: usize = size_of::<ULONG>() + 8;
+
+// USB_NODE_CONNECTION_INFORMATION_EX up to its PipeList, which the 30 USB_PIPE_INFO
+// (endpoint descriptor and ScheduleOffset) after it leave room for
+const CONNECTION_INFORMATION_SIZE: usize = 35;
+const PIPE_INFO_SIZE: usize = 7 + size_of::<ULONG>();
+
+// USB_NODE_CONNECTION_NAME and USB_NODE_CONNECTION_DRIVERKEY_NAME: ConnectionIndex
+// and ActualLength ahead of the name
+const CONNECTION_NAME_HEADER: usize = 2 * size_of::<ULONG>();
+
+// what is attached to one downstream port
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionInformation {
+    pub connection_status: ULONG, // USB_CONNECTION_STATUS, 0 if nothing is connected
+    pub speed: u8, // USB_DEVICE_SPEED
+    pub device_is_hub: bool,
+}
+
+pub struct Hub {
+    name: OsString, // symbolic name
+    h_hub: HANDLE, // hub handle
+}
+
+impl Hub {
+    // open a hub by its symbolic name, e.g. the root hub name of a host controller
+    pub fn open(name: &OsStr) -> io::Result<Self> {
+        let mut path: Vec<u16> = OsStr::new(r"\\.\").encode_wide()
+            .chain(name.encode_wide())
+            .collect();
+        path.push(0);
+        let h_hub = unsafe {
+            CreateFileW(
+                path.as_ptr(),
+                GENERIC_WRITE,
+                FILE_SHARE_WRITE,
+                core::ptr::null_mut(),
+                OPEN_EXISTING,
+                0,
+                core::ptr::null_mut(),
+            )
+        };
+        if h_hub == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error())
+        }
+        Ok(Self { name: name.to_owned(), h_hub })
+    }
+
+    // read a descriptor from the device connected to `port` of this hub
+    pub fn descriptor(
+        &self,
+        port: u8,
+        descriptor_type: u8,
+        index: u8,
+        language_id: u16,
+    ) -> io::Result<Vec<u8>> {
+        let data = self.descriptor_request(port, descriptor_type, index, language_id, u8::MAX as u16)?;
+        match descriptor_type {
+            // configuration, other speed configuration and BOS carry wTotalLength
+            0x02 | 0x07 | 0x0F if data.len() >= 4 => {
+                let total_length = u16::from_le_bytes([data[2], data[3]]);
+                if usize::from(total_length) > data.len() {
+                    return self.descriptor_request(port, descriptor_type, index, language_id, total_length)
+                }
+                Ok(data)
+            },
+            _ => Ok(data),
+        }
+    }
+
+    // the hub descriptor of this hub itself; SuperSpeed hubs answer with their
+    // SuperSpeed hub descriptor
+    pub fn hub_descriptor(&self) -> io::Result<Vec<u8>> {
+        // USB_HUB_INFORMATION_EX (Windows 8 on): HubType, HighestPortNumber, descriptor
+        match self.ioctl(IOCTL_USB_GET_HUB_INFORMATION_EX, size_of::<ULONG>() + 2 + HUB_DESCRIPTOR_SIZE) {
+            Ok(buf) => Ok(embedded_descriptor(buf, size_of::<ULONG>() + 2)),
+            // USB_NODE_INFORMATION: NodeType, USB 2.0 hub descriptor, HubIsBusPowered
+            Err(_) => self.ioctl(IOCTL_USB_GET_NODE_INFORMATION, size_of::<ULONG>() + HUB_DESCRIPTOR_SIZE + 1)
+                .map(|buf| embedded_descriptor(buf, size_of::<ULONG>())),
+        }
+    }
+
+    // number of downstream ports, from the hub descriptor USB_NODE_INFORMATION carries
+    pub fn port_count(&self) -> io::Result<u8> {
+        let buf = self.ioctl(IOCTL_USB_GET_NODE_INFORMATION, size_of::<ULONG>() + HUB_DESCRIPTOR_SIZE + 1)?;
+        buf.get(size_of::<ULONG>() + 2) // bNumberOfPorts
+            .copied()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "truncated node information"))
+    }
+
+    pub fn connection_information(&self, port: u8) -> io::Result<ConnectionInformation> {
+        let buf = self.connection_ioctl(
+            IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX,
+            port,
+            CONNECTION_INFORMATION_SIZE + 30 * PIPE_INFO_SIZE,
+        )?;
+        if buf.len() < CONNECTION_INFORMATION_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated connection information"))
+        }
+        // ConnectionIndex and the 18 byte device descriptor come first
+        Ok(ConnectionInformation {
+            speed: buf[23],
+            device_is_hub: buf[24] != 0,
+            connection_status: ULONG::from_le_bytes([buf[31], buf[32], buf[33], buf[34]]),
+        })
+    }
+
+    // symbolic name of the hub connected to `port`
+    pub fn connection_name(&self, port: u8) -> io::Result<OsString> {
+        self.connection_string(IOCTL_USB_GET_NODE_CONNECTION_NAME, port)
+    }
+
+    // driver key name of the device connected to `port`
+    pub fn connection_driver_key_name(&self, port: u8) -> io::Result<OsString> {
+        self.connection_string(IOCTL_USB_GET_NODE_CONNECTION_DRIVERKEY_NAME, port)
+    }
+
+    // a name IOCTL: the first call only learns ActualLength, which counts the
+    // header and the trailing \0
+    fn connection_string(&self, code: DWORD, port: u8) -> io::Result<OsString> {
+        let buf = self.connection_ioctl(code, port, CONNECTION_NAME_HEADER + size_of::<u16>())?;
+        if buf.len() < CONNECTION_NAME_HEADER {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated connection name"))
+        }
+        let actual_length = ULONG::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]) as usize;
+        let buf = self.connection_ioctl(code, port, actual_length.max(CONNECTION_NAME_HEADER))?;
+        let name: Vec<u16> = buf[CONNECTION_NAME_HEADER.min(buf.len())..]
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+        Ok(OsString::from_wide(&name))
+    }
+
+    // an IOCTL whose input is the ConnectionIndex of `port`
+    fn connection_ioctl(&self, code: DWORD, port: u8, length: usize) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0u8; length.max(size_of::<ULONG>())];
+        buf[0..4].copy_from_slice(&ULONG::from(port).to_le_bytes());
+        self.ioctl_buffer(code, buf)
+    }
+
+    // an IOCTL on the hub handle returning up to `length` bytes
+    fn ioctl(&self, code: DWORD, length: usize) -> io::Result<Vec<u8>> {
+        self.ioctl_buffer(code, vec![0u8; length])
+    }
+
+    // an IOCTL taking its input from `buf` and answering in place
+    fn ioctl_buffer(&self, code: DWORD, mut buf: Vec<u8>) -> io::Result<Vec<u8>> {
+        let mut returned_bytes: DWORD = 0;
+        let success = unsafe {
+            DeviceIoControl(
+                self.h_hub,
+                code,
+                buf.as_mut_ptr() as LPVOID,
+                buf.len() as DWORD,
+                buf.as_mut_ptr() as LPVOID,
+                buf.len() as DWORD,
+                &mut returned_bytes,
+                core::ptr::null_mut()
+            )
+        };
+        if success == FALSE {
+            return Err(io::Error::last_os_error())
+        }
+        buf.truncate(returned_bytes as usize);
+        Ok(buf)
+    }
+
+    fn descriptor_request(
+        &self,
+        port: u8,
+        descriptor_type: u8,
+        index: u8,
+        language_id: u16,
+        length: u16,
+    ) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0u8; DESCRIPTOR_REQUEST_HEADER + usize::from(length)];
+        buf[0..4].copy_from_slice(&ULONG::from(port).to_le_bytes()); // ConnectionIndex
+        // bmRequest: device to host, standard, to the device or, for a HID report
+        // descriptor, to the interface `language_id` then holds
+        buf[4] = if descriptor_type == 0x22 { 0x81 } else { 0x80 };
+        buf[5] = 0x06; // bRequest: GET_DESCRIPTOR
+        buf[6..8].copy_from_slice(&(u16::from(descriptor_type) << 8 | u16::from(index)).to_le_bytes());
+        buf[8..10].copy_from_slice(&language_id.to_le_bytes());
+        buf[10..12].copy_from_slice(&length.to_le_bytes());
+        let mut buf = self.ioctl_buffer(IOCTL_USB_GET_DESCRIPTOR_FROM_NODE_CONNECTION, buf)?;
+        buf.drain(..DESCRIPTOR_REQUEST_HEADER.min(buf.len()));
+        Ok(buf)
+    }
+}
+
+// the descriptor starting at `offset` of an IOCTL answer, cut to its bLength
+fn embedded_descriptor(mut buf: Vec<u8>, offset: usize) -> Vec<u8> {
+    buf.drain(..offset.min(buf.len()));
+    let length = buf.first().map_or(0, |&length| usize::from(length));
+    buf.truncate(length);
+    buf
+}
+
+impl fmt::Debug for Hub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &self.name.to_string_lossy())
+    }
+}
+
+impl Drop for Hub {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.h_hub) };
+    }
+}
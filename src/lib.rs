@@ -0,0 +1,15 @@
+extern crate alloc;
+
+#[cfg(windows)]
+mod get_device_property;
+#[cfg(windows)]
+mod host_controller;
+#[cfg(windows)]
+mod hub;
+#[cfg(windows)]
+mod winusb;
+pub mod descriptor;
+pub mod render;
+pub mod tree;
+pub mod usb_path;
+pub mod usb_subsystem;
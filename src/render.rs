@@ -0,0 +1,1488 @@
+// Text rendering of parsed descriptors. Every line starts with `prefix`, which
+// carries the tree drawing of the caller; nested items indent by two spaces.
+use crate::descriptor::{
+    audio::{self, AudioControl, AudioStreaming, AudioVersion, EntityKind, SampleRates},
+    bos::{Bos, DeviceCapability},
+    cdc::{self, FunctionalDescriptor},
+    class_name,
+    configuration::{AlternateSetting, Configuration, Endpoint, InterfacePower, Otg, RawDescriptor, TransferType},
+    function_name,
+    device::{DebugDescriptor, DeviceDescriptor, DeviceQualifier},
+    dfu::{self, DfuFunctional, DfuMode},
+    hid::{
+        collection_type_name,
+        report_type,
+        usage_tables::{is_vendor_defined, usage_name, usage_page_name},
+        HidDescriptor,
+        ItemKind,
+        ReportDescriptor,
+    },
+    hub::{HubDescriptor, PortControl},
+    mass_storage::{self, Pipe, Transport, UasPipes},
+    ms_os::{self, DescriptorSet, DescriptorSetInfo, ExtendedCompatId, ExtendedProperties, Feature, PropertyValue, RegistryProperty},
+    string::language_name,
+    vendor::DeviceDecoders,
+    video::{self, FormatKind, FrameIntervals, VideoControlDescriptor, VideoStreaming},
+    webusb::{self, WebUsb},
+    Error,
+};
+use std::io::{self, Write};
+
+pub fn device_descriptor(
+    out: &mut dyn Write,
+    prefix: &str,
+    device_descriptor: &DeviceDescriptor,
+) -> io::Result<()> {
+    writeln!(
+        out, "{}ID: {:04x}:{:04x}, Revision: {}",
+        prefix, device_descriptor.vendor_id, device_descriptor.product_id, device_descriptor.device_version
+    )?;
+    writeln!(out, "{}USB Version: {}", prefix, device_descriptor.usb_version)?;
+    writeln!(
+        out, "{}Class: {}, SubClass: {:#04x}, Protocol: {:#04x}",
+        prefix, class(device_descriptor.device_class),
+        device_descriptor.device_subclass, device_descriptor.device_protocol
+    )?;
+    writeln!(out, "{}Max Packet Size 0: {}", prefix, device_descriptor.max_packet_size0_bytes())?;
+    writeln!(out, "{}Configurations: {}", prefix, device_descriptor.num_configurations)
+}
+
+pub fn debug_descriptor(out: &mut dyn Write, prefix: &str, debug: &DebugDescriptor) -> io::Result<()> {
+    writeln!(
+        out, "{}Debug: IN Endpoint {:#04x}, OUT Endpoint {:#04x}",
+        prefix, debug.debug_in_endpoint, debug.debug_out_endpoint
+    )
+}
+
+pub fn otg_descriptor(out: &mut dyn Write, prefix: &str, otg: &Otg) -> io::Result<()> {
+    write!(out, "{}OTG: ", prefix)?;
+    if let Some(version) = otg.otg_version {
+        write!(out, "Version: {}, ", version)?;
+    }
+    let protocols: Vec<_> = [(otg.srp(), "SRP"), (otg.hnp(), "HNP"), (otg.adp(), "ADP")].iter()
+        .filter(|(supported, _)| *supported)
+        .map(|(_, name)| *name)
+        .collect();
+    writeln!(
+        out, "Attributes: {:#04x} ({})",
+        otg.attributes, if protocols.is_empty() { "none".to_owned() } else { protocols.join(", ") }
+    )
+}
+
+pub fn interface_power(out: &mut dyn Write, prefix: &str, power: &InterfacePower) -> io::Result<()> {
+    let states: Vec<_> = (0..4).filter(|&state| power.supports(state)).map(|state| format!("D{}", state)).collect();
+    let wakeup: Vec<_> = (1..3).filter(|&state| power.wakeup_from(state)).map(|state| format!("D{}", state)).collect();
+    writeln!(
+        out, "{}Interface Power: States: {}, Wakeup from: {}, Self Power Consumed in D0: {}, Power Summary ID: {}",
+        prefix,
+        if states.is_empty() { "none".to_owned() } else { states.join(", ") },
+        if wakeup.is_empty() { "none".to_owned() } else { wakeup.join(", ") },
+        power.self_power_consumed_d0, power.power_summary_id
+    )?;
+    for (index, state) in (1..4).enumerate() {
+        writeln!(
+            out, "{}  D{}: Bus Power Saved: {}, Self Power Saved: {}, Transition Time to D0: {}",
+            prefix, state, power.bus_power_saved[index], power.self_power_saved[index], power.transition_time[index]
+        )?;
+    }
+    Ok(())
+}
+
+pub fn device_qualifier(out: &mut dyn Write, prefix: &str, qualifier: &DeviceQualifier) -> io::Result<()> {
+    writeln!(
+        out, "{}Device Qualifier: USB Version: {}, Class: {}, SubClass: {:#04x}, Protocol: {:#04x}, \
+            Max Packet Size 0: {}, Configurations: {}",
+        prefix, qualifier.usb_version, class(qualifier.device_class), qualifier.device_subclass,
+        qualifier.device_protocol, qualifier.max_packet_size0, qualifier.num_configurations
+    )
+}
+
+// an other speed configuration next to the configuration the device runs
+// with, as max packet sizes at full and high speed; `high_speed` tells which
+// speed `current` is for, if known
+pub fn other_speed_configuration(
+    out: &mut dyn Write,
+    prefix: &str,
+    current: &Configuration,
+    other: &Configuration,
+    high_speed: Option<bool>,
+) -> io::Result<()> {
+    writeln!(
+        out, "{}Other Speed Configuration {}: {} interface(s), Max Power: {} mA",
+        prefix, other.configuration_value, other.num_interfaces, other.max_power_milliamps(false)
+    )?;
+    let nested = format!("{}  ", prefix);
+    let find = |configuration: &'_ Configuration, setting: &AlternateSetting, address: u8| {
+        configuration.alternate_settings()
+            .filter(|other| (other.interface_number, other.alternate_setting) == (setting.interface_number, setting.alternate_setting))
+            .flat_map(|other| &other.endpoints)
+            .find(|endpoint| endpoint.address == address)
+            .cloned()
+    };
+    // every endpoint of either configuration, current first
+    let mut rows = Vec::new();
+    for setting in current.alternate_settings() {
+        for endpoint in &setting.endpoints {
+            rows.push((setting, Some(endpoint.clone()), find(other, setting, endpoint.address)));
+        }
+    }
+    for setting in other.alternate_settings() {
+        for endpoint in &setting.endpoints {
+            if find(current, setting, endpoint.address).is_none() {
+                rows.push((setting, None, Some(endpoint.clone())));
+            }
+        }
+    }
+    let (full_label, high_label) = match high_speed {
+        Some(_) => ("Full Speed", "High Speed"),
+        None => ("Current Speed", "Other Speed"),
+    };
+    writeln!(out, "{}Max Packet Size: {} / {}", nested, full_label, high_label)?;
+    for (setting, current, other) in rows {
+        // order the columns full speed first
+        let (full, high) = match high_speed {
+            Some(true) => (other, current),
+            _ => (current, other),
+        };
+        let transfer_type = full.as_ref().or(high.as_ref()).map(Endpoint::transfer_type);
+        let address = full.as_ref().or(high.as_ref()).map_or(0, |endpoint| endpoint.address);
+        write!(
+            out, "{}  Interface {} Alt {} Endpoint {:#04x}", nested,
+            setting.interface_number, setting.alternate_setting, address
+        )?;
+        if let Some(transfer_type) = transfer_type {
+            write!(out, " {}", transfer_type)?;
+        }
+        let size = |endpoint: &Option<Endpoint>| match endpoint {
+            Some(endpoint) if endpoint.additional_transactions() > 0 => {
+                format!("{} x{}", endpoint.max_packet_bytes(), endpoint.additional_transactions() + 1)
+            },
+            Some(endpoint) => endpoint.max_packet_bytes().to_string(),
+            None => "-".to_owned(),
+        };
+        write!(out, ": {} / {}", size(&full), size(&high))?;
+        if high_speed.is_some() {
+            let invalid: Vec<_> = [(&full, false, "Full Speed"), (&high, true, "High Speed")].iter()
+                .filter(|(endpoint, high_speed, _)| endpoint.as_ref().is_some_and(|endpoint| !endpoint.valid_max_packet_size(*high_speed)))
+                .map(|(_, _, name)| *name)
+                .collect();
+            if !invalid.is_empty() {
+                write!(out, " (invalid at {})", invalid.join(" and "))?;
+            }
+        }
+        writeln!(out)?;
+    }
+    for err in &other.problems {
+        problem(out, &nested, err)?;
+    }
+    Ok(())
+}
+
+// the LANGIDs of string descriptor 0 and the one strings are read in
+pub fn language_ids(out: &mut dyn Write, prefix: &str, language_ids: &[u16], chosen: u16) -> io::Result<()> {
+    let names: Vec<_> = language_ids.iter().map(|&language_id| language(language_id)).collect();
+    writeln!(out, "{}Languages: {}, Strings in {}", prefix, names.join(", "), language(chosen))
+}
+
+// string `index` together with what refers to it
+pub fn string_descriptor(
+    out: &mut dyn Write,
+    prefix: &str,
+    index: u8,
+    usages: &[String],
+    text: &Result<String, Error>,
+) -> io::Result<()> {
+    match text {
+        Ok(text) => writeln!(out, "{}String {} ({}): {:?}", prefix, index, usages.join(", "), text),
+        Err(err) => {
+            writeln!(out, "{}String {} ({}): malformed", prefix, index, usages.join(", "))?;
+            problem(out, &format!("{}  ", prefix), err)
+        },
+    }
+}
+
+pub fn hub_descriptor(out: &mut dyn Write, prefix: &str, hub: &HubDescriptor) -> io::Result<()> {
+    let control = |control| match control {
+        PortControl::Ganged => "Ganged",
+        PortControl::Individual => "Per Port",
+        PortControl::None => "None",
+    };
+    writeln!(
+        out, "{}{}Hub Descriptor: {} port(s), Power Switching: {}, Over-current Protection: {}, Compound Device: {}",
+        prefix, if hub.superspeed() { "SuperSpeed " } else { "" }, hub.num_ports,
+        control(hub.power_switching()), control(hub.over_current_protection()), yes_no(hub.compound_device())
+    )?;
+    let nested = format!("{}  ", prefix);
+    if let Some(think_time) = hub.tt_think_time() {
+        writeln!(
+            out, "{}TT Think Time: {} FS bit times, Port Indicators: {}",
+            nested, think_time, yes_no(hub.port_indicators())
+        )?;
+    }
+    writeln!(
+        out, "{}Power On to Power Good: {} ms, Hub Controller Current: {} mA",
+        nested, hub.power_on_to_power_good_ms(), hub.controller_current_ma()
+    )?;
+    if let (Some(latency), Some(delay)) = (hub.header_decode_latency, hub.hub_delay) {
+        writeln!(out, "{}Header Decode Latency: {}.{} us, Hub Delay: {} ns", nested, latency / 10, latency % 10, delay)?;
+    }
+    let (removable, fixed): (Vec<_>, Vec<_>) = (1..=hub.num_ports).partition(|&port| hub.removable(port));
+    let ports = |ports: Vec<u8>| match ports.is_empty() {
+        true => "none".to_owned(),
+        false => ports.iter().map(u8::to_string).collect::<Vec<_>>().join(", "),
+    };
+    writeln!(out, "{}Removable Ports: {}, Fixed Ports: {}", nested, ports(removable), ports(fixed))
+}
+
+pub fn configuration(
+    out: &mut dyn Write,
+    prefix: &str,
+    configuration: &Configuration,
+    super_speed: bool,
+    vendor: DeviceDecoders<'_>,
+) -> io::Result<()> {
+    write!(
+        out, "{}Configuration {}: {} interface(s), Max Power: {} mA",
+        prefix, configuration.configuration_value, configuration.num_interfaces,
+        configuration.max_power_milliamps(super_speed)
+    )?;
+    if configuration.self_powered() {
+        write!(out, ", Self Powered")?;
+    }
+    if configuration.remote_wakeup() {
+        write!(out, ", Remote Wakeup")?;
+    }
+    writeln!(out)?;
+    let nested = format!("{}  ", prefix);
+    if let Some(otg) = &configuration.otg {
+        otg_descriptor(out, &nested, otg)?;
+    }
+    for raw in &configuration.extra {
+        unknown_descriptor(out, &nested, raw, None, vendor)?;
+    }
+    for setting in configuration.alternate_settings() {
+        alternate_setting(out, &nested, setting, vendor)?;
+    }
+    // composite devices: one line per function
+    let functions = configuration.functions();
+    if functions.len() > 1 || !configuration.associations.is_empty() {
+        for (index, function) in functions.iter().enumerate() {
+            let (class, subclass, protocol) = match (function.association, function.interfaces[0].alternate_settings.first()) {
+                (Some(iad), _) => (iad.function_class, iad.function_subclass, iad.function_protocol),
+                (None, Some(setting)) => (setting.interface_class, setting.interface_subclass, setting.interface_protocol),
+                (None, None) => continue,
+            };
+            write!(out, "{}Function {}: ", nested, index)?;
+            match function_name(class, subclass, protocol) {
+                Some(name) => write!(out, "{}", name)?,
+                None => write!(out, "Class {:#04x}", class)?,
+            }
+            let numbers: Vec<_> = function.interfaces.iter().map(|interface| interface.number).collect();
+            match numbers[..] {
+                [number] => write!(out, ", Interface {}", number)?,
+                [first, .., last] if usize::from(last - first) + 1 == numbers.len() => {
+                    write!(out, ", Interfaces {}-{}", first, last)?
+                },
+                _ => {
+                    let numbers: Vec<_> = numbers.iter().map(u8::to_string).collect();
+                    write!(out, ", Interfaces {}", numbers.join(", "))?
+                },
+            }
+            match function.association {
+                Some(iad) if iad.function_index != 0 => writeln!(out, ", String {}", iad.function_index)?,
+                Some(_) => writeln!(out)?,
+                None if configuration.associations.is_empty() => writeln!(out)?,
+                None => writeln!(out, ", not covered by any IAD")?,
+            }
+        }
+    }
+    for function in cdc::functions(configuration) {
+        let control = function.control;
+        write!(out, "{}CDC Function: Control Interface {}", nested, control.interface_number)?;
+        if let Some(name) = cdc::subclass_name(control.interface_subclass) {
+            write!(out, " ({})", name)?;
+        }
+        let data: Vec<_> = function.data.iter().map(|interface| interface.number.to_string()).collect();
+        writeln!(out, ", Data Interface(s): {}", if data.is_empty() { "none".to_owned() } else { data.join(", ") })?;
+        for err in &function.problems {
+            problem(out, &format!("{}  ", nested), err)?;
+        }
+    }
+    let audio_control = configuration.alternate_settings()
+        .filter(|setting| setting.interface_class == 0x01 && setting.interface_subclass == audio::SUBCLASS_AUDIO_CONTROL)
+        .find_map(AudioControl::parse);
+    if let Some(control) = audio_control {
+        for setting in audio::streaming_settings(configuration) {
+            let terminal = match AudioStreaming::parse(setting).and_then(|streaming| streaming.terminal_link) {
+                Some(terminal) => terminal,
+                None => continue,
+            };
+            write!(
+                out, "{}Audio Stream: Interface {} Alternate {} -> ",
+                nested, setting.interface_number, setting.alternate_setting
+            )?;
+            match control.entity(terminal) {
+                Some(entity) => write!(out, "{} {}", entity_kind(&entity.kind), entity.id)?,
+                None => write!(out, "missing Terminal {}", terminal)?,
+            }
+            if let Some(clock) = control.clock_of(terminal).and_then(|id| control.entity(id)) {
+                write!(out, ", {} {}", entity_kind(&clock.kind), clock.id)?;
+            }
+            writeln!(out)?;
+        }
+    }
+    for interface in &configuration.interfaces {
+        let transports: Vec<_> = interface.alternate_settings.iter()
+            .filter_map(|setting| Some(format!("{} (Alt {})", transport_name(Transport::of(setting)?), setting.alternate_setting)))
+            .collect();
+        if !transports.is_empty() {
+            writeln!(out, "{}Mass Storage: Interface {}: {}", nested, interface.number, transports.join(", "))?;
+        }
+    }
+    if let Some((setting, mode)) = dfu::dfu_interface(configuration) {
+        let mode = match mode {
+            DfuMode::Runtime => "Runtime",
+            DfuMode::Dfu => "DFU Mode",
+        };
+        writeln!(out, "{}DFU: {}, Interface {}", nested, mode, setting.interface_number)?;
+    }
+    for err in &configuration.problems {
+        problem(out, &nested, err)?;
+    }
+    Ok(())
+}
+
+pub fn alternate_setting(
+    out: &mut dyn Write,
+    prefix: &str,
+    setting: &AlternateSetting,
+    vendor: DeviceDecoders<'_>,
+) -> io::Result<()> {
+    writeln!(
+        out, "{}Interface {} Alt {}: Class: {}, SubClass: {:#04x}, Protocol: {:#04x}, {} endpoint(s)",
+        prefix, setting.interface_number, setting.alternate_setting, class(setting.interface_class),
+        setting.interface_subclass, setting.interface_protocol, setting.num_endpoints
+    )?;
+    let nested = format!("{}  ", prefix);
+    if let Some(power) = &setting.power {
+        interface_power(out, &nested, power)?;
+    }
+    if let Some(transport) = Transport::of(setting) {
+        write!(out, "{}Mass Storage: ", nested)?;
+        match mass_storage::subclass_name(setting.interface_subclass) {
+            Some(name) => write!(out, "{}", name)?,
+            None => write!(out, "Command Set {:#04x}", setting.interface_subclass)?,
+        }
+        writeln!(out, ", Transport: {}", transport_name(transport))?;
+        if transport == Transport::Uas {
+            for err in &UasPipes::parse(setting).problems {
+                problem(out, &format!("{}  ", nested), err)?;
+            }
+        }
+    }
+    // audio and video streaming interfaces are decoded as a whole, their class
+    // specific descriptors refer to each other
+    let decoded = match (setting.interface_class, setting.interface_subclass) {
+        (0x01, audio::SUBCLASS_AUDIO_CONTROL) => match AudioControl::parse(setting) {
+            Some(control) => audio_control(out, &nested, &control).map(|()| true)?,
+            None => false,
+        },
+        (0x01, audio::SUBCLASS_AUDIO_STREAMING) => match AudioStreaming::parse(setting) {
+            Some(streaming) => audio_streaming(out, &nested, &streaming).map(|()| true)?,
+            None => false,
+        },
+        (0x0E, video::SUBCLASS_VIDEO_STREAMING) => {
+            video_streaming(out, &nested, &VideoStreaming::parse(setting))?;
+            true
+        },
+        _ => false,
+    };
+    for raw in &setting.extra {
+        match (setting.interface_class, raw.descriptor_type()) {
+            (0x01, cdc::CS_INTERFACE) | (0x0E, cdc::CS_INTERFACE) if decoded => {},
+            (0x0E, cdc::CS_INTERFACE) if setting.interface_subclass == video::SUBCLASS_VIDEO_CONTROL => {
+                match VideoControlDescriptor::parse(raw) {
+                    Ok(descriptor) => video_control_descriptor(out, &nested, &descriptor)?,
+                    Err(err) => {
+                        raw_descriptor(out, &nested, raw)?;
+                        problem(out, &nested, &err.at(raw.offset))?;
+                    },
+                }
+            },
+            (0x03, report_type::HID) => match HidDescriptor::parse(&raw.bytes) {
+                Ok(hid) => hid_descriptor(out, &nested, &hid)?,
+                Err(err) => {
+                    raw_descriptor(out, &nested, raw)?;
+                    problem(out, &nested, &err.at(raw.offset))?;
+                },
+            },
+            (dfu::CLASS_APPLICATION_SPECIFIC, dfu::FUNCTIONAL) if setting.interface_subclass == dfu::SUBCLASS_DFU => {
+                match DfuFunctional::parse(&raw.bytes) {
+                    Ok(functional) => dfu_functional(out, &nested, &functional)?,
+                    Err(err) => {
+                        raw_descriptor(out, &nested, raw)?;
+                        problem(out, &nested, &err.at(raw.offset))?;
+                    },
+                }
+            },
+            (0x02, cdc::CS_INTERFACE) => match FunctionalDescriptor::parse(raw) {
+                Ok(descriptor) => functional_descriptor(out, &nested, &descriptor)?,
+                Err(err) => {
+                    raw_descriptor(out, &nested, raw)?;
+                    problem(out, &nested, &err.at(raw.offset))?;
+                },
+            },
+            _ => unknown_descriptor(out, &nested, raw, Some(setting.interface_class), vendor)?,
+        }
+    }
+    for ep in &setting.endpoints {
+        endpoint(out, &nested, ep, setting.interface_class, vendor)?;
+    }
+    Ok(())
+}
+
+// `interface_class` tells what the class specific descriptors of the endpoint are
+pub fn endpoint(
+    out: &mut dyn Write,
+    prefix: &str,
+    endpoint: &Endpoint,
+    interface_class: u8,
+    vendor: DeviceDecoders<'_>,
+) -> io::Result<()> {
+    write!(
+        out, "{}Endpoint {:#04x} {} {}, Max Packet Size: {}",
+        prefix, endpoint.address, endpoint.direction(), endpoint.transfer_type(),
+        endpoint.max_packet_bytes()
+    )?;
+    if endpoint.additional_transactions() > 0 {
+        write!(out, " x{}", endpoint.additional_transactions() + 1)?;
+    }
+    write!(out, ", Interval: {}", endpoint.interval)?;
+    let pipe = match interface_class {
+        mass_storage::CLASS_MASS_STORAGE => Pipe::of(endpoint),
+        _ => None,
+    };
+    if let Some(Ok(pipe)) = pipe {
+        write!(out, ", Pipe: {}", pipe_name(pipe))?;
+    }
+    writeln!(out)?;
+    let nested = format!("{}  ", prefix);
+    if let Some(companion) = &endpoint.superspeed_companion {
+        write!(out, "{}SuperSpeed Companion: Burst: {} packet(s)", nested, companion.max_burst + 1)?;
+        if let Some(streams) = endpoint.max_streams() {
+            write!(out, ", Max Streams: {}", streams)?;
+        }
+        match endpoint.transfer_type() {
+            TransferType::Isochronous if companion.ssp_isochronous() => write!(out, ", SSP Isochronous")?,
+            TransferType::Isochronous => write!(out, ", Mult: {}", companion.mult() + 1)?,
+            _ => {},
+        }
+        if matches!(endpoint.transfer_type(), TransferType::Isochronous | TransferType::Interrupt) {
+            write!(out, ", Bytes per Interval: {}", companion.bytes_per_interval)?;
+        }
+        writeln!(out)?;
+    }
+    if let Some(companion) = &endpoint.superspeedplus_isochronous_companion {
+        writeln!(out, "{}SuperSpeedPlus Isochronous Companion: Bytes per Interval: {}", nested, companion.bytes_per_interval)?;
+    }
+    if matches!(endpoint.transfer_type(), TransferType::Isochronous | TransferType::Interrupt) {
+        writeln!(out, "{}Max Bytes per Service Interval: {}", nested, endpoint.max_bytes_per_interval())?;
+    } else if endpoint.max_packets_per_interval() > 1 {
+        writeln!(out, "{}Max Bytes per Burst: {}", nested, endpoint.max_bytes_per_interval())?;
+    }
+    for raw in &endpoint.extra {
+        match (pipe, raw.descriptor_type()) {
+            (Some(Ok(_)), mass_storage::PIPE_USAGE) => {},
+            (Some(Err(err)), mass_storage::PIPE_USAGE) => {
+                raw_descriptor(out, &nested, raw)?;
+                problem(out, &nested, &err)?;
+            },
+            _ => unknown_descriptor(out, &nested, raw, Some(interface_class), vendor)?,
+        }
+    }
+    Ok(())
+}
+
+pub fn bos(out: &mut dyn Write, prefix: &str, bos: &Bos) -> io::Result<()> {
+    writeln!(out, "{}BOS: {} capabilities", prefix, bos.num_device_caps)?;
+    let nested = format!("{}  ", prefix);
+    for capability in &bos.capabilities {
+        device_capability(out, &nested, capability)?;
+    }
+    for err in &bos.problems {
+        problem(out, &nested, err)?;
+    }
+    Ok(())
+}
+
+pub fn device_capability(out: &mut dyn Write, prefix: &str, capability: &DeviceCapability) -> io::Result<()> {
+    let nested = format!("{}  ", prefix);
+    match capability {
+        DeviceCapability::Usb20Extension(extension) => {
+            write!(out, "{}USB 2.0 Extension: LPM: {}", prefix, yes_no(extension.lpm()))?;
+            if extension.besl() {
+                write!(out, ", BESL")?;
+            }
+            if let Some(besl) = extension.baseline_besl() {
+                write!(out, ", Baseline BESL: {}", besl)?;
+            }
+            if let Some(besl) = extension.deep_besl() {
+                write!(out, ", Deep BESL: {}", besl)?;
+            }
+            writeln!(out)
+        },
+        DeviceCapability::SuperSpeed(ss) => {
+            write!(out, "{}SuperSpeed USB: Speeds:", prefix)?;
+            for (bit, speed) in ["Low", "Full", "High", "5 Gbps"].iter().enumerate() {
+                if ss.speeds_supported & (1 << bit) != 0 {
+                    write!(out, " {}", speed)?;
+                }
+            }
+            writeln!(
+                out, ", Functionality From: {}, LTM: {}, U1 Exit: {} us, U2 Exit: {} us",
+                ss.functionality_support, yes_no(ss.ltm()), ss.u1_exit_latency, ss.u2_exit_latency
+            )
+        },
+        DeviceCapability::SuperSpeedPlus(ssp) => {
+            writeln!(
+                out, "{}SuperSpeedPlus USB: {} Sublink Speed ID(s), Min Lanes: {} RX / {} TX",
+                prefix, ssp.sublink_speed_id_count(), ssp.min_rx_lanes(), ssp.min_tx_lanes()
+            )?;
+            for sublink in &ssp.sublink_speeds {
+                writeln!(
+                    out, "{}Sublink Speed {}: {} Mbps, {} {}, {}",
+                    nested, sublink.id(), sublink.bits_per_second() / 1_000_000,
+                    if sublink.asymmetric() { "Asymmetric" } else { "Symmetric" },
+                    if sublink.transmit() { "TX" } else { "RX" },
+                    if sublink.superspeed_plus() { "SuperSpeedPlus" } else { "SuperSpeed" }
+                )?;
+            }
+            Ok(())
+        },
+        DeviceCapability::ContainerId(uuid) => writeln!(out, "{}Container ID: {{{}}}", prefix, uuid),
+        DeviceCapability::Platform(platform) if platform.uuid == ms_os::PLATFORM_UUID => {
+            writeln!(out, "{}Platform: MS OS 2.0 {{{}}}", prefix, platform.uuid)?;
+            match DescriptorSetInfo::parse(platform) {
+                Some(Ok(infos)) => for info in infos {
+                    writeln!(
+                        out, "{}  {}, Descriptor Set: {} bytes, Vendor Code: {:#04x}, Alternate Enumeration Code: {:#04x}",
+                        prefix, windows_version(info.windows_version), info.total_length, info.vendor_code, info.alt_enum_code
+                    )?;
+                },
+                Some(Err(err)) => problem(out, &format!("{}  ", prefix), &err)?,
+                None => {},
+            }
+            Ok(())
+        },
+        DeviceCapability::Platform(platform) if platform.uuid == webusb::PLATFORM_UUID => {
+            write!(out, "{}Platform: WebUSB {{{}}}", prefix, platform.uuid)?;
+            match WebUsb::parse(platform) {
+                Some(Ok(webusb)) => {
+                    write!(out, ", Version: {}, Vendor Code: {:#04x}, Landing Page: ", webusb.version, webusb.vendor_code)?;
+                    match webusb.landing_page {
+                        0 => writeln!(out, "none"),
+                        index => writeln!(out, "URL {}", index),
+                    }
+                },
+                Some(Err(err)) => {
+                    writeln!(out)?;
+                    problem(out, &format!("{}  ", prefix), &err)
+                },
+                None => writeln!(out),
+            }
+        },
+        DeviceCapability::Platform(platform) => {
+            write!(out, "{}Platform: {{{}}}", prefix, platform.uuid)?;
+            for byte in &platform.data {
+                write!(out, " {:02x}", byte)?;
+            }
+            writeln!(out)
+        },
+        DeviceCapability::Billboard(billboard) => {
+            writeln!(
+                out, "{}Billboard {}: {} Alternate Mode(s), Preferred: {}, VCONN Power: {:#06x}",
+                prefix, billboard.version, billboard.alternate_modes.len(),
+                billboard.preferred_alternate_mode, billboard.vconn_power
+            )?;
+            for (index, mode) in billboard.alternate_modes.iter().enumerate() {
+                let state = (billboard.configured[index / 4] >> (2 * (index % 4))) & 0x03;
+                let state = ["Unspecified Error", "Not Attempted", "Unsuccessful", "Configured"][usize::from(state)];
+                writeln!(out, "{}SVID {:#06x} Mode {}: {}", nested, mode.svid, mode.alternate_mode, state)?;
+            }
+            Ok(())
+        },
+        DeviceCapability::PowerDelivery(pd) => writeln!(
+            out, "{}Power Delivery: BC {}, PD {}, Type-C {}, Battery Charging: {}, USB PD: {}, Provider: {}, Consumer: {}",
+            prefix, pd.bc_version, pd.pd_version, pd.type_c_version, yes_no(pd.battery_charging()),
+            yes_no(pd.usb_power_delivery()), yes_no(pd.provider()), yes_no(pd.consumer())
+        ),
+        DeviceCapability::BatteryInfo(battery) => writeln!(
+            out, "{}Battery {}: Design Capacity: {} mWh, Last Full Charge: {} mWh, Charged: {} mWh, Weak: {} mWh",
+            prefix, battery.battery_id, battery.design_capacity, battery.last_full_charge_capacity,
+            battery.charged_threshold, battery.weak_threshold
+        ),
+        DeviceCapability::PdConsumerPort(port) => writeln!(
+            out, "{}PD Consumer Port: {} mV - {} mV, Operating Power: {}, Peak Power: {}, Peak Power Time: {}",
+            prefix, u32::from(port.min_voltage) * 50, u32::from(port.max_voltage) * 50,
+            pd_limit(port.max_operating_power, 10, "mW"), pd_limit(port.max_peak_power, 10, "mW"),
+            pd_limit(port.max_peak_power_time, 100, "ms")
+        ),
+        DeviceCapability::PdProviderPort(port) => {
+            writeln!(out, "{}PD Provider Port: {} Power Data Object(s)", prefix, port.power_data_objects.len())?;
+            for pdo in &port.power_data_objects {
+                writeln!(out, "{}PDO {:#010x}", nested, pdo)?;
+            }
+            Ok(())
+        },
+        DeviceCapability::Unknown(raw) => {
+            write!(out, "{}Capability {:#04x}:", prefix, raw.subtype().unwrap_or(0))?;
+            for byte in &raw.bytes {
+                write!(out, " {:02x}", byte)?;
+            }
+            writeln!(out)
+        },
+    }
+}
+
+pub fn hid_descriptor(out: &mut dyn Write, prefix: &str, hid: &HidDescriptor) -> io::Result<()> {
+    write!(out, "{}HID {}, Country Code: {}", prefix, hid.hid_version, hid.country_code)?;
+    for descriptor in &hid.descriptors {
+        match descriptor.descriptor_type {
+            report_type::REPORT => write!(out, ", Report Descriptor: {} bytes", descriptor.length)?,
+            report_type::PHYSICAL => write!(out, ", Physical Descriptor: {} bytes", descriptor.length)?,
+            other => write!(out, ", Descriptor {:#04x}: {} bytes", other, descriptor.length)?,
+        }
+    }
+    writeln!(out)
+}
+
+pub fn audio_control(out: &mut dyn Write, prefix: &str, control: &AudioControl) -> io::Result<()> {
+    write!(out, "{}Audio Control {}", prefix, match control.version {
+        AudioVersion::Uac1 => "UAC1",
+        AudioVersion::Uac2 => "UAC2",
+        AudioVersion::Uac3 => "UAC3",
+    })?;
+    if let Some(version) = control.adc_version {
+        write!(out, " {}", version)?;
+    }
+    if let Some(category) = control.category {
+        match audio::category_name(category) {
+            Some(name) => write!(out, ", Category: {}", name)?,
+            None => write!(out, ", Category: {:#04x}", category)?,
+        }
+    }
+    if !control.streaming_interfaces.is_empty() {
+        let interfaces: Vec<_> = control.streaming_interfaces.iter().map(u8::to_string).collect();
+        write!(out, ", Streaming Interface(s): {}", interfaces.join(", "))?;
+    }
+    writeln!(out)?;
+    let nested = format!("{}  ", prefix);
+    for entity in &control.entities {
+        write!(out, "{}{} {}", nested, entity_kind(&entity.kind), entity.id)?;
+        if !entity.sources.is_empty() {
+            let sources: Vec<_> = entity.sources.iter().map(u8::to_string).collect();
+            write!(out, " <- {}", sources.join(", "))?;
+        }
+        match &entity.kind {
+            EntityKind::InputTerminal { terminal_type, associated_terminal, channels } => {
+                write!(out, ": {}", terminal_type_name(*terminal_type))?;
+                if let Some(channels) = channels {
+                    write!(out, ", {} channel(s)", channels)?;
+                }
+                if *associated_terminal != 0 {
+                    write!(out, ", Associated Terminal: {}", associated_terminal)?;
+                }
+            },
+            EntityKind::OutputTerminal { terminal_type, associated_terminal } => {
+                write!(out, ": {}", terminal_type_name(*terminal_type))?;
+                if *associated_terminal != 0 {
+                    write!(out, ", Associated Terminal: {}", associated_terminal)?;
+                }
+            },
+            EntityKind::FeatureUnit { controls } => {
+                let bits_per_control = if control.version == AudioVersion::Uac1 { 1 } else { 2 };
+                for (channel, &controls) in controls.iter().enumerate() {
+                    let names: Vec<_> = audio::FEATURE_CONTROLS.iter().enumerate()
+                        .filter(|(index, _)| (controls >> (bits_per_control * index)) & ((1 << bits_per_control) - 1) != 0)
+                        .map(|(_, name)| *name)
+                        .collect();
+                    if names.is_empty() {
+                        continue;
+                    }
+                    match channel {
+                        0 => write!(out, ", Master: {}", names.join("/"))?,
+                        channel => write!(out, ", Channel {}: {}", channel, names.join("/"))?,
+                    }
+                }
+            },
+            EntityKind::EffectUnit { effect_type } => write!(out, ": Type {:#06x}", effect_type)?,
+            EntityKind::ProcessingUnit { process_type } => write!(out, ": Type {:#06x}", process_type)?,
+            EntityKind::ExtensionUnit { extension_code } => write!(out, ": Code {:#06x}", extension_code)?,
+            EntityKind::ClockSource { attributes } => write!(
+                out, ": {}{}",
+                ["External", "Internal Fixed", "Internal Variable", "Internal Programmable"][usize::from(attributes & 0x03)],
+                if attributes & 0x04 != 0 { ", Synchronized to SOF" } else { "" }
+            )?,
+            _ => {},
+        }
+        if !entity.clock_sources.is_empty() {
+            let clocks: Vec<_> = entity.clock_sources.iter().map(u8::to_string).collect();
+            write!(out, ", Clock: {}", clocks.join(", "))?;
+        }
+        writeln!(out)?;
+    }
+    for path in control.signal_paths() {
+        let names: Vec<_> = path.iter()
+            .filter_map(|&id| control.entity(id))
+            .map(|entity| match entity.kind {
+                EntityKind::InputTerminal { terminal_type, .. } | EntityKind::OutputTerminal { terminal_type, .. } => {
+                    format!("{} {} ({})", entity_kind(&entity.kind), entity.id, terminal_type_name(terminal_type))
+                },
+                _ => format!("{} {}", entity_kind(&entity.kind), entity.id),
+            })
+            .collect();
+        writeln!(out, "{}Signal Path: {}", nested, names.join(" -> "))?;
+    }
+    for err in &control.problems {
+        problem(out, &nested, err)?;
+    }
+    Ok(())
+}
+
+pub fn audio_streaming(out: &mut dyn Write, prefix: &str, streaming: &AudioStreaming) -> io::Result<()> {
+    if let Some(terminal) = streaming.terminal_link {
+        write!(out, "{}Audio Streaming: Terminal Link: {}", prefix, terminal)?;
+        match (streaming.format_tag, streaming.formats) {
+            (Some(tag), _) => match audio::format_tag_name(tag) {
+                Some(name) => write!(out, ", Format: {}", name)?,
+                None => write!(out, ", Format: {:#06x}", tag)?,
+            },
+            (None, Some(formats)) => write!(out, ", Format: {}", audio::format_names(formats).join("/"))?,
+            (None, None) => {},
+        }
+        if let Some(channels) = streaming.channels {
+            write!(out, ", {} channel(s)", channels)?;
+        }
+        if let (Some(bits), Some(subslot)) = (streaming.bit_resolution, streaming.subslot_size) {
+            write!(out, ", {} bit in {} byte subslots", bits, subslot)?;
+        }
+        if let Some(rates) = &streaming.sample_rates {
+            write!(out, ", {}", sample_rates_text(rates))?;
+        }
+        writeln!(out)?;
+    }
+    for err in &streaming.problems {
+        problem(out, prefix, err)?;
+    }
+    Ok(())
+}
+
+// the sampling frequencies a UAC2 or UAC3 clock source reported
+pub fn clock_sample_rates(out: &mut dyn Write, prefix: &str, clock: u8, rates: &SampleRates) -> io::Result<()> {
+    writeln!(out, "{}Clock Source {}: {}", prefix, clock, sample_rates_text(rates))
+}
+
+pub fn video_control_descriptor(
+    out: &mut dyn Write,
+    prefix: &str,
+    descriptor: &VideoControlDescriptor,
+) -> io::Result<()> {
+    match descriptor {
+        VideoControlDescriptor::Header { uvc_version, clock_frequency, streaming_interfaces } => {
+            let interfaces: Vec<_> = streaming_interfaces.iter().map(u8::to_string).collect();
+            writeln!(
+                out, "{}Video Control UVC {}, Clock: {} Hz, Streaming Interface(s): {}",
+                prefix, uvc_version, clock_frequency, interfaces.join(", ")
+            )
+        },
+        VideoControlDescriptor::InputTerminal { id, terminal_type, associated_terminal } => {
+            write!(out, "{}Input Terminal {}: {}", prefix, id, video_terminal_type(*terminal_type))?;
+            if *associated_terminal != 0 {
+                write!(out, ", Associated Terminal: {}", associated_terminal)?;
+            }
+            writeln!(out)
+        },
+        VideoControlDescriptor::CameraTerminal(camera) => {
+            write!(out, "{}Camera Terminal {}", prefix, camera.id)?;
+            if camera.objective_focal_length_max != 0 {
+                write!(
+                    out, ", Objective Focal Length: {} - {}, Ocular Focal Length: {}",
+                    camera.objective_focal_length_min, camera.objective_focal_length_max, camera.ocular_focal_length
+                )?;
+            }
+            writeln!(out, ", Controls: {}", bit_names(camera.controls, &video::CAMERA_CONTROLS))
+        },
+        VideoControlDescriptor::OutputTerminal { id, terminal_type, associated_terminal, source } => {
+            write!(out, "{}Output Terminal {} <- {}: {}", prefix, id, source, video_terminal_type(*terminal_type))?;
+            if *associated_terminal != 0 {
+                write!(out, ", Associated Terminal: {}", associated_terminal)?;
+            }
+            writeln!(out)
+        },
+        VideoControlDescriptor::SelectorUnit { id, sources } => {
+            let sources: Vec<_> = sources.iter().map(u8::to_string).collect();
+            writeln!(out, "{}Selector Unit {} <- {}", prefix, id, sources.join(", "))
+        },
+        VideoControlDescriptor::ProcessingUnit { id, source, max_multiplier, controls } => {
+            write!(out, "{}Processing Unit {} <- {}", prefix, id, source)?;
+            if *max_multiplier != 0 {
+                write!(out, ", Max Multiplier: {}.{:02}", max_multiplier / 100, max_multiplier % 100)?;
+            }
+            writeln!(out, ", Controls: {}", bit_names(*controls, &video::PROCESSING_CONTROLS))
+        },
+        VideoControlDescriptor::ExtensionUnit(extension) => {
+            let sources: Vec<_> = extension.sources.iter().map(u8::to_string).collect();
+            write!(
+                out, "{}Extension Unit {} <- {}: {{{}}}, {} control(s), Controls:",
+                prefix, extension.id, sources.join(", "), extension.guid, extension.num_controls
+            )?;
+            for byte in &extension.controls {
+                write!(out, " {:02x}", byte)?;
+            }
+            writeln!(out)
+        },
+        VideoControlDescriptor::EncodingUnit { id, source, controls } => {
+            writeln!(out, "{}Encoding Unit {} <- {}, Controls: {:#x}", prefix, id, source, controls)
+        },
+        VideoControlDescriptor::Unknown(raw) => raw_descriptor(out, prefix, raw),
+    }
+}
+
+pub fn video_streaming(out: &mut dyn Write, prefix: &str, streaming: &VideoStreaming) -> io::Result<()> {
+    if let Some(header) = &streaming.header {
+        write!(
+            out, "{}Video Streaming {}: Endpoint: {:#04x}, Terminal Link: {}",
+            prefix, if header.input { "Input" } else { "Output" }, header.endpoint_address, header.terminal_link
+        )?;
+        if header.still_capture_method != 0 {
+            write!(out, ", Still Capture Method: {}", header.still_capture_method)?;
+        }
+        writeln!(out)?;
+    }
+    let nested = format!("{}  ", prefix);
+    for format in &streaming.formats {
+        write!(out, "{}Format {}: {}", prefix, format.index, match format.kind {
+            FormatKind::Uncompressed => "Uncompressed",
+            FormatKind::Mjpeg => "MJPEG",
+            FormatKind::MpegTs => "MPEG-2 TS",
+            FormatKind::Dv => "DV",
+            FormatKind::FrameBased => "Frame Based",
+            FormatKind::StreamBased => "Stream Based",
+            FormatKind::H264 => "H.264",
+            FormatKind::Vp8 => "VP8",
+        })?;
+        match (format.fourcc(), &format.guid) {
+            (Some(fourcc), Some(guid)) => write!(out, " {} {{{}}}", fourcc.trim_end(), guid)?,
+            (None, Some(guid)) => write!(out, " {{{}}}", guid)?,
+            _ => {},
+        }
+        if let Some(bits) = format.bits_per_pixel {
+            write!(out, ", {} bits per pixel", bits)?;
+        }
+        writeln!(out)?;
+        for frame in &format.frames {
+            write!(out, "{}Frame {}: {}x{}", nested, frame.index, frame.width, frame.height)?;
+            match &frame.intervals {
+                FrameIntervals::Discrete(intervals) => {
+                    let rates: Vec<_> = intervals.iter().map(|&interval| fps(interval)).collect();
+                    write!(out, ", {} fps", rates.join("/"))?;
+                },
+                // the longest interval is the lowest rate
+                FrameIntervals::Continuous { min, max, step } => write!(
+                    out, ", {} - {} fps in steps of {} us", fps(*max), fps(*min), step / 10
+                )?,
+            }
+            write!(out, ", Default: {} fps", fps(frame.default_interval))?;
+            if frame.index == format.default_frame_index {
+                write!(out, " (default frame)")?;
+            }
+            writeln!(out)?;
+        }
+        if let Some(still_image) = &format.still_image {
+            let sizes: Vec<_> = still_image.sizes.iter().map(|(width, height)| format!("{}x{}", width, height)).collect();
+            write!(out, "{}Still Image: {}", nested, sizes.join(", "))?;
+            if !still_image.compressions.is_empty() {
+                let compressions: Vec<_> = still_image.compressions.iter().map(u8::to_string).collect();
+                write!(out, ", Compression: {}", compressions.join(", "))?;
+            }
+            if still_image.endpoint_address != 0 {
+                write!(out, ", Endpoint: {:#04x}", still_image.endpoint_address)?;
+            }
+            writeln!(out)?;
+        }
+        if let Some(color) = &format.color_matching {
+            let name = |name: Option<&str>, value: u8| name.map_or_else(|| format!("{:#04x}", value), str::to_owned);
+            writeln!(
+                out, "{}Color Matching: Primaries: {}, Transfer: {}, Matrix: {}",
+                nested,
+                name(video::color_primaries_name(color.color_primaries), color.color_primaries),
+                name(video::transfer_characteristics_name(color.transfer_characteristics), color.transfer_characteristics),
+                name(video::matrix_coefficients_name(color.matrix_coefficients), color.matrix_coefficients)
+            )?;
+        }
+    }
+    for err in &streaming.problems {
+        problem(out, prefix, err)?;
+    }
+    Ok(())
+}
+
+pub fn ms_os_descriptor_set(
+    out: &mut dyn Write,
+    prefix: &str,
+    info: &DescriptorSetInfo,
+    set: &DescriptorSet,
+) -> io::Result<()> {
+    writeln!(
+        out, "{}MS OS 2.0 Descriptor Set: {}, {} bytes",
+        prefix, windows_version(set.windows_version), set.total_length
+    )?;
+    let nested = format!("{}  ", prefix);
+    if set.total_length != info.total_length {
+        writeln!(
+            out, "{}Problem: wTotalLength {} differs from the {} bytes announced in the BOS",
+            nested, set.total_length, info.total_length
+        )?;
+    }
+    for feature in &set.features {
+        ms_os_feature(out, &nested, feature)?;
+    }
+    for configuration in &set.configurations {
+        writeln!(out, "{}Configuration Subset: Configuration {}", nested, configuration.configuration_value)?;
+        let configuration_prefix = format!("{}  ", nested);
+        for feature in &configuration.features {
+            ms_os_feature(out, &configuration_prefix, feature)?;
+        }
+        for function in &configuration.functions {
+            writeln!(out, "{}Function Subset: First Interface {}", configuration_prefix, function.first_interface)?;
+            for feature in &function.features {
+                ms_os_feature(out, &format!("{}  ", configuration_prefix), feature)?;
+            }
+        }
+    }
+    for err in &set.problems {
+        problem(out, &nested, err)?;
+    }
+    Ok(())
+}
+
+fn ms_os_feature(out: &mut dyn Write, prefix: &str, feature: &Feature) -> io::Result<()> {
+    match feature {
+        Feature::CompatibleId { compatible_id, sub_compatible_id } => {
+            write!(out, "{}Compatible ID: {}", prefix, compatible_id)?;
+            if !sub_compatible_id.is_empty() {
+                write!(out, ", Sub-compatible ID: {}", sub_compatible_id)?;
+            }
+            writeln!(out)
+        },
+        Feature::RegistryProperty(property) => registry_property(out, prefix, property),
+        Feature::MinResumeTime { recovery_time, signaling_time } => writeln!(
+            out, "{}Minimum Resume Time: Recovery: {} ms, Signaling: {} ms", prefix, recovery_time, signaling_time
+        ),
+        Feature::ModelId(uuid) => writeln!(out, "{}Model ID: {{{}}}", prefix, uuid),
+        Feature::CcgpDevice => writeln!(out, "{}CCGP Device", prefix),
+        Feature::VendorRevision(revision) => writeln!(out, "{}Vendor Revision: {}", prefix, revision),
+        Feature::Unknown { descriptor_type, bytes } => {
+            write!(out, "{}Feature {:#06x}:", prefix, descriptor_type)?;
+            for byte in bytes {
+                write!(out, " {:02x}", byte)?;
+            }
+            writeln!(out)
+        },
+    }
+}
+
+pub fn extended_compat_id(out: &mut dyn Write, prefix: &str, compat_id: &ExtendedCompatId) -> io::Result<()> {
+    writeln!(out, "{}Extended Compat ID {}: {} function(s)", prefix, compat_id.version, compat_id.functions.len())?;
+    for function in &compat_id.functions {
+        write!(out, "{}  Interface {}: {}", prefix, function.first_interface, function.compatible_id)?;
+        if !function.sub_compatible_id.is_empty() {
+            write!(out, ", Sub-compatible ID: {}", function.sub_compatible_id)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+pub fn extended_properties(
+    out: &mut dyn Write,
+    prefix: &str,
+    interface: u8,
+    properties: &ExtendedProperties,
+) -> io::Result<()> {
+    writeln!(out, "{}Extended Properties {}, Interface {}:", prefix, properties.version, interface)?;
+    for property in &properties.properties {
+        registry_property(out, &format!("{}  ", prefix), property)?;
+    }
+    Ok(())
+}
+
+fn registry_property(out: &mut dyn Write, prefix: &str, property: &RegistryProperty) -> io::Result<()> {
+    write!(out, "{}Registry Property: {} (", prefix, property.name)?;
+    match ms_os::data_type_name(property.data_type) {
+        Some(name) => write!(out, "{}): ", name)?,
+        None => write!(out, "{:#x}): ", property.data_type)?,
+    }
+    match &property.value {
+        PropertyValue::String(string) => writeln!(out, "{:?}", string),
+        PropertyValue::MultiString(strings) => {
+            let strings: Vec<_> = strings.iter().map(|string| format!("{:?}", string)).collect();
+            writeln!(out, "{}", strings.join(", "))
+        },
+        PropertyValue::Dword(value) => writeln!(out, "{:#010x}", value),
+        PropertyValue::Binary(bytes) => {
+            let bytes: Vec<_> = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+            writeln!(out, "{}", bytes.join(" "))
+        },
+    }
+}
+
+pub fn dfu_functional(out: &mut dyn Write, prefix: &str, functional: &DfuFunctional) -> io::Result<()> {
+    write!(out, "{}DFU Functional: ", prefix)?;
+    if let Some(version) = functional.dfu_version {
+        write!(out, "Version: {}, ", version)?;
+    }
+    writeln!(
+        out, "{}, Detach Timeout: {} ms, Transfer Size: {}",
+        flags(u32::from(functional.attributes), &[
+            (0x01, "Can Download"), (0x02, "Can Upload"), (0x04, "Manifestation Tolerant"), (0x08, "Will Detach"),
+        ]),
+        functional.detach_timeout, functional.transfer_size
+    )
+}
+
+pub fn functional_descriptor(
+    out: &mut dyn Write,
+    prefix: &str,
+    descriptor: &FunctionalDescriptor,
+) -> io::Result<()> {
+    match descriptor {
+        FunctionalDescriptor::Header { cdc_version } => writeln!(out, "{}CDC Header: {}", prefix, cdc_version),
+        FunctionalDescriptor::CallManagement { capabilities, data_interface } => writeln!(
+            out, "{}CDC Call Management: {}, Data Interface: {}",
+            prefix, flags(u32::from(*capabilities), &[(0x01, "Handles Call Management"), (0x02, "Over Data Interface")]),
+            data_interface
+        ),
+        FunctionalDescriptor::AbstractControlManagement { capabilities } => writeln!(
+            out, "{}CDC ACM: {}", prefix,
+            flags(u32::from(*capabilities), &[
+                (0x01, "Comm Features"), (0x02, "Line Coding"), (0x04, "Send Break"), (0x08, "Network Connection"),
+            ])
+        ),
+        FunctionalDescriptor::Union(union) => {
+            let subordinates: Vec<_> = union.subordinate_interfaces.iter().map(u8::to_string).collect();
+            writeln!(
+                out, "{}CDC Union: Control Interface: {}, Subordinate Interface(s): {}",
+                prefix, union.control_interface, subordinates.join(", ")
+            )
+        },
+        FunctionalDescriptor::EthernetNetworking(ethernet) => writeln!(
+            out, "{}CDC Ethernet: MAC Address String: {}, Statistics: {:#010x}, Max Segment Size: {}, \
+                MC Filters: {}{}, Power Filters: {}",
+            prefix, ethernet.mac_address_index, ethernet.ethernet_statistics, ethernet.max_segment_size,
+            ethernet.number_mc_filters & 0x7FFF,
+            if ethernet.number_mc_filters & 0x8000 != 0 { " (imperfect)" } else { "" },
+            ethernet.number_power_filters
+        ),
+        FunctionalDescriptor::Ncm { ncm_version, network_capabilities } => writeln!(
+            out, "{}CDC NCM {}: {}", prefix, ncm_version,
+            flags(u32::from(*network_capabilities), &[
+                (0x01, "Packet Filter"), (0x02, "Net Address"), (0x04, "Encapsulated Command"),
+                (0x08, "Max Datagram Size"), (0x10, "CRC Mode"), (0x20, "8 Byte NTB Input Size"),
+            ])
+        ),
+        FunctionalDescriptor::Mbim(mbim) => writeln!(
+            out, "{}MBIM {}: Max Control Message: {}, Filters: {}, Max Filter Size: {}, Max Segment Size: {}, {}",
+            prefix, mbim.mbim_version, mbim.max_control_message, mbim.number_filters, mbim.max_filter_size,
+            mbim.max_segment_size,
+            flags(u32::from(mbim.network_capabilities), &[(0x08, "Max Datagram Size"), (0x20, "8 Byte NTB Input Size")])
+        ),
+        FunctionalDescriptor::MbimExtended { mbim_extended_version, max_outstanding_command_messages, mtu } => writeln!(
+            out, "{}MBIM Extended {}: Max Outstanding Command Messages: {}, MTU: {}",
+            prefix, mbim_extended_version, max_outstanding_command_messages, mtu
+        ),
+        FunctionalDescriptor::Unknown(raw) => {
+            write!(out, "{}CDC Functional {:#04x}:", prefix, raw.subtype().unwrap_or(0))?;
+            for byte in &raw.bytes {
+                write!(out, " {:02x}", byte)?;
+            }
+            writeln!(out)
+        },
+    }
+}
+
+// one line per item in the style of the USB-IF HID Descriptor Tool, followed
+// by the report sizes and top level collections
+pub fn report_descriptor(out: &mut dyn Write, prefix: &str, descriptor: &ReportDescriptor) -> io::Result<()> {
+    let mut depth = 0usize;
+    let mut usage_page = 0;
+    let mut pages = Vec::new();
+    for item in &descriptor.items {
+        let hex = item.bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>().join(" ");
+        if item.kind == ItemKind::EndCollection {
+            depth = depth.saturating_sub(1);
+        }
+        write!(out, "{}{:<15}{:indent$}", prefix, hex, "", indent = 2 * depth)?;
+        match item.kind {
+            ItemKind::Input(flags) => writeln!(out, "INPUT ({})", main_item_flags(flags, false)),
+            ItemKind::Output(flags) => writeln!(out, "OUTPUT ({})", main_item_flags(flags, true)),
+            ItemKind::Feature(flags) => writeln!(out, "FEATURE ({})", main_item_flags(flags, true)),
+            ItemKind::Collection(collection_type) => {
+                depth += 1;
+                match collection_type_name(collection_type) {
+                    Some(name) => writeln!(out, "COLLECTION ({})", name),
+                    None => writeln!(out, "COLLECTION ({:#04x})", collection_type),
+                }
+            },
+            ItemKind::EndCollection => writeln!(out, "END_COLLECTION"),
+            ItemKind::UsagePage(page) => {
+                usage_page = page;
+                writeln!(out, "USAGE_PAGE ({})", hid_usage_page(page))
+            },
+            ItemKind::LogicalMinimum(value) => writeln!(out, "LOGICAL_MINIMUM ({})", value),
+            ItemKind::LogicalMaximum(value) => writeln!(out, "LOGICAL_MAXIMUM ({})", value),
+            ItemKind::PhysicalMinimum(value) => writeln!(out, "PHYSICAL_MINIMUM ({})", value),
+            ItemKind::PhysicalMaximum(value) => writeln!(out, "PHYSICAL_MAXIMUM ({})", value),
+            ItemKind::UnitExponent(value) => writeln!(out, "UNIT_EXPONENT ({})", value),
+            ItemKind::Unit(unit) => writeln!(out, "UNIT ({:#x})", unit),
+            ItemKind::ReportSize(size) => writeln!(out, "REPORT_SIZE ({})", size),
+            ItemKind::ReportId(id) => writeln!(out, "REPORT_ID ({})", id),
+            ItemKind::ReportCount(count) => writeln!(out, "REPORT_COUNT ({})", count),
+            ItemKind::Push => {
+                pages.push(usage_page);
+                writeln!(out, "PUSH")
+            },
+            ItemKind::Pop => {
+                usage_page = pages.pop().unwrap_or(usage_page);
+                writeln!(out, "POP")
+            },
+            ItemKind::Usage { page, id } => writeln!(out, "USAGE ({})", hid_usage(usage_page, page, id)),
+            ItemKind::UsageMinimum { page, id } => writeln!(out, "USAGE_MINIMUM ({})", hid_usage(usage_page, page, id)),
+            ItemKind::UsageMaximum { page, id } => writeln!(out, "USAGE_MAXIMUM ({})", hid_usage(usage_page, page, id)),
+            ItemKind::DesignatorIndex(index) => writeln!(out, "DESIGNATOR_INDEX ({})", index),
+            ItemKind::DesignatorMinimum(index) => writeln!(out, "DESIGNATOR_MINIMUM ({})", index),
+            ItemKind::DesignatorMaximum(index) => writeln!(out, "DESIGNATOR_MAXIMUM ({})", index),
+            ItemKind::StringIndex(index) => writeln!(out, "STRING_INDEX ({})", index),
+            ItemKind::StringMinimum(index) => writeln!(out, "STRING_MINIMUM ({})", index),
+            ItemKind::StringMaximum(index) => writeln!(out, "STRING_MAXIMUM ({})", index),
+            ItemKind::Delimiter(delimiter) => writeln!(
+                out, "DELIMITER ({})", if delimiter == 1 { "Open" } else { "Close" }
+            ),
+            ItemKind::Long { tag } => writeln!(out, "LONG_ITEM ({:#04x}, {} bytes)", tag, item.data().len()),
+            ItemKind::Reserved { item_type, tag, data } => writeln!(
+                out, "RESERVED (type {}, tag {:#x}, {:#x})", item_type, tag, data
+            ),
+        }?;
+    }
+    for report in descriptor.reports() {
+        match report.id {
+            Some(id) => write!(out, "{}Report {}:", prefix, id)?,
+            None => write!(out, "{}Report:", prefix)?,
+        }
+        let sizes = [("Input", report.input_bits), ("Output", report.output_bits), ("Feature", report.feature_bits)];
+        let mut separator = " ";
+        for (name, bits) in sizes.iter().filter(|(_, bits)| *bits > 0) {
+            write!(out, "{}{}: {} bits ({} bytes)", separator, name, bits, report.bytes(*bits))?;
+            separator = ", ";
+        }
+        writeln!(out)?;
+    }
+    for usage in descriptor.applications() {
+        writeln!(out, "{}Application: {}: {}", prefix, hid_usage_page(usage.page), hid_usage_name(usage.page, usage.id))?;
+    }
+    for err in &descriptor.problems {
+        problem(out, prefix, err)?;
+    }
+    Ok(())
+}
+
+// a descriptor none of the standard parsers know, decoded by a registered
+// vendor decoder if one applies and dumped as hex otherwise
+fn unknown_descriptor(
+    out: &mut dyn Write,
+    prefix: &str,
+    raw: &RawDescriptor,
+    interface_class: Option<u8>,
+    vendor: DeviceDecoders<'_>,
+) -> io::Result<()> {
+    let decoded = match vendor.decode(interface_class, raw) {
+        Some(decoded) => decoded,
+        None => return raw_descriptor(out, prefix, raw),
+    };
+    match decoded.fields {
+        Ok(fields) => {
+            let fields: Vec<_> = fields.iter().map(|field| format!("{}: {}", field.name, field.value)).collect();
+            writeln!(out, "{}{} ({:#04x}): {}", prefix, decoded.name, raw.descriptor_type(), fields.join(", "))
+        },
+        Err(err) => {
+            raw_descriptor(out, prefix, raw)?;
+            problem(out, prefix, &err)
+        },
+    }
+}
+
+// hex dump of a descriptor nothing more specific is known about
+pub fn raw_descriptor(out: &mut dyn Write, prefix: &str, raw: &RawDescriptor) -> io::Result<()> {
+    write!(out, "{}Descriptor {:#04x}:", prefix, raw.descriptor_type())?;
+    for byte in &raw.bytes {
+        write!(out, " {:02x}", byte)?;
+    }
+    writeln!(out)
+}
+
+pub fn problem(out: &mut dyn Write, prefix: &str, err: &Error) -> io::Result<()> {
+    writeln!(out, "{}Problem: {}", prefix, err)
+}
+
+fn entity_kind(kind: &EntityKind) -> &'static str {
+    match kind {
+        EntityKind::InputTerminal { .. } => "Input Terminal",
+        EntityKind::OutputTerminal { .. } => "Output Terminal",
+        EntityKind::MixerUnit => "Mixer Unit",
+        EntityKind::SelectorUnit => "Selector Unit",
+        EntityKind::FeatureUnit { .. } => "Feature Unit",
+        EntityKind::EffectUnit { .. } => "Effect Unit",
+        EntityKind::ProcessingUnit { .. } => "Processing Unit",
+        EntityKind::ExtensionUnit { .. } => "Extension Unit",
+        EntityKind::ClockSource { .. } => "Clock Source",
+        EntityKind::ClockSelector => "Clock Selector",
+        EntityKind::ClockMultiplier => "Clock Multiplier",
+        EntityKind::SampleRateConverter => "Sample Rate Converter",
+    }
+}
+
+fn terminal_type_name(terminal_type: u16) -> String {
+    match audio::terminal_type_name(terminal_type) {
+        Some(name) => name.to_owned(),
+        None => format!("{:#06x}", terminal_type),
+    }
+}
+
+fn windows_version(version: u32) -> String {
+    match ms_os::windows_version_name(version) {
+        Some(name) => format!("{} ({:#010x})", name, version),
+        None => format!("Windows {:#010x}", version),
+    }
+}
+
+fn video_terminal_type(terminal_type: u16) -> String {
+    match video::terminal_type_name(terminal_type) {
+        Some(name) => name.to_owned(),
+        None => format!("{:#06x}", terminal_type),
+    }
+}
+
+// a PD consumer port limit in `scale` units of `unit`; all ones means there is none
+fn pd_limit(value: u32, scale: u64, unit: &str) -> String {
+    match value {
+        u32::MAX => "no maximum".to_owned(),
+        value => format!("{} {}", u64::from(value) * scale, unit),
+    }
+}
+
+fn sample_rates_text(rates: &SampleRates) -> String {
+    match rates {
+        SampleRates::Discrete(rates) => {
+            let rates: Vec<_> = rates.iter().map(u32::to_string).collect();
+            format!("{} Hz", rates.join("/"))
+        },
+        SampleRates::Continuous { min, max } => format!("{} - {} Hz", min, max),
+        SampleRates::Ranges(ranges) => {
+            let ranges: Vec<_> = ranges.iter()
+                .map(|range| match range.min == range.max {
+                    true => range.min.to_string(),
+                    false => format!("{} - {} in steps of {}", range.min, range.max, range.resolution),
+                })
+                .collect();
+            format!("{} Hz", ranges.join(", "))
+        },
+    }
+}
+
+// frame rate of an interval in 100 ns units, with decimals only where needed
+fn fps(interval: u32) -> String {
+    let millihertz = video::millihertz(interval);
+    match millihertz % 1000 {
+        0 => (millihertz / 1000).to_string(),
+        fraction => format!("{}.{:03}", millihertz / 1000, fraction).trim_end_matches('0').to_owned(),
+    }
+}
+
+// names of the bits set in `value`, taken in bit order from `names`
+fn bit_names(value: u32, names: &[&str]) -> String {
+    let set: Vec<_> = names.iter().enumerate()
+        .filter(|(bit, _)| value & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    if set.is_empty() { "none".to_owned() } else { set.join(", ") }
+}
+
+// a LANGID with its language name where known
+fn language(language_id: u16) -> String {
+    match language_name(language_id) {
+        Some(name) => format!("{:#06x} ({})", language_id, name),
+        None => format!("{:#06x}", language_id),
+    }
+}
+
+fn transport_name(transport: Transport) -> String {
+    match transport {
+        Transport::Cbi { completion_interrupt: true } => "CBI with Command Completion Interrupt".to_owned(),
+        Transport::Cbi { completion_interrupt: false } => "CBI".to_owned(),
+        Transport::BulkOnly => "Bulk-Only".to_owned(),
+        Transport::Uas => "UAS".to_owned(),
+        Transport::Other(protocol) => format!("Protocol {:#04x}", protocol),
+    }
+}
+
+fn pipe_name(pipe: Pipe) -> &'static str {
+    match pipe {
+        Pipe::Command => "Command",
+        Pipe::Status => "Status",
+        Pipe::DataIn => "Data-In",
+        Pipe::DataOut => "Data-Out",
+    }
+}
+
+fn flags(value: u32, names: &[(u32, &str)]) -> String {
+    let set: Vec<_> = names.iter().filter(|(bit, _)| value & bit != 0).map(|(_, name)| *name).collect();
+    if set.is_empty() {
+        format!("Capabilities: {:#04x} (none)", value)
+    } else {
+        format!("Capabilities: {:#04x} ({})", value, set.join(", "))
+    }
+}
+
+fn main_item_flags(flags: u32, non_volatile: bool) -> String {
+    let mut names = vec![
+        if flags & 0x01 != 0 { "Cnst" } else { "Data" },
+        if flags & 0x02 != 0 { "Var" } else { "Ary" },
+        if flags & 0x04 != 0 { "Rel" } else { "Abs" },
+    ];
+    let extra = [(0x08, "Wrap"), (0x10, "NLin"), (0x20, "NPrf"), (0x40, "Null"), (0x80, "Vol"), (0x100, "Buf")];
+    for (bit, name) in extra {
+        // bit 7 is reserved for input items
+        if flags & bit != 0 && (bit != 0x80 || non_volatile) {
+            names.push(name);
+        }
+    }
+    names.join(",")
+}
+
+fn hid_usage_page(page: u16) -> String {
+    match usage_page_name(page) {
+        Some(name) => name.to_owned(),
+        None if is_vendor_defined(page) => format!("Vendor Defined {:#06x}", page),
+        None => format!("{:#06x}", page),
+    }
+}
+
+fn hid_usage_name(page: u16, id: u16) -> String {
+    match usage_name(page, id) {
+        Some(name) => name.to_string(),
+        None => format!("{:#04x}", id),
+    }
+}
+
+// `page` is the page of an extended usage, `usage_page` the current global one
+fn hid_usage(usage_page: u16, page: Option<u16>, id: u16) -> String {
+    match page {
+        Some(page) if page != usage_page => format!("{}: {}", hid_usage_page(page), hid_usage_name(page, id)),
+        _ => hid_usage_name(usage_page, id),
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+fn class(class: u8) -> String {
+    match class_name(class) {
+        Some(name) => format!("{:#04x} ({})", class, name),
+        None => format!("{:#04x}", class),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::descriptor::{
+        bos::PdConsumerPort,
+        vendor::{Field, Selector, VendorRegistry},
+        ErrorKind,
+    };
+
+    fn configuration_text(bytes: &[u8]) -> String {
+        let mut out = Vec::new();
+        let registry = VendorRegistry::default();
+        let parsed = Configuration::parse(bytes).unwrap();
+        configuration(&mut out, "", &parsed, false, registry.for_device(0x1209, 0x0001, 0xEF)).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn interface_outside_associations() {
+        // an IAD over the ACM interfaces 0-1 and a HID interface 2 outside it
+        let bytes = [
+            0x09, 0x02, 0x2C, 0x00, 0x03, 0x01, 0x00, 0x80, 0x32,
+            0x08, 0x0B, 0x00, 0x02, 0x02, 0x02, 0x01, 0x00,
+            0x09, 0x04, 0x00, 0x00, 0x00, 0x02, 0x02, 0x01, 0x00,
+            0x09, 0x04, 0x01, 0x00, 0x00, 0x0A, 0x00, 0x00, 0x00,
+            0x09, 0x04, 0x02, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+        ];
+        let text = configuration_text(&bytes);
+        let functions: Vec<_> = text.lines().filter(|line| line.trim_start().starts_with("Function")).collect();
+        assert_eq!(functions.len(), 2, "{}", text);
+        assert!(functions[0].ends_with(", Interfaces 0-1"), "{}", text);
+        assert!(functions[1].ends_with(", Interface 2, not covered by any IAD"), "{}", text);
+    }
+
+    #[test]
+    fn pd_consumer_port_limits() {
+        // all ones means no limit; one less used to overflow once scaled
+        let port = DeviceCapability::PdConsumerPort(PdConsumerPort {
+            capabilities: 0x0001,
+            min_voltage: 100,
+            max_voltage: 400,
+            max_operating_power: u32::MAX,
+            max_peak_power: u32::MAX - 1,
+            max_peak_power_time: 30,
+        });
+        let mut out = Vec::new();
+        device_capability(&mut out, "", &port).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "PD Consumer Port: 5000 mV - 20000 mV, Operating Power: no maximum, \
+             Peak Power: 42949672940 mW, Peak Power Time: 3000 ms\n",
+        );
+    }
+
+    #[test]
+    fn vendor_descriptors() {
+        // a calibration descriptor, a short one and one nobody decodes
+        let bytes = [
+            0x09, 0x02, 0x1A, 0x00, 0x01, 0x01, 0x00, 0x80, 0x32,
+            0x09, 0x04, 0x00, 0x00, 0x00, 0xFF, 0x00, 0x00, 0x00,
+            0x03, 0x41, 0x07,
+            0x02, 0x41,
+            0x03, 0x42, 0x01,
+        ];
+        let mut registry = VendorRegistry::default();
+        registry.register(Selector::Product { vendor_id: 0x1209, product_id: 0x0001 }, 0x41, "Calibration",
+            |bytes: &[u8]| match bytes.get(2) {
+                Some(gain) => Ok(vec![Field::new("Gain", gain)]),
+                None => Err(Error::new(0, ErrorKind::Truncated { needed: 3, available: bytes.len() })),
+            });
+        let parsed = Configuration::parse(&bytes).unwrap();
+        let mut out = Vec::new();
+        configuration(&mut out, "", &parsed, false, registry.for_device(0x1209, 0x0001, 0x00)).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = text.lines().skip(2).map(str::trim_start).collect();
+        assert_eq!(lines, [
+            "Calibration (0x41): Gain: 7",
+            "Descriptor 0x41: 02 41",
+            &format!("Problem: {}", Error::new(21, ErrorKind::Truncated { needed: 3, available: 2 })),
+            "Descriptor 0x42: 03 42 01",
+        ], "{}", text);
+    }
+}
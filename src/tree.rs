@@ -0,0 +1,723 @@
+use crate::descriptor::{
+    audio::{self, AudioControl, AudioStreaming, AudioVersion},
+    bos::Bos,
+    cdc::{self, FunctionalDescriptor},
+    configuration::Configuration,
+    descriptor_type,
+    device::{DebugDescriptor, DeviceDescriptor, DeviceQualifier},
+    hid::{find_hid_descriptor, report_type, ReportDescriptor},
+    hub::HubDescriptor,
+    ms_os::{self, DescriptorSet, DescriptorSetInfo, ExtendedCompatId, ExtendedProperties, OsStringDescriptor},
+    string,
+    vendor::VendorRegistry,
+    webusb::{self, UrlDescriptor, WebUsb},
+    Bcd,
+};
+use crate::render;
+use crate::usb_path::{DeviceSelector, UsbPath};
+use crate::usb_subsystem::{ConnectedDevice, ControlRequest, Speed, UsbBackend};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct TreeOptions {
+    pub verbose: bool, // print every descriptor, not only the device summary
+    pub language_id: u16, // preferred language of string descriptors
+    pub vendor_descriptors: Arc<VendorRegistry>, // decoders for descriptors usb-z does not know
+    pub device: Option<DeviceSelector>, // only show this device or hub and what lies below it
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            verbose: false,
+            language_id: string::ENGLISH_UNITED_STATES,
+            vendor_descriptors: Arc::default(),
+            device: None,
+        }
+    }
+}
+
+// print every host controller with its root hub and everything below it
+pub fn print_host_controllers(
+    backend: &dyn UsbBackend,
+    out: &mut dyn Write,
+    options: &TreeOptions,
+) -> io::Result<()> {
+    for (index, host_controller) in backend.host_controllers()?.enumerate() {
+        let host_controller = host_controller?;
+        let selected = match &options.device {
+            Some(device) => match device.resolve(index, &host_controller.root_hub_name) {
+                Some(selected) => Some(selected),
+                None => continue,
+            },
+            None => None,
+        };
+        let root_hub_path = UsbPath::root_hub(index);
+        writeln!(out, "├ Host Controller Index: {}", index)?;
+        writeln!(out, "│ (HCD Driver Key Name: {:?})", host_controller.driver_key)?;
+        // find device instance matching the driver name
+        if let Some(dev_props) = backend.device_properties(&host_controller.driver_key)? {
+            writeln!(out, "│ Id: {:?}", dev_props.device_id)?;
+            if let Some(device_desc) = &dev_props.device_desc {
+                writeln!(out, "│ Desc: {:?}", device_desc)?;
+            }
+            if let Some(service) = &dev_props.service {
+                writeln!(out, "│ Service: {:?}", service)?;
+            }
+            if let Some(device_class) = &dev_props.device_class {
+                writeln!(out, "│ Class: {:?}", device_class)?;
+            }
+        }
+        writeln!(out, "│ ├ Root hub")?;
+        writeln!(out, "│ │ Name: {:?}", host_controller.root_hub_name)?;
+        writeln!(out, "│ │ Path: {}", root_hub_path)?;
+        let hub = &host_controller.root_hub_name;
+        print_hub(backend, out, options, selected.as_ref(), hub, &root_hub_path, "│ │ ")?;
+    }
+    Ok(())
+}
+
+// print devices connected to `hub`, descending into downstream hubs; with a
+// device `selected`, only the hubs leading to it and what lies below it
+fn print_hub(
+    backend: &dyn UsbBackend,
+    out: &mut dyn Write,
+    options: &TreeOptions,
+    selected: Option<&UsbPath>,
+    hub: &OsStr,
+    hub_path: &UsbPath,
+    prefix: &str,
+) -> io::Result<()> {
+    let ports = match backend.hub_ports(hub) {
+        Ok(ports) => ports,
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    if options.verbose && selected.is_none_or(|selected| selected.contains(hub_path)) {
+        let hub_descriptor = backend.hub_descriptor(hub)
+            .and_then(|bytes| HubDescriptor::parse(&bytes).map_err(invalid_data));
+        match hub_descriptor {
+            Ok(hub_descriptor) => render::hub_descriptor(out, prefix, &hub_descriptor)?,
+            Err(err) if matches!(err.kind(), io::ErrorKind::Unsupported | io::ErrorKind::NotFound) => {},
+            Err(err) => writeln!(out, "{}Hub Descriptor: {}", prefix, err)?,
+        }
+    }
+    for connection in ports {
+        let path = hub_path.join(connection.port);
+        if let Some(selected) = selected {
+            if !selected.contains(&path) && !path.contains(selected) {
+                continue;
+            }
+        }
+        let device = match &connection.device {
+            Some(device) => device,
+            None => {
+                writeln!(out, "{}├ Port {}: (empty)", prefix, connection.port)?;
+                writeln!(out, "{}│ Path: {}", prefix, path)?;
+                continue;
+            },
+        };
+        let dev_props = match &device.driver_key {
+            Some(driver_key) => backend.device_properties(driver_key)?,
+            None => None,
+        };
+        let desc = dev_props.as_ref().and_then(|dev_props| dev_props.device_desc.as_ref());
+        match (&device.hub_name, desc) {
+            (Some(_), Some(desc)) => writeln!(out, "{}├ Port {}: Hub {:?}", prefix, connection.port, desc)?,
+            (Some(_), None) => writeln!(out, "{}├ Port {}: Hub", prefix, connection.port)?,
+            (None, Some(desc)) => writeln!(out, "{}├ Port {}: {:?}", prefix, connection.port, desc)?,
+            (None, None) => writeln!(out, "{}├ Port {}: Device", prefix, connection.port)?,
+        }
+        if let Some(driver_key) = &device.driver_key {
+            writeln!(out, "{}│ Driver Key: {:?}", prefix, driver_key)?;
+        }
+        if let Some(speed) = device.speed {
+            writeln!(out, "{}│ Speed: {}", prefix, speed)?;
+        }
+        writeln!(out, "{}│ Path: {}", prefix, path)?;
+        if selected.is_none_or(|selected| selected.contains(&path)) {
+            print_device(backend, out, options, hub, connection.port, device, &format!("{}│ ", prefix))?;
+        }
+        if let Some(hub_name) = &device.hub_name {
+            writeln!(out, "{}│ Name: {:?}", prefix, hub_name)?;
+            print_hub(backend, out, options, selected, hub_name, &path, &format!("{}│ ", prefix))?;
+        }
+    }
+    Ok(())
+}
+
+// the descriptors of the device connected to `port` of `hub`
+fn print_device(
+    backend: &dyn UsbBackend,
+    out: &mut dyn Write,
+    options: &TreeOptions,
+    hub: &OsStr,
+    port: u8,
+    device: &ConnectedDevice,
+    prefix: &str,
+) -> io::Result<()> {
+    let device_descriptor = backend.descriptor(hub, port, descriptor_type::DEVICE, 0, 0)
+        .and_then(|bytes| DeviceDescriptor::parse(&bytes).map_err(invalid_data));
+    match &device_descriptor {
+        Ok(device_descriptor) => render::device_descriptor(out, prefix, device_descriptor)?,
+        Err(err) => writeln!(out, "{}Device Descriptor: {}", prefix, err)?,
+    }
+    if let (true, Ok(device_descriptor)) = (options.verbose, &device_descriptor) {
+        // only debug devices answer, failing to read it is not worth a line
+        if let Ok(bytes) = backend.descriptor(hub, port, descriptor_type::DEBUG, 0, 0) {
+            match DebugDescriptor::parse(&bytes) {
+                Ok(debug) => render::debug_descriptor(out, prefix, &debug)?,
+                Err(err) => writeln!(out, "{}Debug: {}", prefix, err)?,
+            }
+        }
+        let super_speed = device.speed >= Some(Speed::Super);
+        let vendor = options.vendor_descriptors.for_device(
+            device_descriptor.vendor_id, device_descriptor.product_id, device_descriptor.device_class,
+        );
+        let mut configurations = Vec::new();
+        for index in 0..device_descriptor.num_configurations {
+            let configuration = backend.descriptor(hub, port, descriptor_type::CONFIGURATION, index, 0)
+                .and_then(|bytes| Configuration::parse(&bytes).map_err(invalid_data));
+            match configuration {
+                Ok(configuration) => {
+                    render::configuration(out, prefix, &configuration, super_speed, vendor)?;
+                    print_report_descriptors(backend, out, hub, port, &configuration, prefix)?;
+                    print_sample_rates(backend, out, hub, port, &configuration, prefix)?;
+                    configurations.push(Some(configuration));
+                },
+                Err(err) => {
+                    writeln!(out, "{}Configuration {}: {}", prefix, index, err)?;
+                    configurations.push(None);
+                },
+            }
+        }
+        let references = string_references(device_descriptor, configurations.iter().flatten());
+        print_strings(backend, out, hub, port, options.language_id, &references, prefix)?;
+        // only high speed capable devices describe the other speed
+        if device_descriptor.usb_version >= Bcd(0x0200) && !super_speed {
+            print_other_speed(backend, out, hub, port, device.speed, &configurations, prefix)?;
+        }
+        // devices report a BOS from USB 2.01 on
+        if device_descriptor.usb_version >= Bcd(0x0201) {
+            let bos = backend.descriptor(hub, port, descriptor_type::BOS, 0, 0)
+                .and_then(|bytes| Bos::parse(&bytes).map_err(invalid_data));
+            match bos {
+                Ok(bos) => {
+                    render::bos(out, prefix, &bos)?;
+                    print_ms_os_descriptor_sets(backend, out, hub, port, &bos, prefix)?;
+                    print_webusb_landing_page(backend, out, hub, port, &bos, prefix)?;
+                },
+                Err(err) if err.kind() == io::ErrorKind::Unsupported => {},
+                Err(err) => writeln!(out, "{}BOS: {}", prefix, err)?,
+            }
+        }
+        print_ms_os_feature_descriptors(backend, out, hub, port, prefix)?;
+    }
+    Ok(())
+}
+
+// fetch and decode the report descriptor of every HID interface of `configuration`
+fn print_report_descriptors(
+    backend: &dyn UsbBackend,
+    out: &mut dyn Write,
+    hub: &OsStr,
+    port: u8,
+    configuration: &Configuration,
+    prefix: &str,
+) -> io::Result<()> {
+    for interface in &configuration.interfaces {
+        let has_hid_descriptor = interface.alternate_settings.iter()
+            .any(|setting| setting.interface_class == 0x03 && find_hid_descriptor(&setting.extra).is_some());
+        if !has_hid_descriptor {
+            continue;
+        }
+        // wIndex of the request is the interface number
+        let bytes = match backend.descriptor(hub, port, report_type::REPORT, 0, interface.number.into()) {
+            Ok(bytes) => bytes,
+            Err(err) if matches!(err.kind(), io::ErrorKind::Unsupported | io::ErrorKind::NotFound) => continue,
+            Err(err) => {
+                writeln!(out, "{}Report Descriptor, Interface {}: {}", prefix, interface.number, err)?;
+                continue;
+            },
+        };
+        writeln!(out, "{}Report Descriptor, Interface {}: {} bytes", prefix, interface.number, bytes.len())?;
+        render::report_descriptor(out, &format!("{}  ", prefix), &ReportDescriptor::parse(&bytes))?;
+    }
+    Ok(())
+}
+
+// ask the clock sources of UAC2 and UAC3 streaming interfaces for their
+// sampling frequencies, which these versions leave out of the descriptors
+fn print_sample_rates(
+    backend: &dyn UsbBackend,
+    out: &mut dyn Write,
+    hub: &OsStr,
+    port: u8,
+    configuration: &Configuration,
+    prefix: &str,
+) -> io::Result<()> {
+    let audio_control = configuration.alternate_settings()
+        .filter(|setting| setting.interface_class == 0x01 && setting.interface_subclass == audio::SUBCLASS_AUDIO_CONTROL)
+        .find_map(|setting| Some((setting.interface_number, AudioControl::parse(setting)?)));
+    let (interface, control) = match audio_control {
+        Some((interface, control)) if control.version != AudioVersion::Uac1 => (interface, control),
+        _ => return Ok(()),
+    };
+    let clocks: BTreeSet<u8> = audio::streaming_settings(configuration)
+        .filter_map(|setting| AudioStreaming::parse(setting)?.terminal_link)
+        .filter_map(|terminal| control.clock_source_of(terminal))
+        .collect();
+    for clock in clocks {
+        // class request to the clock source entity of the AudioControl interface
+        let request = |length| ControlRequest {
+            request_type: 0xA1,
+            request: audio::REQUEST_RANGE,
+            value: u16::from(audio::CS_SAM_FREQ_CONTROL) << 8,
+            index: u16::from(clock) << 8 | u16::from(interface),
+            length,
+        };
+        // wNumSubRanges first, then every subrange
+        let rates = backend.control_in(hub, port, request(2))
+            .and_then(|header| {
+                if header.len() < 2 {
+                    return Ok(header)
+                }
+                let count = u16::from_le_bytes([header[0], header[1]]).min((u16::MAX - 2) / 12);
+                backend.control_in(hub, port, request(2 + 12 * count))
+            })
+            .and_then(|bytes| audio::parse_sample_rate_range(&bytes).map_err(invalid_data));
+        match rates {
+            Ok(rates) => render::clock_sample_rates(out, prefix, clock, &rates)?,
+            Err(err) if err.kind() == io::ErrorKind::Unsupported => {
+                writeln!(out, "{}Clock Source {}: sample rates not fetched, {}", prefix, clock, err)?
+            },
+            Err(err) => writeln!(out, "{}Clock Source {}: {}", prefix, clock, err)?,
+        }
+    }
+    Ok(())
+}
+
+// fetch and decode the MS OS 2.0 descriptor sets announced in `bos`
+fn print_ms_os_descriptor_sets(
+    backend: &dyn UsbBackend,
+    out: &mut dyn Write,
+    hub: &OsStr,
+    port: u8,
+    bos: &Bos,
+    prefix: &str,
+) -> io::Result<()> {
+    for platform in bos.platform_capabilities() {
+        let infos = match DescriptorSetInfo::parse(platform) {
+            Some(Ok(infos)) => infos,
+            Some(Err(err)) => {
+                writeln!(out, "{}MS OS 2.0 Descriptor Set: {}", prefix, err)?;
+                continue;
+            },
+            None => continue,
+        };
+        for info in infos {
+            let request = ControlRequest {
+                request_type: 0xC0,
+                request: info.vendor_code,
+                value: 0,
+                index: ms_os::DESCRIPTOR_SET_INDEX,
+                length: info.total_length,
+            };
+            let set = backend.control_in(hub, port, request)
+                .and_then(|bytes| DescriptorSet::parse(&bytes).map_err(invalid_data));
+            match set {
+                Ok(set) => render::ms_os_descriptor_set(out, prefix, &info, &set)?,
+                Err(err) if err.kind() == io::ErrorKind::Unsupported => {},
+                Err(err) => writeln!(out, "{}MS OS 2.0 Descriptor Set: {}", prefix, err)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+// pick a language from string descriptor 0, preferring `language_id`, and
+// fetch every referenced string in it
+fn print_strings(
+    backend: &dyn UsbBackend,
+    out: &mut dyn Write,
+    hub: &OsStr,
+    port: u8,
+    language_id: u16,
+    references: &BTreeMap<u8, Vec<String>>,
+    prefix: &str,
+) -> io::Result<()> {
+    if references.is_empty() {
+        return Ok(())
+    }
+    let language_ids = backend.descriptor(hub, port, descriptor_type::STRING, 0, 0)
+        .and_then(|bytes| string::parse_language_ids(&bytes).map_err(invalid_data));
+    let language_id = match language_ids {
+        // fall back to the first language when the preferred one is missing
+        Ok(language_ids) => {
+            let language_id = match language_ids.contains(&language_id) {
+                true => language_id,
+                false => language_ids[0],
+            };
+            render::language_ids(out, prefix, &language_ids, language_id)?;
+            language_id
+        },
+        // a backend without descriptor 0, such as sysfs, may still have some strings
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => language_id,
+        Err(err) => return writeln!(out, "{}Languages: {}", prefix, err),
+    };
+    let nested = format!("{}  ", prefix);
+    for (index, usages) in references {
+        let bytes = match backend.descriptor(hub, port, descriptor_type::STRING, *index, language_id) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::Unsupported => continue,
+            Err(err) => {
+                writeln!(out, "{}String {} ({}): {}", nested, index, usages.join(", "), err)?;
+                continue;
+            },
+        };
+        render::string_descriptor(out, &nested, *index, usages, &string::parse_string(&bytes))?;
+    }
+    Ok(())
+}
+
+// every non-zero string index referenced by the device and its
+// configurations, with what refers to it
+fn string_references<'a>(
+    device_descriptor: &DeviceDescriptor,
+    configurations: impl Iterator<Item = &'a Configuration>,
+) -> BTreeMap<u8, Vec<String>> {
+    let mut references: BTreeMap<u8, Vec<String>> = BTreeMap::new();
+    let mut add = |index: u8, usage: String| {
+        if index != 0 {
+            references.entry(index).or_default().push(usage);
+        }
+    };
+    add(device_descriptor.manufacturer_index, "Manufacturer".to_owned());
+    add(device_descriptor.product_index, "Product".to_owned());
+    add(device_descriptor.serial_number_index, "Serial Number".to_owned());
+    for configuration in configurations {
+        add(configuration.configuration_index, format!("Configuration {}", configuration.configuration_value));
+        for association in &configuration.associations {
+            add(association.function_index, format!("Function at Interface {}", association.first_interface));
+        }
+        for setting in configuration.alternate_settings() {
+            add(setting.interface_index, format!("Interface {} Alt {}", setting.interface_number, setting.alternate_setting));
+            if setting.interface_class != 0x02 {
+                continue;
+            }
+            for descriptor in cdc::functional_descriptors(setting).flatten() {
+                if let FunctionalDescriptor::EthernetNetworking(ethernet) = descriptor {
+                    add(ethernet.mac_address_index, format!("MAC Address of Interface {}", setting.interface_number));
+                }
+            }
+        }
+    }
+    references
+}
+
+// the device qualifier and every other speed configuration next to the
+// configuration of the same index
+fn print_other_speed(
+    backend: &dyn UsbBackend,
+    out: &mut dyn Write,
+    hub: &OsStr,
+    port: u8,
+    speed: Option<Speed>,
+    configurations: &[Option<Configuration>],
+    prefix: &str,
+) -> io::Result<()> {
+    let qualifier = backend.descriptor(hub, port, descriptor_type::DEVICE_QUALIFIER, 0, 0)
+        .and_then(|bytes| DeviceQualifier::parse(&bytes).map_err(invalid_data));
+    let qualifier = match qualifier {
+        Ok(qualifier) => qualifier,
+        // full speed only devices stall the request
+        Err(err) if matches!(err.kind(), io::ErrorKind::Unsupported | io::ErrorKind::NotFound) => return Ok(()),
+        Err(err) => return writeln!(out, "{}Device Qualifier: {}", prefix, err),
+    };
+    render::device_qualifier(out, prefix, &qualifier)?;
+    let high_speed = match speed {
+        Some(Speed::High) => Some(true),
+        Some(Speed::Full) => Some(false),
+        _ => None,
+    };
+    for (index, current) in configurations.iter().enumerate().take(qualifier.num_configurations.into()) {
+        let other = backend.descriptor(hub, port, descriptor_type::OTHER_SPEED_CONFIGURATION, index as u8, 0)
+            .and_then(|bytes| Configuration::parse_other_speed(&bytes).map_err(invalid_data));
+        match (current, other) {
+            (Some(current), Ok(other)) => render::other_speed_configuration(out, prefix, current, &other, high_speed)?,
+            (None, Ok(_)) => {},
+            (_, Err(err)) => writeln!(out, "{}Other Speed Configuration {}: {}", prefix, index, err)?,
+        }
+    }
+    Ok(())
+}
+
+// fetch the landing page URL of a WebUSB device
+fn print_webusb_landing_page(
+    backend: &dyn UsbBackend,
+    out: &mut dyn Write,
+    hub: &OsStr,
+    port: u8,
+    bos: &Bos,
+    prefix: &str,
+) -> io::Result<()> {
+    let webusb = bos.platform_capabilities().find_map(|platform| WebUsb::parse(platform)?.ok());
+    let webusb = match webusb {
+        Some(webusb) if webusb.landing_page != 0 => webusb,
+        _ => return Ok(()),
+    };
+    let request = ControlRequest {
+        request_type: 0xC0,
+        request: webusb.vendor_code,
+        value: webusb.landing_page.into(),
+        index: webusb::GET_URL,
+        length: u8::MAX.into(),
+    };
+    let url = backend.control_in(hub, port, request)
+        .and_then(|bytes| UrlDescriptor::parse(&bytes).map_err(invalid_data));
+    match url {
+        Ok(url) => writeln!(out, "{}WebUSB Landing Page: {}", prefix, url),
+        // the device does advertise a page, so say why it is missing
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => {
+            writeln!(out, "{}WebUSB Landing Page: URL {} not fetched, {}", prefix, webusb.landing_page, err)
+        },
+        Err(err) => writeln!(out, "{}WebUSB Landing Page: {}", prefix, err),
+    }
+}
+
+// the OS string descriptor and, if the device has one, the MS OS 1.0 extended
+// compat ID and extended properties descriptors
+fn print_ms_os_feature_descriptors(
+    backend: &dyn UsbBackend,
+    out: &mut dyn Write,
+    hub: &OsStr,
+    port: u8,
+    prefix: &str,
+) -> io::Result<()> {
+    // most devices have no OS string descriptor, so failing to read it is not worth a line
+    let bytes = match backend.descriptor(hub, port, descriptor_type::STRING, ms_os::OS_STRING_INDEX, 0) {
+        Ok(bytes) => bytes,
+        Err(_) => return Ok(()),
+    };
+    let os_string = match OsStringDescriptor::parse(&bytes) {
+        Ok(os_string) => os_string,
+        Err(err) => return writeln!(out, "{}OS String Descriptor: {}", prefix, err),
+    };
+    writeln!(out, "{}OS String Descriptor: Vendor Code: {:#04x}", prefix, os_string.vendor_code)?;
+    let request = ControlRequest {
+        request_type: 0xC0,
+        request: os_string.vendor_code,
+        value: 0,
+        index: ms_os::EXTENDED_COMPAT_ID_INDEX,
+        length: 0,
+    };
+    let compat_id = control_in_sized(backend, hub, port, request, ExtendedCompatId::HEADER_LENGTH)
+        .and_then(|bytes| ExtendedCompatId::parse(&bytes).map_err(invalid_data));
+    let interfaces = match compat_id {
+        Ok(compat_id) => {
+            render::extended_compat_id(out, prefix, &compat_id)?;
+            compat_id.functions.iter().map(|function| function.first_interface).collect()
+        },
+        // the extended properties need control transfers just the same
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => return Ok(()),
+        Err(err) => {
+            writeln!(out, "{}Extended Compat ID: {}", prefix, err)?;
+            vec![0]
+        },
+    };
+    for interface in interfaces {
+        // interface recipient, the interface number in the high byte of wValue
+        let request = ControlRequest {
+            request_type: 0xC1,
+            request: os_string.vendor_code,
+            value: u16::from(interface) << 8,
+            index: ms_os::EXTENDED_PROPERTIES_INDEX,
+            length: 0,
+        };
+        let properties = control_in_sized(backend, hub, port, request, ExtendedProperties::HEADER_LENGTH)
+            .and_then(|bytes| ExtendedProperties::parse(&bytes).map_err(invalid_data));
+        match properties {
+            Ok(properties) => render::extended_properties(out, prefix, interface, &properties)?,
+            Err(err) if err.kind() == io::ErrorKind::Unsupported => {},
+            Err(err) => writeln!(out, "{}Extended Properties, Interface {}: {}", prefix, interface, err)?,
+        }
+    }
+    Ok(())
+}
+
+// read the `header_length` bytes holding dwLength first, then the whole descriptor
+fn control_in_sized(
+    backend: &dyn UsbBackend,
+    hub: &OsStr,
+    port: u8,
+    request: ControlRequest,
+    header_length: usize,
+) -> io::Result<Vec<u8>> {
+    let header = backend.control_in(hub, port, ControlRequest { length: header_length as u16, ..request })?;
+    if header.len() < 4 {
+        return Ok(header)
+    }
+    let length = u32::from_le_bytes([header[0], header[1], header[2], header[3]]).min(u16::MAX.into()) as u16;
+    if usize::from(length) <= header.len() {
+        return Ok(header)
+    }
+    backend.control_in(hub, port, ControlRequest { length, ..request })
+}
+
+fn invalid_data(err: crate::descriptor::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usb_subsystem::mock::{MockBackend, MockDevice, MockHostController, MockHub};
+
+    // USB 2.01 so that a BOS is asked for, iManufacturer 1, iProduct 2
+    const DEVICE: [u8; 18] = [
+        0x12, 0x01, 0x01, 0x02, 0x00, 0x00, 0x00, 0x40, 0x6D, 0x04, 0x1C, 0xC5, 0x00, 0x01, 0x01, 0x02, 0x00, 0x01,
+    ];
+
+    fn tree(backend: &MockBackend, verbose: bool) -> String {
+        let mut out = Vec::new();
+        print_host_controllers(backend, &mut out, &TreeOptions { verbose, ..TreeOptions::default() }).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn prints_empty_ports_and_nested_hubs() {
+        let backend = MockBackend::builder()
+            .host_controller(MockHostController::new("hc0", MockHub::new("rh0", 3)
+                .port(1, MockDevice::new("kbd").description("Keyboard").device_descriptor(DEVICE))
+                .port(2, MockDevice::new("hub1").hub(MockHub::new("hub1", 2)
+                    .port(2, MockDevice::new("disk").device_descriptor(DEVICE))))))
+            .build();
+        assert_eq!(tree(&backend, false), "\
+├ Host Controller Index: 0
+│ (HCD Driver Key Name: hc0)
+│ Id: \"hc0\"
+│ ├ Root hub
+│ │ Name: \"rh0\"
+│ │ Path: hc0/rh
+│ │ ├ Port 1: \"Keyboard\"
+│ │ │ Driver Key: kbd
+│ │ │ Path: hc0/rh/1
+│ │ │ ID: 046d:c51c, Revision: 1.00
+│ │ │ USB Version: 2.01
+│ │ │ Class: 0x00 (Defined at Interface level), SubClass: 0x00, Protocol: 0x00
+│ │ │ Max Packet Size 0: 64
+│ │ │ Configurations: 1
+│ │ ├ Port 2: Hub
+│ │ │ Driver Key: hub1
+│ │ │ Path: hc0/rh/2
+│ │ │ Device Descriptor: device has no such descriptor
+│ │ │ Name: \"hub1\"
+│ │ │ ├ Port 1: (empty)
+│ │ │ │ Path: hc0/rh/2.1
+│ │ │ ├ Port 2: Device
+│ │ │ │ Driver Key: disk
+│ │ │ │ Path: hc0/rh/2.2
+│ │ │ │ ID: 046d:c51c, Revision: 1.00
+│ │ │ │ USB Version: 2.01
+│ │ │ │ Class: 0x00 (Defined at Interface level), SubClass: 0x00, Protocol: 0x00
+│ │ │ │ Max Packet Size 0: 64
+│ │ │ │ Configurations: 1
+│ │ ├ Port 3: (empty)
+│ │ │ Path: hc0/rh/3
+");
+    }
+
+    #[test]
+    fn leaves_out_unsupported_descriptors() {
+        // a missing descriptor is worth a line, one the backend cannot ask for is not
+        let backend = MockBackend::builder()
+            .host_controller(MockHostController::new("hc0", MockHub::new("rh0", 2)
+                .port(1, MockDevice::new("missing").device_descriptor(DEVICE))
+                .port(2, MockDevice::new("unsupported").device_descriptor(DEVICE)
+                    .unsupported_descriptor(descriptor_type::STRING, 0)
+                    .unsupported_descriptor(descriptor_type::STRING, 1)
+                    .unsupported_descriptor(descriptor_type::STRING, 2)
+                    .unsupported_descriptor(descriptor_type::BOS, 0))))
+            .build();
+        let output = tree(&backend, true);
+        let (missing, unsupported) = output.split_at(output.find("Port 2").unwrap());
+        assert!(missing.contains("Languages: device has no such descriptor"), "{}", missing);
+        assert!(missing.contains("BOS: device has no such descriptor"), "{}", missing);
+        assert!(!unsupported.contains("Languages"), "{}", unsupported);
+        assert!(!unsupported.contains("BOS"), "{}", unsupported);
+        assert!(!unsupported.contains("String"), "{}", unsupported);
+        // what could be read is still shown
+        assert!(unsupported.contains("Configuration 0: device has no such descriptor"), "{}", unsupported);
+    }
+
+    #[test]
+    fn prints_strings_without_language_ids() {
+        // sysfs has no string descriptor 0 but still knows some strings
+        let backend = MockBackend::builder()
+            .host_controller(MockHostController::new("hc0", MockHub::new("rh0", 1)
+                .port(1, MockDevice::new("kbd").device_descriptor(DEVICE)
+                    .descriptor(descriptor_type::STRING, 1, string::ENGLISH_UNITED_STATES, *b"\x0A\x03L\0o\0g\0i\0")
+                    .unsupported_descriptor(descriptor_type::STRING, 0)
+                    .unsupported_descriptor(descriptor_type::STRING, 2)
+                    .unsupported_descriptor(descriptor_type::BOS, 0))))
+            .build();
+        let output = tree(&backend, true);
+        assert!(output.contains("String 1 (Manufacturer): \"Logi\""), "{}", output);
+        assert!(!output.contains("Languages"), "{}", output);
+    }
+
+    #[test]
+    fn falls_back_to_the_first_language() {
+        // the preferred English is missing, so the strings are read in German
+        let backend = MockBackend::builder()
+            .host_controller(MockHostController::new("hc0", MockHub::new("rh0", 1)
+                .port(1, MockDevice::new("kbd").device_descriptor(DEVICE)
+                    .descriptor(descriptor_type::STRING, 0, 0, [0x06, 0x03, 0x07, 0x04, 0x0C, 0x04])
+                    .descriptor(descriptor_type::STRING, 1, 0x0407, *b"\x0A\x03L\0o\0g\0i\0")
+                    .descriptor(descriptor_type::STRING, 1, 0x0409, *b"\x0A\x03E\0n\0g\0l\0")
+                    .unsupported_descriptor(descriptor_type::BOS, 0))))
+            .build();
+        let output = tree(&backend, true);
+        assert!(output.contains("Strings in 0x0407 (German (Standard))"), "{}", output);
+        assert!(output.contains("String 1 (Manufacturer): \"Logi\""), "{}", output);
+        assert!(output.contains("String 2 (Product): device has no such descriptor"), "{}", output);
+        assert!(!output.contains("Engl"), "{}", output);
+    }
+
+    #[test]
+    fn asks_the_clock_source_for_sample_rates() {
+        // UAC2: streaming terminal 1 and speaker 3 both run from clock source 5
+        let configuration = [
+            0x09, 0x02, 0x6F, 0x00, 0x02, 0x01, 0x00, 0x80, 0x32,
+            0x09, 0x04, 0x00, 0x00, 0x00, 0x01, 0x01, 0x20, 0x00,
+            0x09, 0x24, 0x01, 0x00, 0x02, 0x08, 0x2E, 0x00, 0x00,
+            0x08, 0x24, 0x0A, 0x05, 0x01, 0x07, 0x00, 0x00,
+            0x11, 0x24, 0x02, 0x01, 0x01, 0x01, 0x00, 0x05, 0x02, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x0C, 0x24, 0x03, 0x03, 0x01, 0x03, 0x00, 0x01, 0x05, 0x00, 0x00, 0x00,
+            0x09, 0x04, 0x01, 0x00, 0x00, 0x01, 0x02, 0x20, 0x00,
+            0x09, 0x04, 0x01, 0x01, 0x01, 0x01, 0x02, 0x20, 0x00,
+            0x10, 0x24, 0x01, 0x01, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x02, 0x03, 0x00, 0x00, 0x00, 0x00,
+            0x06, 0x24, 0x02, 0x01, 0x02, 0x10,
+            0x07, 0x05, 0x01, 0x05, 0xC8, 0x00, 0x01,
+        ];
+        // wNumSubRanges 2: 44100 and 48000 Hz
+        let ranges = [
+            0x02, 0x00,
+            0x44, 0xAC, 0x00, 0x00, 0x44, 0xAC, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x80, 0xBB, 0x00, 0x00, 0x80, 0xBB, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        // RANGE of the sampling frequency control, clock source 5 of interface 0
+        let backend = MockBackend::builder()
+            .host_controller(MockHostController::new("hc0", MockHub::new("rh0", 1)
+                .port(1, MockDevice::new("dac").device_descriptor(DEVICE)
+                    .configuration(configuration)
+                    .control_in(0xA1, 0x02, 0x0100, 0x0500, ranges))))
+            .build();
+        let output = tree(&backend, true);
+        assert!(output.contains("Clock Source 5: 44100/48000 Hz"), "{}", output);
+        assert!(!output.contains("does not answer"), "{}", output);
+    }
+}
@@ -1,9 +1,297 @@
-#![allow(unused)]
+// Location of a hub or device in the USB tree, independent of the names the
+// operating system gives them: `hc0/rh` is the root hub of host controller 0,
+// `hc0/rh/3.2.1` the device on port 1 of the hub on port 2 of the hub on
+// port 3 of that root hub. Paths order depth first, the way the tree prints.
+use core::fmt;
+use std::error;
 use std::ffi::OsStr;
+use std::str::FromStr;
 
-pub enum Component<'a> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
     Root,
-    HostController(&'a OsStr),
-    Hub(&'a OsStr),
-    Device(&'a OsStr)
+    HostController(usize),
+    RootHub,
+    Port(u8), // numbered from 1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UsbPath {
+    host_controller: usize,
+    ports: Vec<u8>, // from the root hub down
+}
+
+// a device picked on the command line, either by path or by its Linux name;
+// which host controller a Linux bus number means depends on the buses present
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceSelector {
+    Path(UsbPath),
+    Linux { bus: u32, ports: Vec<u8> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseUsbPathError {
+    input: String,
+    reason: &'static str,
+}
+
+impl UsbPath {
+    pub fn root_hub(host_controller: usize) -> Self {
+        Self { host_controller, ports: Vec::new() }
+    }
+
+    // the path of whatever is plugged into `port` of the hub at this path
+    pub fn join(&self, port: u8) -> Self {
+        let mut ports = self.ports.clone();
+        ports.push(port);
+        Self { host_controller: self.host_controller, ports }
+    }
+
+    // the hub this path hangs off, None for a root hub
+    pub fn parent(&self) -> Option<Self> {
+        let (_, ports) = self.ports.split_last()?;
+        Some(Self { host_controller: self.host_controller, ports: ports.to_vec() })
+    }
+
+    pub fn host_controller(&self) -> usize {
+        self.host_controller
+    }
+
+    pub fn ports(&self) -> &[u8] {
+        &self.ports
+    }
+
+    pub fn is_root_hub(&self) -> bool {
+        self.ports.is_empty()
+    }
+
+    // whether `other` is this path or lies below it
+    pub fn contains(&self, other: &UsbPath) -> bool {
+        self.host_controller == other.host_controller && other.ports.starts_with(&self.ports)
+    }
+
+    pub fn components(&self) -> impl Iterator<Item = Component> + '_ {
+        [Component::Root, Component::HostController(self.host_controller), Component::RootHub].into_iter()
+            .chain(self.ports.iter().map(|&port| Component::Port(port)))
+    }
+
+    // the inverse of `components`; the leading `Root` may be left out
+    pub fn from_components(components: impl IntoIterator<Item = Component>) -> Result<Self, ParseUsbPathError> {
+        let mut components = components.into_iter().peekable();
+        let error = |reason| ParseUsbPathError { input: String::new(), reason };
+        components.next_if_eq(&Component::Root);
+        let host_controller = match components.next() {
+            Some(Component::HostController(index)) => index,
+            _ => return Err(error("expected a host controller")),
+        };
+        if components.next() != Some(Component::RootHub) {
+            return Err(error("expected the root hub after the host controller"))
+        }
+        let mut ports = Vec::new();
+        for component in components {
+            match component {
+                Component::Port(0) => return Err(error("ports are numbered from 1")),
+                Component::Port(port) => ports.push(port),
+                _ => return Err(error("expected a port")),
+            }
+        }
+        Ok(Self { host_controller, ports })
+    }
+
+    // Linux names root hubs `usbB` and devices `B-P.P.P` after the bus number
+    // of the root hub, `bus`; buses need not be contiguous, so this does not
+    // follow from the host controller index
+    pub fn to_linux(&self, bus: u32) -> String {
+        match self.ports.split_first() {
+            None => format!("usb{}", bus),
+            Some((first, rest)) => {
+                let mut name = format!("{}-{}", bus, first);
+                for port in rest {
+                    name.push_str(&format!(".{}", port));
+                }
+                name
+            },
+        }
+    }
+}
+
+impl DeviceSelector {
+    // `hcN/rh/P.P` selects by path, anything else is taken as a Linux name
+    pub fn parse(input: &str) -> Result<Self, ParseUsbPathError> {
+        match input.starts_with("hc") {
+            true => input.parse().map(DeviceSelector::Path),
+            false => Self::from_linux(input),
+        }
+    }
+
+    pub fn from_linux(name: &str) -> Result<Self, ParseUsbPathError> {
+        let error = |reason| ParseUsbPathError { input: name.to_owned(), reason };
+        let (bus, ports) = match name.strip_prefix("usb") {
+            Some(bus) => (bus, None),
+            None => match name.split_once('-') {
+                Some((bus, ports)) => (bus, Some(ports)),
+                None => return Err(error("expected `usbB` or `B-P.P`")),
+            },
+        };
+        let bus = match bus.parse::<u32>() {
+            Ok(bus) if bus > 0 => bus,
+            _ => return Err(error("invalid bus number")),
+        };
+        let ports = match ports {
+            Some(ports) => parse_ports(ports).map_err(error)?,
+            None => Vec::new(),
+        };
+        Ok(DeviceSelector::Linux { bus, ports })
+    }
+
+    // the selected path if it lies on host controller `host_controller`, whose
+    // root hub the backend calls `root_hub_name`
+    pub fn resolve(&self, host_controller: usize, root_hub_name: &OsStr) -> Option<UsbPath> {
+        match self {
+            DeviceSelector::Path(path) if path.host_controller == host_controller => Some(path.clone()),
+            DeviceSelector::Linux { bus, ports } if linux_bus(root_hub_name) == Some(*bus) => {
+                Some(UsbPath { host_controller, ports: ports.clone() })
+            },
+            _ => None,
+        }
+    }
+}
+
+// the bus number of a Linux root hub name, `usbB`
+pub fn linux_bus(root_hub_name: &OsStr) -> Option<u32> {
+    root_hub_name.to_str()?
+        .strip_prefix("usb")?
+        .parse()
+        .ok()
+        .filter(|&bus| bus > 0)
+}
+
+impl fmt::Display for UsbPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "hc{}/rh", self.host_controller)?;
+        for (index, port) in self.ports.iter().enumerate() {
+            write!(f, "{}{}", if index == 0 { "/" } else { "." }, port)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for UsbPath {
+    type Err = ParseUsbPathError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = |reason| ParseUsbPathError { input: input.to_owned(), reason };
+        let mut parts = input.splitn(3, '/');
+        let host_controller = parts.next()
+            .and_then(|part| part.strip_prefix("hc"))
+            .and_then(|index| index.parse().ok())
+            .ok_or_else(|| error("expected `hcN` first"))?;
+        if parts.next() != Some("rh") {
+            return Err(error("expected `rh` after the host controller"))
+        }
+        let ports = match parts.next() {
+            Some(ports) => parse_ports(ports).map_err(error)?,
+            None => Vec::new(),
+        };
+        Ok(Self { host_controller, ports })
+    }
+}
+
+impl fmt::Display for ParseUsbPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.input.is_empty() {
+            true => write!(f, "invalid USB path: {}", self.reason),
+            false => write!(f, "invalid USB path {:?}: {}", self.input, self.reason),
+        }
+    }
+}
+
+impl error::Error for ParseUsbPathError {}
+
+// a dot separated port chain such as `3.2.1`
+fn parse_ports(ports: &str) -> Result<Vec<u8>, &'static str> {
+    ports.split('.')
+        .map(|port| match port.parse::<u8>() {
+            Ok(0) => Err("ports are numbered from 1"),
+            Ok(port) => Ok(port),
+            Err(_) => Err("invalid port number"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(input: &str) -> UsbPath {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for input in ["hc0/rh", "hc0/rh/3.2.1", "hc12/rh/7"] {
+            assert_eq!(path(input).to_string(), input);
+        }
+        assert_eq!(path("hc0/rh/3.2.1"), UsbPath::root_hub(0).join(3).join(2).join(1));
+        assert_eq!(path("hc0/rh/3.2.1").parent(), Some(path("hc0/rh/3.2")));
+        assert_eq!(path("hc0/rh").parent(), None);
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        for (input, reason) in [
+            ("hc0/rh/", "invalid port number"),
+            ("hc0/rh/3.0", "ports are numbered from 1"),
+            ("hc0/rh/3..1", "invalid port number"),
+            ("hc0/rh/256", "invalid port number"),
+            ("hc0/x", "expected `rh` after the host controller"),
+            ("hc0", "expected `rh` after the host controller"),
+            ("hcx/rh", "expected `hcN` first"),
+            ("rh/1", "expected `hcN` first"),
+        ] {
+            assert_eq!(input.parse::<UsbPath>(), Err(ParseUsbPathError { input: input.to_owned(), reason }));
+        }
+    }
+
+    #[test]
+    fn linux_names() {
+        assert_eq!(DeviceSelector::parse("usb1"), Ok(DeviceSelector::Linux { bus: 1, ports: vec![] }));
+        assert_eq!(DeviceSelector::parse("1-3.2.1"), Ok(DeviceSelector::Linux { bus: 1, ports: vec![3, 2, 1] }));
+        assert_eq!(DeviceSelector::parse("hc0/rh/3"), Ok(DeviceSelector::Path(path("hc0/rh/3"))));
+        for input in ["usb0", "1-0", "1-", "1", "x-1"] {
+            assert!(DeviceSelector::parse(input).is_err(), "{}", input);
+        }
+        assert_eq!(path("hc0/rh").to_linux(1), "usb1");
+        assert_eq!(path("hc0/rh/3.2.1").to_linux(1), "1-3.2.1");
+        assert_eq!(linux_bus(OsStr::new("usb3")), Some(3));
+        assert_eq!(linux_bus(OsStr::new("usb0")), None);
+        assert_eq!(linux_bus(OsStr::new("USB#ROOT_HUB30")), None);
+    }
+
+    #[test]
+    fn resolves_non_contiguous_buses() {
+        // bus 1 is gone, so bus 2 is host controller 0 and bus 4 controller 1
+        let selector = DeviceSelector::parse("4-2.1").unwrap();
+        assert_eq!(selector.resolve(0, OsStr::new("usb2")), None);
+        assert_eq!(selector.resolve(1, OsStr::new("usb4")), Some(path("hc1/rh/2.1")));
+        let selector = DeviceSelector::parse("usb2").unwrap();
+        assert_eq!(selector.resolve(0, OsStr::new("usb2")), Some(path("hc0/rh")));
+        // paths select by index whatever the root hub is called
+        let selector = DeviceSelector::parse("hc1/rh/2").unwrap();
+        assert_eq!(selector.resolve(0, OsStr::new("usb2")), None);
+        assert_eq!(selector.resolve(1, OsStr::new("usb4")), Some(path("hc1/rh/2")));
+    }
+
+    #[test]
+    fn orders_depth_first() {
+        let mut paths = [
+            path("hc1/rh"), path("hc0/rh/4"), path("hc0/rh/3.2"), path("hc0/rh"), path("hc0/rh/3"),
+            path("hc0/rh/3.10"), path("hc0/rh/3.2.1"),
+        ];
+        paths.sort();
+        let paths: Vec<_> = paths.iter().map(UsbPath::to_string).collect();
+        assert_eq!(paths, [
+            "hc0/rh", "hc0/rh/3", "hc0/rh/3.2", "hc0/rh/3.2.1", "hc0/rh/3.10", "hc0/rh/4", "hc1/rh",
+        ]);
+    }
 }
@@ -0,0 +1,195 @@
+// Platform-neutral view of the USB subsystem. Every enumeration goes through
+// a `UsbBackend`; the operating system specific code lives in the submodules.
+pub mod linux;
+pub mod mock;
+pub mod replay;
+#[cfg(windows)]
+pub mod windows;
+
+use core::fmt;
+use std::ffi::{OsStr, OsString};
+use std::io;
+
+pub trait UsbBackend {
+    // enumerate usb host controllers
+    fn host_controllers(&self) -> io::Result<HostControllers<'_>>;
+
+    // all usb hubs present, including root hubs
+    fn hubs(&self) -> io::Result<Vec<DeviceNode>>;
+
+    // all usb devices present
+    fn devices(&self) -> io::Result<Vec<DeviceNode>>;
+
+    // connection state of every downstream port of `hub`
+    fn hub_ports(&self, hub: &OsStr) -> io::Result<Vec<PortConnection>>;
+
+    // the raw hub or SuperSpeed hub descriptor of `hub` itself
+    fn hub_descriptor(&self, hub: &OsStr) -> io::Result<Vec<u8>> {
+        let _ = hub;
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "hub descriptors are not supported by this backend",
+        ))
+    }
+
+    // read a raw descriptor from the device connected to `port` of `hub`
+    fn descriptor(
+        &self,
+        hub: &OsStr,
+        port: u8,
+        descriptor_type: u8,
+        index: u8,
+        language_id: u16,
+    ) -> io::Result<Vec<u8>>;
+
+    // an IN control transfer to the device connected to `port` of `hub`, for the
+    // class and vendor requests GET_DESCRIPTOR does not cover
+    fn control_in(&self, hub: &OsStr, port: u8, request: ControlRequest) -> io::Result<Vec<u8>> {
+        let _ = (hub, port, request);
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "control requests are not supported by this backend",
+        ))
+    }
+
+    // plug and play properties of the device owning `driver_key`
+    fn device_properties(&self, driver_key: &DriverKey) -> io::Result<Option<DevicePnpStrings>>;
+}
+
+// the backend for the platform usb-z is running on
+pub fn default_backend() -> io::Result<Box<dyn UsbBackend>> {
+    #[cfg(windows)]
+    return Ok(Box::new(windows::WindowsBackend::new()));
+    #[cfg(target_os = "linux")]
+    return Ok(Box::new(linux::SysfsBackend::new()));
+    #[cfg(not(any(windows, target_os = "linux")))]
+    return Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "no usb backend for this platform",
+    ));
+}
+
+#[derive(Debug, Clone)]
+pub struct HostController {
+    pub path: OsString,
+    pub driver_key: DriverKey,
+    pub root_hub_name: OsString,
+}
+
+// host controller enumerator shared by all backends
+pub struct HostControllers<'a> {
+    inner: Box<dyn Iterator<Item = io::Result<HostController>> + 'a>,
+}
+
+impl<'a> HostControllers<'a> {
+    pub fn new(iter: impl Iterator<Item = io::Result<HostController>> + 'a) -> Self {
+        Self { inner: Box::new(iter) }
+    }
+}
+
+impl Iterator for HostControllers<'_> {
+    type Item = io::Result<HostController>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl fmt::Debug for HostControllers<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostControllers").finish_non_exhaustive()
+    }
+}
+
+// setup packet of an IN control transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ControlRequest {
+    pub request_type: u8, // bmRequestType, direction bit set
+    pub request: u8,
+    pub value: u16,
+    pub index: u16,
+    pub length: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct PortConnection {
+    pub port: u8,
+    pub device: Option<ConnectedDevice>, // None if nothing is plugged in
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectedDevice {
+    pub driver_key: Option<DriverKey>,
+    pub speed: Option<Speed>,
+    pub hub_name: Option<OsString>, // set if the device is itself a hub
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Speed {
+    Low,
+    Full,
+    High,
+    Super,
+    SuperPlus,
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Speed::Low => "Low (1.5 Mbps)",
+            Speed::Full => "Full (12 Mbps)",
+            Speed::High => "High (480 Mbps)",
+            Speed::Super => "Super (5 Gbps)",
+            Speed::SuperPlus => "SuperPlus (10 Gbps+)",
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct DriverKey {
+    name: OsString,
+}
+
+impl DriverKey {
+    pub fn as_os_str(&self) -> &OsStr {
+        &self.name
+    }
+}
+
+impl From<OsString> for DriverKey {
+    fn from(name: OsString) -> Self {
+        Self { name }
+    }
+}
+
+impl fmt::Debug for DriverKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &self.name.to_string_lossy())
+    }
+}
+
+#[derive(Clone)]
+pub struct DeviceNode {
+    pub device_desc_name: Option<OsString>,
+    pub device_driver_name: Option<OsString>,
+    pub device_path: OsString,
+}
+
+impl fmt::Debug for DeviceNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeviceNode")
+            .field("desc", &self.device_desc_name)
+            .field("driver", &self.device_driver_name)
+            .field("path", &self.device_path)
+            .finish()
+    }
+}
+
+// We may enumerate more about this device here
+#[derive(Debug, Clone)]
+pub struct DevicePnpStrings {
+    pub device_id: OsString,
+    pub device_desc: Option<OsString>,
+    pub device_hw_id: Option<OsString>,
+    pub service: Option<OsString>,
+    pub device_class: Option<OsString>,
+}
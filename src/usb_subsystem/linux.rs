@@ -0,0 +1,543 @@
+use crate::descriptor::descriptor_type;
+use super::{
+    ConnectedDevice, ControlRequest, DeviceNode, DevicePnpStrings, DriverKey, HostController, HostControllers,
+    PortConnection, Speed, UsbBackend,
+};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// sysfs based backend; root hubs are `usbN`, devices are `B-P.P.P` and
+// interfaces (`B-P.P:C.I`) are skipped
+#[derive(Debug, Clone)]
+pub struct SysfsBackend {
+    root: PathBuf,
+    usbfs_root: Option<PathBuf>, // device nodes for control transfers, None to never issue any
+}
+
+impl SysfsBackend {
+    pub fn new() -> Self {
+        Self::with_root("/sys").usbfs_root("/dev/bus/usb")
+    }
+
+    // use a sysfs tree mounted somewhere else, e.g. a fake tree for testing.
+    // Its busnum and devnum need not match the live devices, so control
+    // transfers stay off until `usbfs_root` is given.
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into(), usbfs_root: None }
+    }
+
+    // the usbfs directory holding `BBB/DDD` nodes for the devices of this tree
+    pub fn usbfs_root(mut self, usbfs_root: impl Into<PathBuf>) -> Self {
+        self.usbfs_root = Some(usbfs_root.into());
+        self
+    }
+
+    fn devices_dir(&self) -> PathBuf {
+        self.root.join("bus").join("usb").join("devices")
+    }
+
+    fn device_dir(&self, name: &OsStr) -> PathBuf {
+        self.devices_dir().join(name)
+    }
+
+    // names of all usb devices (root hubs included), sorted
+    fn device_names(&self) -> io::Result<Vec<OsString>> {
+        let mut names = Vec::new();
+        let entries = match fs::read_dir(self.devices_dir()) {
+            Ok(entries) => entries,
+            // no usb bus registered at all
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(names),
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let name = entry?.file_name();
+            if !name.to_string_lossy().contains(':') {
+                names.push(name);
+            }
+        }
+        names.sort_by_key(|name| sort_key(name));
+        Ok(names)
+    }
+
+    fn attribute(&self, name: &OsStr, attribute: &str) -> io::Result<String> {
+        let contents = fs::read_to_string(self.device_dir(name).join(attribute))?;
+        Ok(contents.trim_end().to_owned())
+    }
+
+    fn optional_attribute(&self, name: &OsStr, attribute: &str) -> Option<String> {
+        self.attribute(name, attribute).ok()
+    }
+
+    // sysfs directory of the host controller owning root hub `name`
+    fn controller_dir(&self, root_hub: &OsStr) -> io::Result<PathBuf> {
+        let root_hub_dir = fs::canonicalize(self.device_dir(root_hub))?;
+        root_hub_dir.parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "root hub without parent device"))
+    }
+
+    fn is_hub(&self, name: &OsStr) -> bool {
+        self.optional_attribute(name, "bDeviceClass").as_deref() == Some("09")
+    }
+
+    fn device_node(&self, name: &OsStr) -> DeviceNode {
+        DeviceNode {
+            device_desc_name: self.optional_attribute(name, "product").map(OsString::from),
+            device_driver_name: driver_name(&self.device_dir(name)),
+            device_path: self.device_dir(name).into_os_string(),
+        }
+    }
+
+    // the device plugged into `port` of `hub`, if any
+    fn child(&self, hub: &OsStr, port: u8) -> Option<OsString> {
+        let hub = hub.to_string_lossy();
+        let name = match hub.strip_prefix("usb") {
+            Some(bus) => format!("{}-{}", bus, port),
+            None => format!("{}.{}", hub, port),
+        };
+        let name = OsString::from(name);
+        if self.device_dir(&name).is_dir() {
+            Some(name)
+        } else {
+            None
+        }
+    }
+
+    // report descriptor of HID interface `interface` of the active configuration,
+    // e.g. `1-1.1/1-1.1:1.0/0003:046D:C31C.0001/report_descriptor`
+    fn report_descriptor(&self, name: &OsStr, interface: u16) -> io::Result<Vec<u8>> {
+        let configuration = self.attribute(name, "bConfigurationValue")?;
+        let interface_dir = self.device_dir(name)
+            .join(format!("{}:{}.{}", name.to_string_lossy(), configuration, interface));
+        for entry in fs::read_dir(interface_dir)? {
+            let path = entry?.path().join("report_descriptor");
+            if path.is_file() {
+                return fs::read(path)
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::NotFound, "interface has no HID report descriptor"))
+    }
+
+    // the usbfs node of device `name`, opened for control transfers. Without
+    // one, e.g. when not running as root or on a custom sysfs root, control
+    // transfers are unsupported rather than failing.
+    fn usbfs(&self, name: &OsStr) -> io::Result<fs::File> {
+        let unsupported = |err: &dyn std::fmt::Display| io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("control transfers need usbfs: {}", err),
+        );
+        let usbfs_root = self.usbfs_root.as_ref()
+            .ok_or_else(|| unsupported(&"not used with a custom sysfs root"))?;
+        let number = |attribute| -> io::Result<u32> {
+            self.attribute(name, attribute)?
+                .parse()
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+        };
+        let (bus, device) = match (number("busnum"), number("devnum")) {
+            (Ok(bus), Ok(device)) => (bus, device),
+            (Err(err), _) | (_, Err(err)) => return Err(unsupported(&err)),
+        };
+        fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(usbfs_root.join(format!("{:03}", bus)).join(format!("{:03}", device)))
+            .map_err(|err| unsupported(&err))
+    }
+
+    // a descriptor sysfs does not keep, read from the device through usbfs
+    fn usbfs_descriptor(&self, name: &OsStr, descriptor_type: u8, index: u8, language_id: u16) -> io::Result<Vec<u8>> {
+        let device = self.usbfs(name)?;
+        let request = |length| ControlRequest {
+            request_type: 0x80, // device to host, standard, device
+            request: 0x06, // GET_DESCRIPTOR
+            value: u16::from(descriptor_type) << 8 | u16::from(index),
+            index: language_id,
+            length,
+        };
+        let data = usbfs_control_in(&device, request(u8::MAX.into()))?;
+        match descriptor_type {
+            // other speed configuration and BOS carry wTotalLength
+            0x07 | 0x0F if data.len() >= 4 => {
+                let total_length = u16::from_le_bytes([data[2], data[3]]);
+                if usize::from(total_length) > data.len() {
+                    return usbfs_control_in(&device, request(total_length))
+                }
+                Ok(data)
+            },
+            _ => Ok(data),
+        }
+    }
+
+    // the manufacturer, product and serial number strings the kernel read when
+    // the device enumerated, rebuilt as string descriptors. sysfs does not say
+    // which language they are in, so there is no descriptor 0 and `language_id`
+    // is ignored.
+    fn string_descriptor(&self, name: &OsStr, device: &[u8], index: u8) -> io::Result<Vec<u8>> {
+        let unsupported = || io::Error::new(
+            io::ErrorKind::Unsupported,
+            "sysfs only exposes the manufacturer, product and serial strings",
+        );
+        if index == 0 || device.len() < 17 {
+            return Err(unsupported())
+        }
+        // iManufacturer, iProduct and iSerialNumber
+        let attribute = match device[14..17].iter().position(|&string_index| string_index == index) {
+            Some(0) => "manufacturer",
+            Some(1) => "product",
+            Some(_) => "serial",
+            None => return Err(unsupported()),
+        };
+        let text = self.attribute(name, attribute)?;
+        let mut bytes = vec![0, descriptor_type::STRING];
+        for unit in text.encode_utf16().take((usize::from(u8::MAX) - 2) / 2) {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        bytes[0] = bytes.len() as u8;
+        Ok(bytes)
+    }
+}
+
+impl Default for SysfsBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsbBackend for SysfsBackend {
+    fn host_controllers(&self) -> io::Result<HostControllers<'_>> {
+        let root_hubs: Vec<_> = self.device_names()?
+            .into_iter()
+            .filter(|name| is_root_hub(name))
+            .collect();
+        let host_controllers = root_hubs.into_iter().map(move |root_hub_name| {
+            let controller_dir = self.controller_dir(&root_hub_name)?;
+            let driver_key = controller_dir.file_name()
+                .map(|name| DriverKey::from(name.to_owned()))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host controller without name"))?;
+            Ok(HostController {
+                path: controller_dir.into_os_string(),
+                driver_key,
+                root_hub_name,
+            })
+        });
+        Ok(HostControllers::new(host_controllers))
+    }
+
+    fn hubs(&self) -> io::Result<Vec<DeviceNode>> {
+        Ok(self.device_names()?
+            .iter()
+            .filter(|name| self.is_hub(name))
+            .map(|name| self.device_node(name))
+            .collect())
+    }
+
+    fn devices(&self) -> io::Result<Vec<DeviceNode>> {
+        Ok(self.device_names()?
+            .iter()
+            .filter(|name| !is_root_hub(name))
+            .map(|name| self.device_node(name))
+            .collect())
+    }
+
+    fn hub_ports(&self, hub: &OsStr) -> io::Result<Vec<PortConnection>> {
+        let port_count: u8 = self.attribute(hub, "maxchild")?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let ports = (1..=port_count).map(|port| {
+            let device = self.child(hub, port).map(|name| ConnectedDevice {
+                speed: self.optional_attribute(&name, "speed").as_deref().and_then(parse_speed),
+                hub_name: if self.is_hub(&name) { Some(name.clone()) } else { None },
+                driver_key: Some(DriverKey::from(name)),
+            });
+            PortConnection { port, device }
+        });
+        Ok(ports.collect())
+    }
+
+    // sysfs does not keep hub descriptors, so ask the hub through its own usbfs node
+    fn hub_descriptor(&self, hub: &OsStr) -> io::Result<Vec<u8>> {
+        let device = self.usbfs(hub)?;
+        let super_speed = self.optional_attribute(hub, "speed").as_deref().and_then(parse_speed) >= Some(Speed::Super);
+        let descriptor_type = match super_speed {
+            true => descriptor_type::SUPERSPEED_HUB,
+            false => descriptor_type::HUB,
+        };
+        let request = ControlRequest {
+            request_type: 0xA0, // device to host, class, device
+            request: 0x06, // GET_DESCRIPTOR
+            value: u16::from(descriptor_type) << 8,
+            index: 0,
+            length: u8::MAX.into(),
+        };
+        usbfs_control_in(&device, request)
+    }
+
+    fn descriptor(
+        &self,
+        hub: &OsStr,
+        port: u8,
+        descriptor_type: u8,
+        index: u8,
+        language_id: u16,
+    ) -> io::Result<Vec<u8>> {
+        let name = self.child(hub, port)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no device connected to port"))?;
+        // `descriptors` holds the device descriptor followed by every configuration
+        let descriptors = fs::read(self.device_dir(&name).join("descriptors"))?;
+        let device_length = descriptors.first().map_or(0, |&len| usize::from(len));
+        if descriptors.len() < device_length || device_length == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated descriptors file"))
+        }
+        match descriptor_type {
+            0x01 => Ok(descriptors[..device_length].to_vec()),
+            0x02 => {
+                let mut rest = &descriptors[device_length..];
+                for current in 0..=index {
+                    if rest.len() < 4 {
+                        break;
+                    }
+                    let total_length = usize::from(u16::from_le_bytes([rest[2], rest[3]])).min(rest.len());
+                    if current == index {
+                        return Ok(rest[..total_length].to_vec())
+                    }
+                    rest = &rest[total_length.max(1)..];
+                }
+                Err(io::Error::new(io::ErrorKind::NotFound, "no such configuration"))
+            },
+            // ask the device when usbfs allows, so every string in every language can be read
+            0x03 => match self.usbfs_descriptor(&name, descriptor_type, index, language_id) {
+                Err(err) if err.kind() == io::ErrorKind::Unsupported => {
+                    self.string_descriptor(&name, &descriptors[..device_length], index)
+                },
+                result => result,
+            },
+            0x22 => self.report_descriptor(&name, language_id),
+            // the device qualifier, other speed configurations, BOS and debug
+            // descriptors are not in sysfs
+            _ => self.usbfs_descriptor(&name, descriptor_type, index, language_id),
+        }
+    }
+
+    fn control_in(&self, hub: &OsStr, port: u8, request: ControlRequest) -> io::Result<Vec<u8>> {
+        let name = self.child(hub, port)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no device connected to port"))?;
+        usbfs_control_in(&self.usbfs(&name)?, request)
+    }
+
+    fn device_properties(&self, driver_key: &DriverKey) -> io::Result<Option<DevicePnpStrings>> {
+        let name = driver_key.as_os_str();
+        if self.device_dir(name).is_dir() {
+            let hw_id = match (self.optional_attribute(name, "idVendor"), self.optional_attribute(name, "idProduct")) {
+                (Some(vendor), Some(product)) => Some(OsString::from(format!("usb:v{}p{}", vendor, product))),
+                _ => None,
+            };
+            return Ok(Some(DevicePnpStrings {
+                device_id: name.to_owned(),
+                device_desc: self.optional_attribute(name, "product").map(OsString::from),
+                device_hw_id: hw_id,
+                service: driver_name(&self.device_dir(name)),
+                device_class: self.optional_attribute(name, "bDeviceClass").map(OsString::from),
+            }))
+        }
+        // otherwise this is the name of a host controller
+        for root_hub in self.device_names()?.into_iter().filter(|name| is_root_hub(name)) {
+            let controller_dir = self.controller_dir(&root_hub)?;
+            if controller_dir.file_name() != Some(name) {
+                continue;
+            }
+            return Ok(Some(DevicePnpStrings {
+                device_id: name.to_owned(),
+                device_desc: self.optional_attribute(&root_hub, "product").map(OsString::from),
+                device_hw_id: fs::read_to_string(controller_dir.join("modalias")).ok()
+                    .map(|modalias| OsString::from(modalias.trim_end())),
+                service: driver_name(&controller_dir),
+                device_class: None,
+            }))
+        }
+        Ok(None)
+    }
+}
+
+// an IN control transfer with USBDEVFS_CONTROL
+#[cfg(target_os = "linux")]
+fn usbfs_control_in(device: &fs::File, request: ControlRequest) -> io::Result<Vec<u8>> {
+    use std::os::unix::io::AsRawFd;
+    // struct usbdevfs_ctrltransfer
+    #[repr(C)]
+    struct CtrlTransfer {
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        length: u16,
+        timeout: u32, // ms
+        data: *mut libc::c_void,
+    }
+    // _IOWR('U', 0, struct usbdevfs_ctrltransfer)
+    const USBDEVFS_CONTROL: u32 = 3 << 30 | (core::mem::size_of::<CtrlTransfer>() as u32) << 16 | (b'U' as u32) << 8;
+    let mut buf = vec![0u8; usize::from(request.length)];
+    let mut transfer = CtrlTransfer {
+        request_type: request.request_type,
+        request: request.request,
+        value: request.value,
+        index: request.index,
+        length: request.length,
+        timeout: 1000,
+        data: buf.as_mut_ptr().cast(),
+    };
+    let transferred = unsafe { libc::ioctl(device.as_raw_fd(), USBDEVFS_CONTROL as _, &mut transfer) };
+    if transferred < 0 {
+        return Err(io::Error::last_os_error())
+    }
+    buf.truncate(transferred as usize);
+    Ok(buf)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn usbfs_control_in(_device: &fs::File, _request: ControlRequest) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "usbfs is only available on Linux"))
+}
+
+fn is_root_hub(name: &OsStr) -> bool {
+    name.to_string_lossy()
+        .strip_prefix("usb")
+        .is_some_and(|bus| !bus.is_empty() && bus.bytes().all(|b| b.is_ascii_digit()))
+}
+
+// name of the kernel driver bound to the device at `dir`
+fn driver_name(dir: &Path) -> Option<OsString> {
+    fs::read_link(dir.join("driver")).ok()
+        .and_then(|target| target.file_name().map(OsStr::to_owned))
+}
+
+fn parse_speed(speed: &str) -> Option<Speed> {
+    match speed {
+        "1.5" => Some(Speed::Low),
+        "12" => Some(Speed::Full),
+        "480" => Some(Speed::High),
+        "5000" => Some(Speed::Super),
+        "10000" | "20000" => Some(Speed::SuperPlus),
+        _ => None,
+    }
+}
+
+// order root hubs by bus number and devices by bus then port chain
+fn sort_key(name: &OsStr) -> (u32, Vec<u32>) {
+    let name = name.to_string_lossy();
+    if let Some(bus) = name.strip_prefix("usb") {
+        return (bus.parse().unwrap_or(u32::MAX), Vec::new())
+    }
+    let (bus, ports) = name.split_once('-').unwrap_or((&name, ""));
+    let ports = ports.split('.').map(|port| port.parse().unwrap_or(u32::MAX)).collect();
+    (bus.parse().unwrap_or(u32::MAX), ports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::descriptor::{configuration::Configuration, string};
+
+    // usb1 has a hub at 1-1 with a keyboard on port 1 and an ACM gadget on
+    // port 3, usb2 a UAS bridge at 2-2
+    fn backend() -> SysfsBackend {
+        SysfsBackend::with_root(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/sysfs"))
+    }
+
+    // (port, device name, is a hub, speed) of every port of `hub`
+    fn ports(hub: &str) -> Vec<(u8, Option<String>, bool, Option<Speed>)> {
+        backend().hub_ports(OsStr::new(hub)).unwrap()
+            .into_iter()
+            .map(|connection| match connection.device {
+                Some(device) => (
+                    connection.port,
+                    device.driver_key.map(|key| key.as_os_str().to_string_lossy().into_owned()),
+                    device.hub_name.is_some(),
+                    device.speed,
+                ),
+                None => (connection.port, None, false, None),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn host_controllers() {
+        let backend = backend();
+        let host_controllers: Vec<_> = backend.host_controllers().unwrap()
+            .map(Result::unwrap)
+            .map(|hc| (hc.root_hub_name, hc.driver_key.as_os_str().to_owned()))
+            .collect();
+        assert_eq!(host_controllers, [
+            (OsString::from("usb1"), OsString::from("xhci-hcd.0.auto")),
+            (OsString::from("usb2"), OsString::from("xhci-hcd.0.auto")),
+        ]);
+    }
+
+    #[test]
+    fn root_hub_ports() {
+        assert_eq!(ports("usb1"), [
+            (1, Some("1-1".into()), true, Some(Speed::High)),
+            (2, None, false, None),
+            (3, None, false, None),
+            (4, None, false, None),
+        ]);
+    }
+
+    #[test]
+    fn hub_ports() {
+        assert_eq!(ports("1-1"), [
+            (1, Some("1-1.1".into()), false, Some(Speed::Low)),
+            (2, None, false, None),
+            (3, Some("1-1.3".into()), false, Some(Speed::Full)),
+            (4, None, false, None),
+        ]);
+        // a device that is not a hub has no ports
+        assert!(ports("1-1.1").is_empty());
+    }
+
+    #[test]
+    fn configuration() {
+        let backend = backend();
+        let hub = OsStr::new("1-1");
+        let bytes = backend.descriptor(hub, 1, descriptor_type::CONFIGURATION, 0, 0).unwrap();
+        assert_eq!(bytes.len(), 59);
+        let configuration = Configuration::parse(&bytes).unwrap();
+        assert!(configuration.problems.is_empty(), "{:?}", configuration.problems);
+        assert_eq!(configuration.interfaces.len(), 2);
+        let err = backend.descriptor(hub, 1, descriptor_type::CONFIGURATION, 1, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        let err = backend.descriptor(hub, 2, descriptor_type::DEVICE, 0, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn strings() {
+        let backend = backend();
+        let read = |index| backend.descriptor(OsStr::new("1-1"), 1, descriptor_type::STRING, index, 0x0409)
+            .map(|bytes| string::parse_string(&bytes).unwrap());
+        assert_eq!(read(1).unwrap(), "Logitech");
+        assert_eq!(read(2).unwrap(), "USB Keyboard");
+        // no language ids and no serial number (iSerialNumber is 0)
+        assert_eq!(read(0).unwrap_err().kind(), io::ErrorKind::Unsupported);
+        assert_eq!(read(3).unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn custom_root_sends_no_control_transfers() {
+        // 2-2 has busnum and devnum like a copied sysfs tree, which must not
+        // reach whatever live device has those numbers now
+        let backend = backend();
+        let hub = OsStr::new("usb2");
+        let err = backend.descriptor(hub, 2, descriptor_type::BOS, 0, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        let request = ControlRequest { request_type: 0xC0, request: 0x01, value: 0, index: 0x07, length: 16 };
+        let err = backend.control_in(hub, 2, request).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        // strings still come from the attributes
+        let err = backend.hub_descriptor(OsStr::new("1-1")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        let bytes = backend.descriptor(hub, 2, descriptor_type::STRING, 2, 0x0409).unwrap();
+        assert_eq!(string::parse_string(&bytes).unwrap(), "ASMedia");
+    }
+}
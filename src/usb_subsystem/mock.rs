@@ -0,0 +1,302 @@
+use super::{
+    ConnectedDevice, ControlRequest, DeviceNode, DevicePnpStrings, DriverKey, HostController, HostControllers,
+    PortConnection, Speed, UsbBackend,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ffi::{OsStr, OsString};
+use std::io;
+
+// In-memory backend serving a synthetic topology, e.g.
+//
+// MockBackend::builder()
+//     .host_controller(MockHostController::new("hc0", MockHub::new("rh0", 4)
+//         .port(1, MockDevice::new("kbd").device_descriptor(DEVICE).configuration(CONFIG))))
+//     .build()
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    host_controllers: Vec<HostController>,
+    hubs: BTreeMap<OsString, MockHub>,
+    properties: HashMap<DriverKey, DevicePnpStrings>,
+}
+
+impl MockBackend {
+    pub fn builder() -> MockBackendBuilder {
+        MockBackendBuilder::default()
+    }
+
+    fn port(&self, hub: &OsStr, port: u8) -> io::Result<Option<&MockDevice>> {
+        let hub = self.hubs.get(hub)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such hub"))?;
+        Ok(hub.ports.get(&port))
+    }
+
+    fn device_node(&self, name: &OsStr, driver_key: &DriverKey) -> DeviceNode {
+        DeviceNode {
+            device_desc_name: self.properties.get(driver_key)
+                .and_then(|properties| properties.device_desc.clone()),
+            device_driver_name: Some(driver_key.as_os_str().to_owned()),
+            device_path: name.to_owned(),
+        }
+    }
+}
+
+impl UsbBackend for MockBackend {
+    fn host_controllers(&self) -> io::Result<HostControllers<'_>> {
+        Ok(HostControllers::new(self.host_controllers.iter().cloned().map(Ok)))
+    }
+
+    fn hubs(&self) -> io::Result<Vec<DeviceNode>> {
+        let root_hubs = self.host_controllers.iter()
+            .map(|hc| self.device_node(&hc.root_hub_name, &hc.driver_key));
+        let hubs = self.hubs.values()
+            .flat_map(|hub| hub.ports.values())
+            .filter_map(|device| device.hub.as_ref().map(|hub| self.device_node(&hub.name, &device.driver_key)));
+        Ok(root_hubs.chain(hubs).collect())
+    }
+
+    fn devices(&self) -> io::Result<Vec<DeviceNode>> {
+        Ok(self.hubs.values()
+            .flat_map(|hub| hub.ports.values())
+            .map(|device| self.device_node(device.driver_key.as_os_str(), &device.driver_key))
+            .collect())
+    }
+
+    fn hub_ports(&self, hub: &OsStr) -> io::Result<Vec<PortConnection>> {
+        let hub = self.hubs.get(hub)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such hub"))?;
+        let ports = (1..=hub.port_count).map(|port| PortConnection {
+            port,
+            device: hub.ports.get(&port).map(|device| ConnectedDevice {
+                driver_key: Some(device.driver_key.clone()),
+                speed: device.speed,
+                hub_name: device.hub.as_ref().map(|hub| hub.name.clone()),
+            }),
+        });
+        Ok(ports.collect())
+    }
+
+    fn hub_descriptor(&self, hub: &OsStr) -> io::Result<Vec<u8>> {
+        let hub = self.hubs.get(hub)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such hub"))?;
+        hub.descriptor.clone()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "hub has no hub descriptor"))
+    }
+
+    fn descriptor(
+        &self,
+        hub: &OsStr,
+        port: u8,
+        descriptor_type: u8,
+        index: u8,
+        language_id: u16,
+    ) -> io::Result<Vec<u8>> {
+        let device = self.port(hub, port)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no device connected to port"))?;
+        if device.unsupported_descriptors.contains(&(descriptor_type, index)) {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "descriptor type not readable"))
+        }
+        device.descriptors.get(&(descriptor_type, index, language_id))
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "device has no such descriptor"))
+    }
+
+    fn control_in(&self, hub: &OsStr, port: u8, request: ControlRequest) -> io::Result<Vec<u8>> {
+        let device = self.port(hub, port)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no device connected to port"))?;
+        let key = (request.request_type, request.request, request.value, request.index);
+        let mut bytes = device.control_requests.get(&key)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "device does not answer this request"))?;
+        bytes.truncate(usize::from(request.length));
+        Ok(bytes)
+    }
+
+    fn device_properties(&self, driver_key: &DriverKey) -> io::Result<Option<DevicePnpStrings>> {
+        Ok(self.properties.get(driver_key).cloned())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MockBackendBuilder {
+    host_controllers: Vec<MockHostController>,
+}
+
+impl MockBackendBuilder {
+    pub fn host_controller(mut self, host_controller: MockHostController) -> Self {
+        self.host_controllers.push(host_controller);
+        self
+    }
+
+    pub fn build(self) -> MockBackend {
+        let mut backend = MockBackend::default();
+        for host_controller in self.host_controllers {
+            backend.properties.insert(host_controller.driver_key.clone(), host_controller.properties);
+            backend.host_controllers.push(HostController {
+                path: host_controller.path,
+                root_hub_name: host_controller.root_hub.name.clone(),
+                driver_key: host_controller.driver_key,
+            });
+            add_hub(&mut backend, host_controller.root_hub);
+        }
+        backend
+    }
+}
+
+// register `hub` and every hub below it by name
+fn add_hub(backend: &mut MockBackend, hub: MockHub) {
+    for device in hub.ports.values() {
+        backend.properties.insert(device.driver_key.clone(), device.properties.clone());
+        if let Some(downstream) = &device.hub {
+            add_hub(backend, downstream.clone());
+        }
+    }
+    backend.hubs.insert(hub.name.clone(), hub);
+}
+
+#[derive(Debug, Clone)]
+pub struct MockHostController {
+    path: OsString,
+    driver_key: DriverKey,
+    properties: DevicePnpStrings,
+    root_hub: MockHub,
+}
+
+impl MockHostController {
+    pub fn new(driver_key: impl Into<OsString>, root_hub: MockHub) -> Self {
+        let driver_key = driver_key.into();
+        Self {
+            path: driver_key.clone(),
+            properties: pnp_strings(&driver_key),
+            driver_key: DriverKey::from(driver_key),
+            root_hub,
+        }
+    }
+
+    pub fn path(mut self, path: impl Into<OsString>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    pub fn properties(mut self, properties: DevicePnpStrings) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<OsString>) -> Self {
+        self.properties.device_desc = Some(description.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockHub {
+    name: OsString,
+    port_count: u8,
+    ports: BTreeMap<u8, MockDevice>,
+    descriptor: Option<Vec<u8>>,
+}
+
+impl MockHub {
+    pub fn new(name: impl Into<OsString>, port_count: u8) -> Self {
+        Self { name: name.into(), port_count, ports: BTreeMap::new(), descriptor: None }
+    }
+
+    // plug `device` into `port`; ports are numbered from 1
+    pub fn port(mut self, port: u8, device: MockDevice) -> Self {
+        assert!((1..=self.port_count).contains(&port), "port {} out of range", port);
+        self.ports.insert(port, device);
+        self
+    }
+
+    // raw bytes answered for the hub class GET_DESCRIPTOR request
+    pub fn descriptor(mut self, bytes: impl Into<Vec<u8>>) -> Self {
+        self.descriptor = Some(bytes.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockDevice {
+    driver_key: DriverKey,
+    properties: DevicePnpStrings,
+    speed: Option<Speed>,
+    descriptors: BTreeMap<(u8, u8, u16), Vec<u8>>, // (type, index, language id)
+    unsupported_descriptors: BTreeSet<(u8, u8)>, // (type, index) the backend cannot request at all
+    control_requests: BTreeMap<(u8, u8, u16, u16), Vec<u8>>, // (request type, request, value, index)
+    hub: Option<MockHub>,
+}
+
+impl MockDevice {
+    pub fn new(driver_key: impl Into<OsString>) -> Self {
+        let driver_key = driver_key.into();
+        Self {
+            properties: pnp_strings(&driver_key),
+            driver_key: DriverKey::from(driver_key),
+            speed: None,
+            descriptors: BTreeMap::new(),
+            unsupported_descriptors: BTreeSet::new(),
+            control_requests: BTreeMap::new(),
+            hub: None,
+        }
+    }
+
+    pub fn properties(mut self, properties: DevicePnpStrings) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<OsString>) -> Self {
+        self.properties.device_desc = Some(description.into());
+        self
+    }
+
+    pub fn speed(mut self, speed: Speed) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    pub fn device_descriptor(self, bytes: impl Into<Vec<u8>>) -> Self {
+        self.descriptor(0x01, 0, 0, bytes)
+    }
+
+    // configurations are indexed in the order they are added
+    pub fn configuration(self, bytes: impl Into<Vec<u8>>) -> Self {
+        let index = self.descriptors.range((0x02, 0, 0)..(0x03, 0, 0)).count() as u8;
+        self.descriptor(0x02, index, 0, bytes)
+    }
+
+    // raw bytes answered for GET_DESCRIPTOR(type, index) with wIndex `language_id`
+    pub fn descriptor(mut self, descriptor_type: u8, index: u8, language_id: u16, bytes: impl Into<Vec<u8>>) -> Self {
+        self.descriptors.insert((descriptor_type, index, language_id), bytes.into());
+        self
+    }
+
+    // fail GET_DESCRIPTOR(type, index) as Unsupported in every language,
+    // like a backend that only exposes some descriptors
+    pub fn unsupported_descriptor(mut self, descriptor_type: u8, index: u8) -> Self {
+        self.unsupported_descriptors.insert((descriptor_type, index));
+        self
+    }
+
+    // raw bytes answered for an IN control request, cut to its wLength
+    pub fn control_in(mut self, request_type: u8, request: u8, value: u16, index: u16, bytes: impl Into<Vec<u8>>) -> Self {
+        self.control_requests.insert((request_type, request, value, index), bytes.into());
+        self
+    }
+
+    // make this device a hub with its own downstream ports
+    pub fn hub(mut self, hub: MockHub) -> Self {
+        self.hub = Some(hub);
+        self
+    }
+}
+
+fn pnp_strings(device_id: &OsStr) -> DevicePnpStrings {
+    DevicePnpStrings {
+        device_id: device_id.to_owned(),
+        device_desc: None,
+        device_hw_id: None,
+        service: None,
+        device_class: None,
+    }
+}
@@ -0,0 +1,891 @@
+// Record every answer a backend gives into a `Snapshot`, save it as a versioned
+// text file and replay it later on any platform.
+//
+// The file starts with `usb-z-snapshot <version>` and holds one record per line;
+// strings are quoted, absent values are `-` and descriptors are hex encoded.
+// Names that are not valid Unicode keep their raw code units as escapes, `\xXX`
+// for a stray byte of a Unix name and `\uXXXX` for an unpaired surrogate of a
+// Windows name, so that replay looks them up exactly as recorded.
+use super::{
+    ConnectedDevice, ControlRequest, DeviceNode, DevicePnpStrings, DriverKey, HostController, HostControllers,
+    PortConnection, Speed, UsbBackend,
+};
+use core::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+const SNAPSHOT_MAGIC: &str = "usb-z-snapshot";
+
+// hub name, port, descriptor type, index, language id
+type DescriptorKey = (OsString, u8, u8, u8, u16);
+
+// hub name, port, request
+type ControlKey = (OsString, u8, ControlRequest);
+
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    host_controllers: Vec<Result<HostController, RecordedError>>,
+    hubs: Vec<DeviceNode>,
+    devices: Vec<DeviceNode>,
+    hub_ports: BTreeMap<OsString, Result<Vec<PortConnection>, RecordedError>>,
+    hub_descriptors: BTreeMap<OsString, Result<Vec<u8>, RecordedError>>,
+    descriptors: BTreeMap<DescriptorKey, Result<Vec<u8>, RecordedError>>,
+    control_requests: BTreeMap<ControlKey, Result<Vec<u8>, RecordedError>>,
+    properties: BTreeMap<OsString, Option<DevicePnpStrings>>,
+}
+
+#[derive(Debug, Clone)]
+struct RecordedError {
+    kind: io::ErrorKind,
+    message: String,
+}
+
+impl From<&io::Error> for RecordedError {
+    fn from(err: &io::Error) -> Self {
+        Self { kind: err.kind(), message: err.to_string() }
+    }
+}
+
+impl From<&RecordedError> for io::Error {
+    fn from(err: &RecordedError) -> Self {
+        io::Error::new(err.kind, err.message.clone())
+    }
+}
+
+fn record<T: Clone>(result: &io::Result<T>) -> Result<T, RecordedError> {
+    result.as_ref().cloned().map_err(RecordedError::from)
+}
+
+fn replay<T: Clone>(recorded: &Result<T, RecordedError>) -> io::Result<T> {
+    recorded.as_ref().cloned().map_err(io::Error::from)
+}
+
+// wraps a backend and remembers everything it answered
+pub struct RecordingBackend<'a> {
+    inner: &'a dyn UsbBackend,
+    snapshot: RefCell<Snapshot>,
+}
+
+impl<'a> RecordingBackend<'a> {
+    pub fn new(inner: &'a dyn UsbBackend) -> Self {
+        Self { inner, snapshot: RefCell::new(Snapshot::default()) }
+    }
+
+    pub fn into_snapshot(self) -> Snapshot {
+        self.snapshot.into_inner()
+    }
+}
+
+impl UsbBackend for RecordingBackend<'_> {
+    fn host_controllers(&self) -> io::Result<HostControllers<'_>> {
+        let host_controllers = self.inner.host_controllers()?;
+        self.snapshot.borrow_mut().host_controllers.clear();
+        Ok(HostControllers::new(host_controllers.inspect(move |host_controller| {
+            self.snapshot.borrow_mut().host_controllers.push(record(host_controller));
+        })))
+    }
+
+    fn hubs(&self) -> io::Result<Vec<DeviceNode>> {
+        let hubs = self.inner.hubs()?;
+        self.snapshot.borrow_mut().hubs = hubs.clone();
+        Ok(hubs)
+    }
+
+    fn devices(&self) -> io::Result<Vec<DeviceNode>> {
+        let devices = self.inner.devices()?;
+        self.snapshot.borrow_mut().devices = devices.clone();
+        Ok(devices)
+    }
+
+    fn hub_ports(&self, hub: &OsStr) -> io::Result<Vec<PortConnection>> {
+        let ports = self.inner.hub_ports(hub);
+        self.snapshot.borrow_mut().hub_ports.insert(hub.to_owned(), record(&ports));
+        ports
+    }
+
+    fn hub_descriptor(&self, hub: &OsStr) -> io::Result<Vec<u8>> {
+        let descriptor = self.inner.hub_descriptor(hub);
+        self.snapshot.borrow_mut().hub_descriptors.insert(hub.to_owned(), record(&descriptor));
+        descriptor
+    }
+
+    fn descriptor(
+        &self,
+        hub: &OsStr,
+        port: u8,
+        descriptor_type: u8,
+        index: u8,
+        language_id: u16,
+    ) -> io::Result<Vec<u8>> {
+        let descriptor = self.inner.descriptor(hub, port, descriptor_type, index, language_id);
+        let key = (hub.to_owned(), port, descriptor_type, index, language_id);
+        self.snapshot.borrow_mut().descriptors.insert(key, record(&descriptor));
+        descriptor
+    }
+
+    fn control_in(&self, hub: &OsStr, port: u8, request: ControlRequest) -> io::Result<Vec<u8>> {
+        let answer = self.inner.control_in(hub, port, request);
+        self.snapshot.borrow_mut().control_requests.insert((hub.to_owned(), port, request), record(&answer));
+        answer
+    }
+
+    fn device_properties(&self, driver_key: &DriverKey) -> io::Result<Option<DevicePnpStrings>> {
+        let properties = self.inner.device_properties(driver_key)?;
+        self.snapshot.borrow_mut().properties.insert(driver_key.as_os_str().to_owned(), properties.clone());
+        Ok(properties)
+    }
+}
+
+// serves a snapshot as if it were the live system
+#[derive(Debug, Clone)]
+pub struct ReplayBackend {
+    snapshot: Snapshot,
+}
+
+impl ReplayBackend {
+    pub fn new(snapshot: Snapshot) -> Self {
+        Self { snapshot }
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(Self::new(Snapshot::read_from(&mut BufReader::new(file))?))
+    }
+}
+
+fn not_recorded(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{} was not recorded in the snapshot", what))
+}
+
+impl UsbBackend for ReplayBackend {
+    fn host_controllers(&self) -> io::Result<HostControllers<'_>> {
+        Ok(HostControllers::new(self.snapshot.host_controllers.iter().map(replay)))
+    }
+
+    fn hubs(&self) -> io::Result<Vec<DeviceNode>> {
+        Ok(self.snapshot.hubs.clone())
+    }
+
+    fn devices(&self) -> io::Result<Vec<DeviceNode>> {
+        Ok(self.snapshot.devices.clone())
+    }
+
+    fn hub_ports(&self, hub: &OsStr) -> io::Result<Vec<PortConnection>> {
+        self.snapshot.hub_ports.get(hub)
+            .map(replay)
+            .unwrap_or_else(|| Err(not_recorded("hub")))
+    }
+
+    fn hub_descriptor(&self, hub: &OsStr) -> io::Result<Vec<u8>> {
+        self.snapshot.hub_descriptors.get(hub)
+            .map(replay)
+            .unwrap_or_else(|| Err(not_recorded("hub descriptor")))
+    }
+
+    fn descriptor(
+        &self,
+        hub: &OsStr,
+        port: u8,
+        descriptor_type: u8,
+        index: u8,
+        language_id: u16,
+    ) -> io::Result<Vec<u8>> {
+        let key = (hub.to_owned(), port, descriptor_type, index, language_id);
+        self.snapshot.descriptors.get(&key)
+            .map(replay)
+            .unwrap_or_else(|| Err(not_recorded("descriptor")))
+    }
+
+    fn control_in(&self, hub: &OsStr, port: u8, request: ControlRequest) -> io::Result<Vec<u8>> {
+        self.snapshot.control_requests.get(&(hub.to_owned(), port, request))
+            .map(replay)
+            .unwrap_or_else(|| Err(not_recorded("control request")))
+    }
+
+    fn device_properties(&self, driver_key: &DriverKey) -> io::Result<Option<DevicePnpStrings>> {
+        Ok(self.snapshot.properties.get(driver_key.as_os_str()).cloned().flatten())
+    }
+}
+
+impl Snapshot {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = io::BufWriter::new(File::create(path)?);
+        self.write_to(&mut file)?;
+        file.flush()
+    }
+
+    pub fn write_to(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{} {}", SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+        for host_controller in &self.host_controllers {
+            let line = match host_controller {
+                Ok(host_controller) => Line::new("host-controller")
+                    .string(&host_controller.path)
+                    .string(host_controller.driver_key.as_os_str())
+                    .string(&host_controller.root_hub_name),
+                Err(err) => Line::new("host-controller-error").error(err),
+            };
+            writeln!(out, "{}", line.0)?;
+        }
+        for (record, nodes) in [("hub-node", &self.hubs), ("device-node", &self.devices)] {
+            for node in nodes {
+                let line = Line::new(record)
+                    .optional(node.device_desc_name.as_deref())
+                    .optional(node.device_driver_name.as_deref())
+                    .string(&node.device_path);
+                writeln!(out, "{}", line.0)?;
+            }
+        }
+        for (driver_key, properties) in &self.properties {
+            let line = match properties {
+                Some(properties) => Line::new("properties")
+                    .string(driver_key)
+                    .string(&properties.device_id)
+                    .optional(properties.device_desc.as_deref())
+                    .optional(properties.device_hw_id.as_deref())
+                    .optional(properties.service.as_deref())
+                    .optional(properties.device_class.as_deref()),
+                None => Line::new("no-properties").string(driver_key),
+            };
+            writeln!(out, "{}", line.0)?;
+        }
+        for (hub, ports) in &self.hub_ports {
+            let ports = match ports {
+                Ok(ports) => ports,
+                Err(err) => {
+                    writeln!(out, "{}", Line::new("hub-error").string(hub).error(err).0)?;
+                    continue;
+                },
+            };
+            writeln!(out, "{}", Line::new("hub").string(hub).0)?;
+            for connection in ports {
+                let line = Line::new("port").string(hub).number(connection.port);
+                let line = match &connection.device {
+                    Some(device) => line
+                        .optional(device.driver_key.as_ref().map(DriverKey::as_os_str))
+                        .word(device.speed.map_or("-", speed_name))
+                        .optional(device.hub_name.as_deref()),
+                    None => line.word("empty"),
+                };
+                writeln!(out, "{}", line.0)?;
+            }
+        }
+        for (hub, descriptor) in &self.hub_descriptors {
+            let line = match descriptor {
+                Ok(bytes) => Line::new("hub-descriptor").string(hub).hex(bytes),
+                Err(err) => Line::new("hub-descriptor-error").string(hub).error(err),
+            };
+            writeln!(out, "{}", line.0)?;
+        }
+        for ((hub, port, descriptor_type, index, language_id), descriptor) in &self.descriptors {
+            let line = |record| Line::new(record)
+                .string(hub)
+                .number(*port)
+                .number(*descriptor_type)
+                .number(*index)
+                .number(*language_id);
+            let line = match descriptor {
+                Ok(bytes) => line("descriptor").hex(bytes),
+                Err(err) => line("descriptor-error").error(err),
+            };
+            writeln!(out, "{}", line.0)?;
+        }
+        for ((hub, port, request), answer) in &self.control_requests {
+            let line = |record| Line::new(record)
+                .string(hub)
+                .number(*port)
+                .number(request.request_type)
+                .number(request.request)
+                .number(request.value)
+                .number(request.index)
+                .number(request.length);
+            let line = match answer {
+                Ok(bytes) => line("control-in").hex(bytes),
+                Err(err) => line("control-in-error").error(err),
+            };
+            writeln!(out, "{}", line.0)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from(input: &mut dyn BufRead) -> io::Result<Self> {
+        let mut lines = input.lines().enumerate();
+        let header = lines.next().map(|(_, line)| line).transpose()?.unwrap_or_default();
+        match header.split_once(' ') {
+            Some((SNAPSHOT_MAGIC, version)) if version.trim() == SNAPSHOT_VERSION.to_string() => {},
+            Some((SNAPSHOT_MAGIC, version)) => return Err(invalid_data(format!(
+                "unsupported snapshot version {}", version.trim()
+            ))),
+            _ => return Err(invalid_data("not a usb-z snapshot".to_owned())),
+        }
+        let mut snapshot = Snapshot::default();
+        for (index, line) in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            snapshot.read_line(&line)
+                .map_err(|message| invalid_data(format!("snapshot line {}: {}", index + 1, message)))?;
+        }
+        Ok(snapshot)
+    }
+
+    fn read_line(&mut self, line: &str) -> Result<(), String> {
+        let mut fields = Fields::parse(line)?;
+        let record = fields.word()?;
+        match record.as_str() {
+            "host-controller" => self.host_controllers.push(Ok(HostController {
+                path: fields.string()?,
+                driver_key: DriverKey::from(fields.string()?),
+                root_hub_name: fields.string()?,
+            })),
+            "host-controller-error" => self.host_controllers.push(Err(fields.error()?)),
+            "hub-node" | "device-node" => {
+                let node = DeviceNode {
+                    device_desc_name: fields.optional()?,
+                    device_driver_name: fields.optional()?,
+                    device_path: fields.string()?,
+                };
+                if record == "hub-node" { &mut self.hubs } else { &mut self.devices }.push(node);
+            },
+            "properties" => {
+                let driver_key = fields.string()?;
+                let properties = DevicePnpStrings {
+                    device_id: fields.string()?,
+                    device_desc: fields.optional()?,
+                    device_hw_id: fields.optional()?,
+                    service: fields.optional()?,
+                    device_class: fields.optional()?,
+                };
+                self.properties.insert(driver_key, Some(properties));
+            },
+            "no-properties" => {
+                self.properties.insert(fields.string()?, None);
+            },
+            "hub" => {
+                self.hub_ports.insert(fields.string()?, Ok(Vec::new()));
+            },
+            "hub-error" => {
+                let hub = fields.string()?;
+                self.hub_ports.insert(hub, Err(fields.error()?));
+            },
+            "port" => {
+                let hub = fields.string()?;
+                let port = fields.number()?;
+                let device = if fields.peek_word() == Some("empty") {
+                    None
+                } else {
+                    Some(ConnectedDevice {
+                        driver_key: fields.optional()?.map(DriverKey::from),
+                        speed: match fields.word()?.as_str() {
+                            "-" => None,
+                            name => Some(parse_speed(name)?),
+                        },
+                        hub_name: fields.optional()?,
+                    })
+                };
+                match self.hub_ports.get_mut(&hub) {
+                    Some(Ok(ports)) => ports.push(PortConnection { port, device }),
+                    _ => return Err("port record before its hub record".to_owned()),
+                }
+            },
+            "hub-descriptor" | "hub-descriptor-error" => {
+                let hub = fields.string()?;
+                let descriptor = if record == "hub-descriptor" {
+                    Ok(fields.hex()?)
+                } else {
+                    Err(fields.error()?)
+                };
+                self.hub_descriptors.insert(hub, descriptor);
+            },
+            "descriptor" | "descriptor-error" => {
+                let key = (fields.string()?, fields.number()?, fields.number()?, fields.number()?, fields.number()?);
+                let descriptor = if record == "descriptor" {
+                    Ok(fields.hex()?)
+                } else {
+                    Err(fields.error()?)
+                };
+                self.descriptors.insert(key, descriptor);
+            },
+            "control-in" | "control-in-error" => {
+                let hub = fields.string()?;
+                let port = fields.number()?;
+                let request = ControlRequest {
+                    request_type: fields.number()?,
+                    request: fields.number()?,
+                    value: fields.number()?,
+                    index: fields.number()?,
+                    length: fields.number()?,
+                };
+                let answer = if record == "control-in" {
+                    Ok(fields.hex()?)
+                } else {
+                    Err(fields.error()?)
+                };
+                self.control_requests.insert((hub, port, request), answer);
+            },
+            _ => return Err(format!("unknown record {:?}", record)),
+        }
+        Ok(())
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn speed_name(speed: Speed) -> &'static str {
+    match speed {
+        Speed::Low => "low",
+        Speed::Full => "full",
+        Speed::High => "high",
+        Speed::Super => "super",
+        Speed::SuperPlus => "super-plus",
+    }
+}
+
+fn parse_speed(name: &str) -> Result<Speed, String> {
+    match name {
+        "low" => Ok(Speed::Low),
+        "full" => Ok(Speed::Full),
+        "high" => Ok(Speed::High),
+        "super" => Ok(Speed::Super),
+        "super-plus" => Ok(Speed::SuperPlus),
+        _ => Err(format!("unknown speed {:?}", name)),
+    }
+}
+
+// error kinds a backend is expected to report; anything else is kept as `Other`
+const ERROR_KINDS: &[(io::ErrorKind, &str)] = &[
+    (io::ErrorKind::NotFound, "not-found"),
+    (io::ErrorKind::PermissionDenied, "permission-denied"),
+    (io::ErrorKind::Unsupported, "unsupported"),
+    (io::ErrorKind::InvalidInput, "invalid-input"),
+    (io::ErrorKind::InvalidData, "invalid-data"),
+    (io::ErrorKind::TimedOut, "timed-out"),
+    (io::ErrorKind::OutOfMemory, "out-of-memory"),
+    (io::ErrorKind::Other, "other"),
+];
+
+// builds one record line
+struct Line(String);
+
+impl Line {
+    fn new(record: &str) -> Self {
+        Line(record.to_owned())
+    }
+
+    fn word(mut self, word: &str) -> Self {
+        self.0.push(' ');
+        self.0.push_str(word);
+        self
+    }
+
+    fn number(self, number: impl Into<u32>) -> Self {
+        let number = number.into().to_string();
+        self.word(&number)
+    }
+
+    fn string(mut self, string: &OsStr) -> Self {
+        self.0.push_str(" \"");
+        for c in os_chars(string) {
+            match c {
+                Ok('"') => self.0.push_str("\\\""),
+                Ok('\\') => self.0.push_str("\\\\"),
+                Ok('\n') => self.0.push_str("\\n"),
+                Ok('\r') => self.0.push_str("\\r"),
+                Ok('\t') => self.0.push_str("\\t"),
+                Ok(c) => self.0.push(c),
+                Err(RawUnit::Byte(byte)) => { let _ = write!(self.0, "\\x{:02x}", byte); },
+                Err(RawUnit::Surrogate(unit)) => { let _ = write!(self.0, "\\u{:04x}", unit); },
+            }
+        }
+        self.0.push('"');
+        self
+    }
+
+    fn optional(self, string: Option<&OsStr>) -> Self {
+        match string {
+            Some(string) => self.string(string),
+            None => self.word("-"),
+        }
+    }
+
+    fn hex(mut self, bytes: &[u8]) -> Self {
+        self.0.push(' ');
+        for byte in bytes {
+            let _ = write!(self.0, "{:02x}", byte);
+        }
+        if bytes.is_empty() {
+            self.0.push('-');
+        }
+        self
+    }
+
+    fn error(self, err: &RecordedError) -> Self {
+        let kind = ERROR_KINDS.iter()
+            .find(|(kind, _)| *kind == err.kind)
+            .map_or("other", |(_, name)| name);
+        self.word(kind).string(OsStr::new(&err.message))
+    }
+}
+
+// splits one record line into words and quoted strings
+struct Fields {
+    fields: std::vec::IntoIter<Field>,
+    peeked: Option<Field>,
+}
+
+enum Field {
+    Word(String),
+    Quoted(OsString),
+}
+
+impl Fields {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut fields = Vec::new();
+        let mut chars = line.chars().peekable();
+        loop {
+            while chars.next_if(|c| *c == ' ').is_some() {}
+            match chars.peek() {
+                None => break,
+                Some('"') => {
+                    chars.next();
+                    let mut string = OsStringBuilder::default();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some('\\') => match chars.next() {
+                                Some('n') => string.push('\n'),
+                                Some('r') => string.push('\r'),
+                                Some('t') => string.push('\t'),
+                                Some(c @ ('"' | '\\')) => string.push(c),
+                                Some('x') => match hex_escape(&mut chars, 2) {
+                                    Some(byte @ 0x80..=0xFF) => string.push_raw(RawUnit::Byte(byte as u8)),
+                                    _ => return Err("bad escape in string".to_owned()),
+                                },
+                                Some('u') => match hex_escape(&mut chars, 4) {
+                                    Some(unit @ 0xD800..=0xDFFF) => string.push_raw(RawUnit::Surrogate(unit as u16)),
+                                    _ => return Err("bad escape in string".to_owned()),
+                                },
+                                _ => return Err("bad escape in string".to_owned()),
+                            },
+                            Some(c) => string.push(c),
+                            None => return Err("unterminated string".to_owned()),
+                        }
+                    }
+                    fields.push(Field::Quoted(string.finish()));
+                },
+                Some(_) => {
+                    let mut word = String::new();
+                    while let Some(c) = chars.next_if(|c| *c != ' ') {
+                        word.push(c);
+                    }
+                    fields.push(Field::Word(word));
+                },
+            }
+        }
+        Ok(Fields { fields: fields.into_iter(), peeked: None })
+    }
+
+    fn next(&mut self) -> Result<Field, String> {
+        self.peeked.take()
+            .or_else(|| self.fields.next())
+            .ok_or_else(|| "missing field".to_owned())
+    }
+
+    fn peek_word(&mut self) -> Option<&str> {
+        if self.peeked.is_none() {
+            self.peeked = self.fields.next();
+        }
+        match &self.peeked {
+            Some(Field::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    fn word(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Field::Word(word) => Ok(word),
+            Field::Quoted(_) => Err("expected a word, found a string".to_owned()),
+        }
+    }
+
+    fn string(&mut self) -> Result<OsString, String> {
+        match self.next()? {
+            Field::Quoted(string) => Ok(string),
+            Field::Word(_) => Err("expected a string".to_owned()),
+        }
+    }
+
+    fn optional(&mut self) -> Result<Option<OsString>, String> {
+        match self.next()? {
+            Field::Quoted(string) => Ok(Some(string)),
+            Field::Word(word) if word == "-" => Ok(None),
+            Field::Word(_) => Err("expected a string or -".to_owned()),
+        }
+    }
+
+    fn number<T: TryFrom<u32>>(&mut self) -> Result<T, String> {
+        let word = self.word()?;
+        word.parse::<u32>().ok()
+            .and_then(|number| T::try_from(number).ok())
+            .ok_or_else(|| format!("bad number {:?}", word))
+    }
+
+    fn hex(&mut self) -> Result<Vec<u8>, String> {
+        let word = self.word()?;
+        if word == "-" {
+            return Ok(Vec::new())
+        }
+        if !word.is_ascii() || word.len() % 2 != 0 {
+            return Err(format!("bad hex {:?}", word))
+        }
+        (0..word.len()).step_by(2)
+            .map(|i| u8::from_str_radix(&word[i..i + 2], 16).map_err(|_| format!("bad hex {:?}", word)))
+            .collect()
+    }
+
+    fn error(&mut self) -> Result<RecordedError, String> {
+        let name = self.word()?;
+        let kind = ERROR_KINDS.iter()
+            .find(|(_, known)| *known == name)
+            .map_or(io::ErrorKind::Other, |(kind, _)| *kind);
+        let message = self.string()?.to_string_lossy().into_owned();
+        Ok(RecordedError { kind, message })
+    }
+}
+
+// a code unit of an OS string that is not part of any character
+#[derive(Debug, Clone, Copy)]
+enum RawUnit {
+    Byte(u8), // invalid UTF-8 in a Unix name
+    Surrogate(u16), // an unpaired surrogate in a Windows name
+}
+
+#[cfg(unix)]
+fn os_chars(string: &OsStr) -> Vec<Result<char, RawUnit>> {
+    use std::os::unix::ffi::OsStrExt;
+    string.as_bytes().utf8_chunks()
+        .flat_map(|chunk| {
+            chunk.valid().chars().map(Ok).chain(chunk.invalid().iter().map(|&byte| Err(RawUnit::Byte(byte))))
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn os_chars(string: &OsStr) -> Vec<Result<char, RawUnit>> {
+    use std::os::windows::ffi::OsStrExt;
+    char::decode_utf16(string.encode_wide())
+        .map(|c| c.map_err(|err| RawUnit::Surrogate(err.unpaired_surrogate())))
+        .collect()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_chars(string: &OsStr) -> Vec<Result<char, RawUnit>> {
+    string.to_string_lossy().chars().map(Ok).collect()
+}
+
+// `digits` hex digits following an escape
+fn hex_escape(chars: &mut impl Iterator<Item = char>, digits: usize) -> Option<u32> {
+    let hex: String = chars.take(digits).collect();
+    match hex.len() == digits {
+        true => u32::from_str_radix(&hex, 16).ok(),
+        false => None,
+    }
+}
+
+// a string read back from a snapshot. Raw code units recorded on the other
+// platform are kept the way that platform would: a surrogate as its three byte
+// generalized UTF-8 form on Unix, a byte as the low surrogate 0xDC00 + byte on
+// Windows. Either way every mention of a name reads back the same.
+#[derive(Default)]
+struct OsStringBuilder {
+    #[cfg(unix)]
+    units: Vec<u8>,
+    #[cfg(windows)]
+    units: Vec<u16>,
+    #[cfg(not(any(unix, windows)))]
+    units: String,
+}
+
+impl OsStringBuilder {
+    fn push(&mut self, c: char) {
+        #[cfg(unix)]
+        self.units.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        #[cfg(windows)]
+        self.units.extend_from_slice(c.encode_utf16(&mut [0; 2]));
+        #[cfg(not(any(unix, windows)))]
+        self.units.push(c);
+    }
+
+    fn push_raw(&mut self, raw: RawUnit) {
+        #[cfg(unix)]
+        match raw {
+            RawUnit::Byte(byte) => self.units.push(byte),
+            RawUnit::Surrogate(unit) => self.units.extend_from_slice(&[
+                0xE0 | (unit >> 12) as u8,
+                0x80 | (unit >> 6 & 0x3F) as u8,
+                0x80 | (unit & 0x3F) as u8,
+            ]),
+        }
+        #[cfg(windows)]
+        match raw {
+            RawUnit::Byte(byte) => self.units.push(0xDC00 | u16::from(byte)),
+            RawUnit::Surrogate(unit) => self.units.push(unit),
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            let _ = raw;
+            self.units.push(char::REPLACEMENT_CHARACTER);
+        }
+    }
+
+    fn finish(self) -> OsString {
+        #[cfg(unix)]
+        return std::os::unix::ffi::OsStringExt::from_vec(self.units);
+        #[cfg(windows)]
+        return std::os::windows::ffi::OsStringExt::from_wide(&self.units);
+        #[cfg(not(any(unix, windows)))]
+        return OsString::from(self.units);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::{print_host_controllers, TreeOptions};
+    use crate::usb_subsystem::mock::{MockBackend, MockDevice, MockHostController, MockHub};
+
+    const DEVICE: [u8; 18] = [
+        0x12, 0x01, 0x01, 0x02, 0x00, 0x00, 0x00, 0x40, 0x6D, 0x04, 0x1C, 0xC5, 0x00, 0x01, 0x01, 0x02, 0x00, 0x01,
+    ];
+
+    // names that need quoting and escaping, a device without speed, empty
+    // ports, an empty descriptor and recorded errors
+    fn backend() -> MockBackend {
+        MockBackend::builder()
+            .host_controller(MockHostController::new("hc \"0\"", MockHub::new("C:\\root hub", 3)
+                .port(1, MockDevice::new("key\twith\ncontrol\rcharacters").description("\"Quoted\" \\ Device")
+                    .device_descriptor(DEVICE)
+                    .descriptor(0x0F, 0, 0, [])
+                    .unsupported_descriptor(0x03, 0))
+                .port(3, MockDevice::new("hub").speed(Speed::High).hub(MockHub::new("hub \"1\"", 2)
+                    .descriptor([0x09, 0x29, 0x02, 0x00, 0x00, 0x32, 0x64, 0x00, 0xFF])))))
+            .build()
+    }
+
+    fn tree(backend: &dyn UsbBackend) -> String {
+        let mut out = Vec::new();
+        let options = TreeOptions { verbose: true, ..TreeOptions::default() };
+        print_host_controllers(backend, &mut out, &options).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    // the tree printed through a recording of `backend`, and the snapshot text
+    fn record(backend: &dyn UsbBackend) -> (String, String) {
+        let recording = RecordingBackend::new(backend);
+        let tree = tree(&recording);
+        (tree, write(&recording.into_snapshot()))
+    }
+
+    fn write(snapshot: &Snapshot) -> String {
+        let mut out = Vec::new();
+        snapshot.write_to(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn read(text: &str) -> io::Result<Snapshot> {
+        Snapshot::read_from(&mut text.as_bytes())
+    }
+
+    #[test]
+    fn round_trip() {
+        let (live, text) = record(&backend());
+        let snapshot = read(&text).unwrap();
+        assert_eq!(write(&snapshot), text);
+        assert_eq!(tree(&ReplayBackend::new(snapshot)), live);
+    }
+
+    #[test]
+    fn quotes_and_escapes_strings() {
+        let (_, text) = record(&backend());
+        let lines: Vec<_> = text.lines().collect();
+        assert!(lines.contains(&r#"hub "C:\\root hub""#), "{}", text);
+        assert!(lines.contains(&r#"host-controller "hc \"0\"" "hc \"0\"" "C:\\root hub""#), "{}", text);
+        assert!(text.contains(r#""key\twith\ncontrol\rcharacters""#), "{}", text);
+        assert!(text.contains(r#""\"Quoted\" \\ Device""#), "{}", text);
+    }
+
+    #[test]
+    fn keeps_absent_values() {
+        let (_, text) = record(&backend());
+        let lines: Vec<_> = text.lines().collect();
+        // no speed and no downstream hub, then nothing plugged in
+        assert!(lines.contains(&r#"port "C:\\root hub" 1 "key\twith\ncontrol\rcharacters" - -"#), "{}", text);
+        assert!(lines.contains(&r#"port "C:\\root hub" 2 empty"#), "{}", text);
+        assert!(lines.contains(&r#"descriptor "C:\\root hub" 1 15 0 0 -"#), "{}", text);
+        assert!(lines.iter().any(|line| line.starts_with(r#"descriptor-error "C:\\root hub" 1 3 0 0 unsupported "#)));
+
+        let replay = ReplayBackend::new(read(&text).unwrap());
+        let ports = replay.hub_ports(OsStr::new("C:\\root hub")).unwrap();
+        let device = ports[0].device.as_ref().unwrap();
+        assert_eq!((device.speed, device.hub_name.as_deref()), (None, None));
+        assert!(ports[1].device.is_none());
+        assert_eq!(ports[2].device.as_ref().unwrap().speed, Some(Speed::High));
+        assert_eq!(replay.descriptor(OsStr::new("C:\\root hub"), 1, 0x0F, 0, 0).unwrap(), []);
+        let err = replay.descriptor(OsStr::new("C:\\root hub"), 1, 0x03, 0, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let err = read("usb-z-snapshot 2\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "unsupported snapshot version 2");
+        let err = read("usbview 1\n").unwrap_err();
+        assert_eq!(err.to_string(), "not a usb-z snapshot");
+        assert!(read("").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let err = read("usb-z-snapshot 1\n\nhub \"rh0\n").unwrap_err();
+        assert_eq!(err.to_string(), "snapshot line 3: unterminated string");
+        let err = read("usb-z-snapshot 1\nport \"rh0\" 1 empty\n").unwrap_err();
+        assert_eq!(err.to_string(), "snapshot line 2: port record before its hub record");
+        let err = read("usb-z-snapshot 1\ndescriptor \"rh0\" 1 1 0 0 abc\n").unwrap_err();
+        assert_eq!(err.to_string(), "snapshot line 2: bad hex \"abc\"");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_names_that_are_not_unicode() {
+        use std::os::unix::ffi::{OsStrExt, OsStringExt};
+        let hub = OsString::from_vec(b"hub \xff\xfe".to_vec());
+        let backend = MockBackend::builder()
+            .host_controller(MockHostController::new("hc0", MockHub::new("rh0", 1)
+                .port(1, MockDevice::new(OsString::from_vec(b"key\x80".to_vec())).hub(MockHub::new(hub.clone(), 1)
+                    .port(1, MockDevice::new("kbd").device_descriptor(DEVICE))))))
+            .build();
+        let (live, text) = record(&backend);
+        assert!(text.contains(r#""hub \xff\xfe""#), "{}", text);
+        assert!(text.contains(r#""key\x80""#), "{}", text);
+        let snapshot = read(&text).unwrap();
+        assert_eq!(write(&snapshot), text);
+        let replay = ReplayBackend::new(snapshot);
+        assert_eq!(replay.hub_ports(&hub).unwrap()[0].device.as_ref().unwrap().driver_key, Some(DriverKey::from(OsString::from("kbd"))));
+        assert_eq!(tree(&replay), live);
+
+        // an unpaired surrogate of a Windows name reads back as generalized UTF-8
+        let snapshot = read("usb-z-snapshot 1\nhub \"rh\\ud800\"\n").unwrap();
+        assert_eq!(snapshot.hub_ports.keys().next().map(|name| name.as_bytes()), Some(&b"rh\xed\xa0\x80"[..]));
+        let err = read("usb-z-snapshot 1\nhub \"rh\\x41\"\n").unwrap_err();
+        assert_eq!(err.to_string(), "snapshot line 2: bad escape in string");
+    }
+}
@@ -0,0 +1,345 @@
+use crate::get_device_property::get_device_property;
+use crate::host_controller;
+use crate::hub::Hub;
+use crate::winusb::WinUsbDevice;
+use super::{
+    ConnectedDevice, ControlRequest, DeviceNode, DevicePnpStrings, DriverKey, HostController, HostControllers,
+    PortConnection, Speed, UsbBackend,
+};
+
+use winapi::{
+    shared::{guiddef::GUID, usbiodef::*, minwindef::*, winerror::*},
+    um::{errhandlingapi::*, setupapi::*, handleapi::*, heapapi::*},
+};
+use core::{
+    mem::{size_of, MaybeUninit},
+    ptr::NonNull
+};
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::os::windows::prelude::*;
+
+// SetupDi and DeviceIoControl based backend
+#[derive(Debug, Default)]
+pub struct WindowsBackend {
+    _private: (),
+}
+
+impl WindowsBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl UsbBackend for WindowsBackend {
+    fn host_controllers(&self) -> io::Result<HostControllers<'_>> {
+        let host_controllers = host_controller::host_controllers()?
+            .map(|host_controller| {
+                let host_controller = host_controller?;
+                Ok(HostController {
+                    path: host_controller.path().clone(),
+                    driver_key: host_controller.driver_key()?,
+                    root_hub_name: host_controller.root_hub_name()?,
+                })
+            });
+        Ok(HostControllers::new(host_controllers))
+    }
+
+    fn hubs(&self) -> io::Result<Vec<DeviceNode>> {
+        enumerate_all_devices_with_guid(&GUID_DEVINTERFACE_USB_HUB as *const _)
+    }
+
+    fn devices(&self) -> io::Result<Vec<DeviceNode>> {
+        enumerate_all_devices_with_guid(&GUID_DEVINTERFACE_USB_DEVICE as *const _)
+    }
+
+    fn hub_ports(&self, hub: &OsStr) -> io::Result<Vec<PortConnection>> {
+        let hub = Hub::open(hub)?;
+        let ports = (1..=hub.port_count()?)
+            .map(|port| {
+                let driver_key = || hub.connection_driver_key_name(port).ok().map(DriverKey::from);
+                let information = match hub.connection_information(port) {
+                    Ok(information) => information,
+                    // a port that fails the IOCTL is a device only if it has a driver
+                    Err(_) => return PortConnection {
+                        port,
+                        device: driver_key().map(|driver_key| ConnectedDevice {
+                            driver_key: Some(driver_key),
+                            speed: None,
+                            hub_name: hub.connection_name(port).ok(),
+                        }),
+                    },
+                };
+                // NoDeviceConnected; anything else, a failed enumeration included, is a device
+                if information.connection_status == 0 {
+                    return PortConnection { port, device: None }
+                }
+                let hub_name = match information.device_is_hub {
+                    true => hub.connection_name(port).ok(),
+                    false => None,
+                };
+                PortConnection {
+                    port,
+                    device: Some(ConnectedDevice { driver_key: driver_key(), speed: speed(information.speed), hub_name }),
+                }
+            })
+            .collect();
+        Ok(ports)
+    }
+
+    fn hub_descriptor(&self, hub: &OsStr) -> io::Result<Vec<u8>> {
+        Hub::open(hub)?.hub_descriptor()
+    }
+
+    fn descriptor(
+        &self,
+        hub: &OsStr,
+        port: u8,
+        descriptor_type: u8,
+        index: u8,
+        language_id: u16,
+    ) -> io::Result<Vec<u8>> {
+        Hub::open(hub)?.descriptor(port, descriptor_type, index, language_id)
+    }
+
+    // through WinUSB, so only for devices bound to it
+    fn control_in(&self, hub: &OsStr, port: u8, request: ControlRequest) -> io::Result<Vec<u8>> {
+        let driver_key = Hub::open(hub)?.connection_driver_key_name(port)?;
+        let device = self.devices()?
+            .into_iter()
+            .find(|device| device.device_driver_name.as_deref() == Some(driver_key.as_os_str()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no device interface for the device on this port"))?;
+        WinUsbDevice::open(&device.device_path)?.control_in(request)
+    }
+
+    fn device_properties(&self, driver_key: &DriverKey) -> io::Result<Option<DevicePnpStrings>> {
+        Ok(driver_name_to_device_properties(driver_key.as_os_str()))
+    }
+}
+
+// USB_DEVICE_SPEED; SuperSpeedPlus is only told apart by the V2 connection IOCTL
+fn speed(speed: u8) -> Option<Speed> {
+    match speed {
+        0 => Some(Speed::Low),
+        1 => Some(Speed::Full),
+        2 => Some(Speed::High),
+        3 => Some(Speed::Super),
+        _ => None,
+    }
+}
+
+fn enumerate_all_devices_with_guid(guid: *const GUID) -> io::Result<Vec<DeviceNode>> {
+    let device_info = unsafe {
+        SetupDiGetClassDevsW(
+            guid,
+            core::ptr::null(),
+            core::ptr::null_mut(),
+            DIGCF_PRESENT | DIGCF_DEVICEINTERFACE,
+        )
+    };
+    if device_info == INVALID_HANDLE_VALUE {
+        return Err(io::Error::last_os_error())
+    }
+    let mut index = 0;
+    let mut nodes = Vec::new();
+    let mut buf = vec![0; 100];
+    let ans = loop {
+        let mut device_info_data: MaybeUninit<SP_DEVINFO_DATA> = MaybeUninit::uninit();
+        unsafe { device_info_data.assume_init_mut() }.cbSize = size_of::<SP_DEVINFO_DATA>() as DWORD;
+        let success = unsafe {
+            SetupDiEnumDeviceInfo(
+                device_info,
+                index,
+                device_info_data.as_mut_ptr()
+            )
+        };
+        if success == FALSE {
+            break Ok(nodes);
+        }
+        let name_device_desc =
+            get_device_property(device_info, device_info_data.as_mut_ptr(), SPDRP_DEVICEDESC, &mut buf)
+            .ok();
+        let name_driver =
+            get_device_property(device_info, device_info_data.as_mut_ptr(), SPDRP_DRIVER, &mut buf)
+            .ok();
+
+        let mut device_interface_data = MaybeUninit::<SP_DEVICE_INTERFACE_DATA>::uninit();
+        unsafe { device_interface_data.assume_init_mut() }.cbSize = size_of::<SP_DEVICE_INTERFACE_DATA>() as DWORD;
+        let success = unsafe {
+            SetupDiEnumDeviceInterfaces(
+                device_info,
+                core::ptr::null_mut(),
+                guid,
+                index,
+                device_interface_data.as_mut_ptr()
+            )
+        };
+        if success == FALSE {
+            break Err(io::Error::last_os_error());
+        }
+        let mut required_length = MaybeUninit::uninit();
+        let success = unsafe {
+            SetupDiGetDeviceInterfaceDetailW(
+                device_info,
+                device_interface_data.as_mut_ptr(),
+                core::ptr::null_mut(),
+                0,
+                required_length.as_mut_ptr(),
+                core::ptr::null_mut()
+            )
+        };
+        if success == FALSE && unsafe { GetLastError() } != ERROR_INSUFFICIENT_BUFFER {
+            break Err(io::Error::last_os_error());
+        }
+        let heap_handle = unsafe { GetProcessHeap() };
+        let device_detail_data = NonNull::new(unsafe {
+            HeapAlloc(heap_handle, 0, required_length.assume_init() as usize) as *mut _
+        });
+        let mut device_detail_data = if let Some(device_detail_data) = device_detail_data {
+            device_detail_data.cast::<SP_DEVICE_INTERFACE_DETAIL_DATA_W>()
+        } else {
+            break Err(io::ErrorKind::OutOfMemory.into());
+        };
+        unsafe { device_detail_data.as_mut() }.cbSize = size_of::<SP_DEVICE_INTERFACE_DETAIL_DATA_W>() as DWORD;
+        let success = unsafe {
+            SetupDiGetDeviceInterfaceDetailW(
+                device_info,
+                device_interface_data.as_mut_ptr(),
+                device_detail_data.as_mut(),
+                *required_length.as_ptr(),
+                required_length.as_mut_ptr(),
+                core::ptr::null_mut()
+            )
+        };
+        if success == FALSE {
+            let err = io::Error::last_os_error();
+            unsafe { HeapFree(heap_handle, 0, device_detail_data.cast().as_ptr()) };
+            break Err(err);
+        }
+        let path = &unsafe { device_detail_data.as_ref() }.DevicePath;
+        let name_path = OsString::from_wide(unsafe { core::slice::from_raw_parts(
+            path as *const _ as *mut _,
+            required_length.assume_init() as usize / 2 - 3
+        ) });
+        unsafe { HeapFree(heap_handle, 0, device_detail_data.cast().as_ptr())};
+        nodes.push(DeviceNode {
+            device_desc_name: name_device_desc,
+            device_driver_name: name_driver,
+            device_path: name_path,
+        });
+
+        index += 1;
+    };
+    unsafe { SetupDiDestroyDeviceInfoList(device_info) };
+    ans
+}
+
+fn driver_name_to_device_inst(
+    driver_name: &OsStr,
+) -> Option<(HDEVINFO, SP_DEVINFO_DATA)> {
+    let device_info = unsafe {
+        SetupDiGetClassDevsW (
+            core::ptr::null(),
+            core::ptr::null(),
+            core::ptr::null_mut(),
+            DIGCF_ALLCLASSES | DIGCF_PRESENT
+        )
+    };
+    if device_info == INVALID_HANDLE_VALUE {
+        return None;
+    }
+    let mut index = 0;
+    let mut buf = vec![0; 300];
+    loop {
+        let mut device_info_data: MaybeUninit<SP_DEVINFO_DATA> = MaybeUninit::uninit();
+        unsafe { device_info_data.assume_init_mut() }.cbSize = size_of::<SP_DEVINFO_DATA>() as DWORD;
+        let success = unsafe {
+            SetupDiEnumDeviceInfo(
+                device_info,
+                index,
+                device_info_data.as_mut_ptr()
+            )
+        };
+        index += 1;
+        if success == FALSE {
+            break;
+        }
+        let buf_string = {
+            let tmp = get_device_property(device_info, device_info_data.as_mut_ptr(), SPDRP_DRIVER, &mut buf);
+            if let Ok(a) = tmp {
+                a
+            } else {
+                continue
+            }
+        };
+        if buf_string == driver_name {
+            // caller destroys the device info set
+            return Some((device_info, unsafe { device_info_data.assume_init() }));
+        }
+    }
+    unsafe { SetupDiDestroyDeviceInfoList(device_info) };
+    None
+}
+
+fn get_device_pnp_strings(
+    device_id: OsString,
+    device_info: HDEVINFO,
+    device_info_data: PSP_DEVINFO_DATA
+) -> DevicePnpStrings {
+    let mut buf = vec![0; 200];
+    let device_desc = get_device_property(device_info, device_info_data, SPDRP_DEVICEDESC, &mut buf).ok();
+    let device_hw_id = get_device_property(device_info, device_info_data, SPDRP_HARDWAREID, &mut buf).ok();
+    let service = get_device_property(device_info, device_info_data, SPDRP_SERVICE, &mut buf).ok();
+    let device_class = get_device_property(device_info, device_info_data, SPDRP_CLASS, &mut buf).ok();
+    DevicePnpStrings {
+        device_id,
+        device_desc,
+        device_hw_id,
+        service,
+        device_class,
+    }
+}
+
+fn driver_name_to_device_properties(
+    driver_name: &OsStr
+) -> Option<DevicePnpStrings> {
+    let (device_info, mut device_info_data) = driver_name_to_device_inst(driver_name)?;
+    let ans = device_instance_id(device_info, &mut device_info_data)
+        .map(|device_id| get_device_pnp_strings(device_id, device_info, &mut device_info_data));
+    unsafe { SetupDiDestroyDeviceInfoList(device_info) };
+    ans
+}
+
+fn device_instance_id(
+    device_info: HDEVINFO,
+    device_info_data: &mut SP_DEVINFO_DATA,
+) -> Option<OsString> {
+    let mut len = 0;
+    let success = unsafe {
+        SetupDiGetDeviceInstanceIdW(
+            device_info,
+            device_info_data,
+            core::ptr::null_mut(),
+            0,
+            &mut len
+        )
+    };
+    if success == FALSE && unsafe { GetLastError() } != ERROR_INSUFFICIENT_BUFFER {
+        return None;
+    }
+    let mut device_id_buf: Vec<u16> = vec![0; len as usize];
+    let success = unsafe {
+        SetupDiGetDeviceInstanceIdW(
+            device_info,
+            device_info_data,
+            device_id_buf.as_mut_ptr(),
+            len,
+            &mut len
+        )
+    };
+    if success == FALSE {
+        return None;
+    }
+    device_id_buf.truncate(len as usize - 1); // remove \0
+    Some(OsString::from_wide(&device_id_buf))
+}
@@ -0,0 +1,95 @@
+use crate::usb_subsystem::ControlRequest;
+use winapi::{
+    shared::{minwindef::*, winerror::*},
+    um::{errhandlingapi::GetLastError, fileapi::*, winbase::FILE_FLAG_OVERLAPPED, winnt::*, handleapi::*, winusb::*},
+};
+use core::fmt;
+use std::io;
+use std::ffi::{OsStr, OsString};
+use std::os::windows::ffi::OsStrExt;
+
+// a device bound to the WinUSB driver, the only one that lets user mode
+// issue arbitrary control transfers
+pub struct WinUsbDevice {
+    path: OsString, // device interface path
+    h_device: HANDLE,
+    h_winusb: WINUSB_INTERFACE_HANDLE,
+}
+
+impl WinUsbDevice {
+    // open a device by its GUID_DEVINTERFACE_USB_DEVICE path
+    pub fn open(path: &OsStr) -> io::Result<Self> {
+        let mut wide: Vec<u16> = path.encode_wide().collect();
+        wide.push(0);
+        let h_device = unsafe {
+            CreateFileW(
+                wide.as_ptr(),
+                GENERIC_READ | GENERIC_WRITE,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                core::ptr::null_mut(),
+                OPEN_EXISTING,
+                FILE_FLAG_OVERLAPPED,
+                core::ptr::null_mut(),
+            )
+        };
+        if h_device == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error())
+        }
+        let mut h_winusb = core::ptr::null_mut();
+        if unsafe { WinUsb_Initialize(h_device, &mut h_winusb) } == FALSE {
+            let code = unsafe { GetLastError() };
+            unsafe { CloseHandle(h_device) };
+            // any other function driver refuses WinUSB
+            return Err(match code {
+                ERROR_INVALID_FUNCTION | ERROR_NOT_SUPPORTED | ERROR_INVALID_HANDLE => io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "control transfers need the WinUSB driver",
+                ),
+                code => io::Error::from_raw_os_error(code as i32),
+            })
+        }
+        Ok(Self { path: path.to_owned(), h_device, h_winusb })
+    }
+
+    pub fn control_in(&self, request: ControlRequest) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0u8; usize::from(request.length)];
+        let setup_packet = WINUSB_SETUP_PACKET {
+            RequestType: request.request_type,
+            Request: request.request,
+            Value: request.value,
+            Index: request.index,
+            Length: request.length,
+        };
+        let mut transferred: ULONG = 0;
+        let success = unsafe {
+            WinUsb_ControlTransfer(
+                self.h_winusb,
+                setup_packet,
+                buf.as_mut_ptr(),
+                buf.len() as ULONG,
+                &mut transferred,
+                core::ptr::null_mut(),
+            )
+        };
+        if success == FALSE {
+            return Err(io::Error::last_os_error())
+        }
+        buf.truncate(transferred as usize);
+        Ok(buf)
+    }
+}
+
+impl fmt::Debug for WinUsbDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &self.path.to_string_lossy())
+    }
+}
+
+impl Drop for WinUsbDevice {
+    fn drop(&mut self) {
+        unsafe {
+            WinUsb_Free(self.h_winusb);
+            CloseHandle(self.h_device);
+        }
+    }
+}